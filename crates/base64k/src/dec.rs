@@ -60,7 +60,7 @@ impl<I: Iterator<Item = char>> io::Read for Decoder<I> {
                 },
                 [a] => {
                     *a = lo;
-                    self.buf = Some(hi);
+                    self.buf = has_hi.then_some(hi);
                     nread += 1;
                 },
                 _ if cfg!(debug_assertions) => unreachable!(),
@@ -116,6 +116,17 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_odd_bytewise() {
+        let mut dec = Decoder::new([encode2(b'o', b'd'), encode1(b'd')]);
+        let mut buf = vec![];
+        let mut byte = [0];
+        while dec.read(&mut byte).unwrap() != 0 {
+            buf.push(byte[0]);
+        }
+        assert_eq!(buf, b"odd");
+    }
+
     #[test]
     fn test_long() {
         let odd = b"the quick brown fox jumps over the lazy dog";
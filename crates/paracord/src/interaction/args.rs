@@ -0,0 +1,382 @@
+//! Typed command parameters, registered and extracted from a single definition
+//!
+//! Instead of describing a command's parameters with an [`ArgBuilder`] and
+//! then separately reading them back out of a
+//! [`CommandVisitor`](super::handler::CommandVisitor) by name, a handler can
+//! `#[derive(CommandArgs)]` on a type and use it for both.  A struct describes
+//! a flat list of parameters, one per field, and an enum describes a set of
+//! subcommands, one per variant.
+//!
+//! # Attributes
+//! Fields and variants are described by their doc comments, or by an explicit
+//! `#[arg(desc = "...")]`.  Names are taken from field names and snake-cased
+//! variant names unless overridden with `#[arg(rename = "...")]`.  Fields
+//! additionally accept:
+//! - `min = ...` and `max = ...` to bound numeric values (or string lengths)
+//! - `choices = ...` to restrict the value to an iterable of [`Choice`]s
+//! - `autocomplete` to enable autocomplete interactions
+//!
+//! Structs and enum variants may declare usage examples with one or more
+//! `#[arg(example = "...")]` attributes, written as the parameters a user
+//! would enter (see [`ArgBuilderExt::example`](super::command::ArgBuilderExt::example)).
+//!
+//! A field of type [`Option<T>`] registers an optional parameter.  An enum
+//! variant may be a unit variant (a subcommand with no parameters), have named
+//! fields (a subcommand with those parameters), or wrap a single
+//! [`CommandArgs`] type (a subcommand described by that type, which may itself
+//! be an enum to form a subcommand group).
+//!
+//! [`CommandArgs::visited`] describes what [`CommandArgs::from_visitor`]
+//! reads, which
+//! [`Registry::validate_commands`](super::registry::Registry::validate_commands)
+//! checks against the registered parameters of any handler reporting it
+//! through
+//! [`CommandHandler::visited_args`](super::handler::CommandHandler::visited_args).
+
+use std::ops::RangeInclusive;
+
+pub use qcore::CommandArgs;
+use qcore::build_range::BuildRange;
+use serenity::model::{
+    channel::{Attachment, PartialChannel},
+    guild::Role,
+    user::User,
+};
+
+use super::{
+    command::{prelude::*, ArgBuilder, Choice},
+    handler::CommandVisitor,
+    visitor::Error,
+};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A set of command parameters or subcommands which can register itself with
+/// an [`ArgBuilder`] and be extracted from a [`CommandVisitor`]
+///
+/// This trait should usually be implemented with `#[derive(CommandArgs)]`; see
+/// the [module documentation](self) for details.
+pub trait CommandArgs<'a>: Sized {
+    /// Register the parameters or subcommands described by this type
+    #[must_use]
+    fn register(builder: ArgBuilder) -> ArgBuilder;
+
+    /// Extract an instance of this type from a command invocation
+    ///
+    /// # Errors
+    /// This method returns an error if the invocation does not match the
+    /// registered parameters.
+    fn from_visitor(visitor: &mut CommandVisitor<'a>) -> Result<Self>;
+
+    #[doc(hidden)]
+    fn from_subcmd(visitor: &mut CommandVisitor<'a>, path: &[&'a str]) -> Result<Self>;
+
+    /// Describe the subcommands and parameters read by
+    /// [`from_visitor`](Self::from_visitor)
+    #[must_use]
+    fn visited() -> Visited;
+}
+
+/// The kind of value a parameter holds, regardless of its bounds or choices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A string
+    String,
+    /// An integer
+    Int,
+    /// A real (decimal) number
+    Real,
+    /// A Boolean
+    Bool,
+    /// A user
+    User,
+    /// A channel
+    Channel,
+    /// A role
+    Role,
+    /// A user or a role
+    Mention,
+    /// An attachment
+    Attachment,
+}
+
+/// A parameter read by [`CommandArgs::from_visitor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisitedArg {
+    /// The name of the parameter
+    pub name: &'static str,
+    /// The kind of value the parameter is read as
+    pub kind: ArgKind,
+    /// Whether reading fails if the parameter is absent
+    pub required: bool,
+}
+
+/// The subcommands and parameters read by [`CommandArgs::from_visitor`],
+/// shaped like the registered command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visited {
+    /// A set of named subcommands
+    Branch(Vec<(&'static str, Visited)>),
+    /// A list of parameters
+    Leaf(Vec<VisitedArg>),
+}
+
+/// Registration options for a single command parameter
+#[derive(Debug)]
+pub struct ArgSpec<B, C> {
+    /// The name of the parameter
+    pub name: &'static str,
+    /// The description of the parameter
+    pub desc: &'static str,
+    /// Whether the parameter must be provided
+    pub required: bool,
+    /// The minimum value (or length) of the parameter
+    pub min: Option<B>,
+    /// The maximum value (or length) of the parameter
+    pub max: Option<B>,
+    /// The values the parameter is restricted to, if any
+    pub choices: Option<Vec<Choice<C>>>,
+    /// Whether the parameter should send autocomplete interactions
+    pub autocomplete: bool,
+}
+
+/// A type which can be used as a single command parameter
+pub trait ArgValue<'a>: Sized {
+    /// The type of the bounds accepted by `min` and `max`
+    type Bound;
+    /// The type of the values accepted by `choices`
+    type Choice;
+    /// The kind of value this parameter is read as
+    const KIND: ArgKind;
+    /// Whether [`visit_arg`] fails if this parameter is absent
+    const REQUIRED: bool = true;
+
+    /// Register a parameter of this type
+    #[must_use]
+    fn register(builder: ArgBuilder, spec: ArgSpec<Self::Bound, Self::Choice>) -> ArgBuilder;
+
+    /// Extract a parameter of this type, returning `None` if it is absent
+    ///
+    /// # Errors
+    /// This method returns an error if the parameter is of the wrong type.
+    fn visit(visitor: &mut CommandVisitor<'a>, name: &'a str) -> Result<Option<Self>>;
+}
+
+/// Extract a parameter, returning an error if a required parameter is absent
+///
+/// # Errors
+/// This method returns an error if the parameter is missing or of the wrong
+/// type.
+#[inline]
+pub fn visit_arg<'a, T: ArgValue<'a>>(
+    visitor: &mut CommandVisitor<'a>,
+    name: &'a str,
+) -> Result<T> {
+    T::visit(visitor, name)?.ok_or_else(|| Error::MissingOption(name.into()))
+}
+
+struct Bounds<T>(Option<T>, Option<T>);
+
+impl<T> BuildRange<T> for Bounds<T> {
+    fn build_range(self) -> RangeInclusive<Option<T>> { self.0..=self.1 }
+}
+
+fn autocomplete(builder: ArgBuilder, name: &str, enable: bool) -> ArgBuilder {
+    if enable {
+        builder.autocomplete(true, [name])
+    } else {
+        builder
+    }
+}
+
+macro_rules! ranged {
+    (
+        $(
+            $ty:ty => $bound:ty, $choice:ty, $kind:ident,
+            $plain:ident, $choices:ident,
+            |$v:ident, $n:ident| $visit:expr;
+        )*
+    ) => {
+        $(
+            impl<'a> ArgValue<'a> for $ty {
+                type Bound = $bound;
+                type Choice = $choice;
+                const KIND: ArgKind = ArgKind::$kind;
+
+                fn register(builder: ArgBuilder, spec: ArgSpec<$bound, $choice>) -> ArgBuilder {
+                    let ArgSpec {
+                        name,
+                        desc,
+                        required,
+                        min,
+                        max,
+                        choices,
+                        autocomplete: ac,
+                    } = spec;
+
+                    let builder = match choices {
+                        Some(c) => builder.$choices(name, desc, required, c),
+                        None => builder.$plain(name, desc, required, Bounds(min, max)),
+                    };
+                    autocomplete(builder, name, ac)
+                }
+
+                #[inline]
+                fn visit($v: &mut CommandVisitor<'a>, $n: &'a str) -> Result<Option<Self>> {
+                    $visit
+                }
+            }
+        )*
+    };
+}
+
+ranged! {
+    &'a str => u16, String, String, string, string_choice,
+        |v, n| Ok(v.visit_string(n)?.optional().map(String::as_str));
+    String => u16, String, String, string, string_choice,
+        |v, n| Ok(v.visit_string(n)?.optional().cloned());
+    i64 => i64, i64, Int, int, int_choice,
+        |v, n| Ok(v.visit_i64(n)?.optional());
+    f64 => f64, f64, Real, real, real_choice,
+        |v, n| Ok(v.visit_number(n)?.optional());
+}
+
+macro_rules! simple {
+    (
+        $(
+            $ty:ty => $kind:ident, |$b:ident, $name:ident, $desc:ident, $req:ident| $reg:expr,
+            |$v:ident, $n:ident| $visit:expr;
+        )*
+    ) => {
+        $(
+            impl<'a> ArgValue<'a> for $ty {
+                type Bound = ();
+                type Choice = ();
+                const KIND: ArgKind = ArgKind::$kind;
+
+                fn register($b: ArgBuilder, spec: ArgSpec<(), ()>) -> ArgBuilder {
+                    let ArgSpec {
+                        name: $name,
+                        desc: $desc,
+                        required: $req,
+                        ..
+                    } = spec;
+                    $reg
+                }
+
+                #[inline]
+                fn visit($v: &mut CommandVisitor<'a>, $n: &'a str) -> Result<Option<Self>> {
+                    $visit
+                }
+            }
+        )*
+    };
+}
+
+simple! {
+    bool => Bool, |b, name, desc, req| b.bool(name, desc, req),
+        |v, n| Ok(v.visit_bool(n)?.optional());
+    &'a User => User, |b, name, desc, req| b.user(name, desc, req),
+        |v, n| Ok(v.visit_user(n)?.optional().map(|(u, _)| u));
+    &'a PartialChannel => Channel, |b, name, desc, req| b.channel(name, desc, req, []),
+        |v, n| Ok(v.visit_channel(n)?.optional());
+    &'a Role => Role, |b, name, desc, req| b.role(name, desc, req),
+        |v, n| Ok(v.visit_role(n)?.optional());
+    &'a Attachment => Attachment, |b, name, desc, req| b.attachment(name, desc, req),
+        |v, n| Ok(v.visit_attachment(n)?.optional());
+}
+
+impl<'a, T: ArgValue<'a>> ArgValue<'a> for Option<T> {
+    type Bound = T::Bound;
+    type Choice = T::Choice;
+
+    const KIND: ArgKind = T::KIND;
+    const REQUIRED: bool = false;
+
+    #[inline]
+    fn register(builder: ArgBuilder, spec: ArgSpec<T::Bound, T::Choice>) -> ArgBuilder {
+        T::register(builder, ArgSpec {
+            required: false,
+            ..spec
+        })
+    }
+
+    #[inline]
+    fn visit(visitor: &mut CommandVisitor<'a>, name: &'a str) -> Result<Option<Self>> {
+        T::visit(visitor, name).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgKind, CommandArgs, Visited, VisitedArg};
+    use crate::interaction::command::{prelude::*, Choice, CommandInfo};
+
+    #[allow(dead_code)]
+    #[derive(CommandArgs)]
+    enum Args<'a> {
+        /// Play a file
+        #[arg(example = "path:a.flac")]
+        Play {
+            /// The file to play
+            #[arg(autocomplete, max = 50)]
+            path: &'a str,
+            /// The volume to play at
+            #[arg(choices = [Choice::new("Quiet", 1), Choice::new("Loud", 2)])]
+            volume: Option<i64>,
+        },
+        /// Stop playback
+        StopAll,
+    }
+
+    #[test]
+    fn test_register() {
+        let derived = CommandInfo::build_slash("sound", "Sounds", Args::register).unwrap();
+        let manual = CommandInfo::build_slash("sound", "Sounds", |a| {
+            a.build_subcmd("play", "Play a file", |a| {
+                a.string("path", "The file to play", true, ..=50)
+                    .autocomplete(true, ["path"])
+                    .int_choice("volume", "The volume to play at", false, [
+                        Choice::new("Quiet", 1),
+                        Choice::new("Loud", 2),
+                    ])
+                    .example("path:a.flac")
+            })
+            .build_subcmd("stop_all", "Stop playback", |a| a)
+        })
+        .unwrap();
+
+        assert_eq!(derived, manual);
+        assert_eq!(derived.examples(&["play"]).collect::<Vec<_>>(), [
+            "/sound play path:a.flac"
+        ]);
+    }
+
+    #[test]
+    fn test_visited() {
+        assert_eq!(
+            Args::visited(),
+            Visited::Branch(vec![
+                (
+                    "play",
+                    Visited::Leaf(vec![
+                        VisitedArg {
+                            name: "path",
+                            kind: ArgKind::String,
+                            required: true,
+                        },
+                        VisitedArg {
+                            name: "volume",
+                            kind: ArgKind::Int,
+                            required: false,
+                        },
+                    ])
+                ),
+                ("stop_all", Visited::Leaf(vec![])),
+            ])
+        );
+
+        let info = CommandInfo::build_slash("sound", "Sounds", Args::register).unwrap();
+        assert_eq!(info.validate_args(&Args::visited()), Ok(()));
+    }
+}
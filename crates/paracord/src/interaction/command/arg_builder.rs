@@ -4,13 +4,15 @@ use ordered_float::{NotNan, OrderedFloat};
 use qcore::{build_range::BuildRange, builder};
 use serenity::model::channel::ChannelType;
 
-use super::{Arg, ArgType, Args, Choice, Subcommand, Trie, TryFromError};
+use super::{
+    Arg, ArgType, Args, Choice, Examples, Localizations, Subcommand, Trie, TryFromError,
+};
 
 // TODO: sort through all imports
 
 /// Helper for constructing chat input command parameters and/or subcommands
 #[derive(Debug, Default)]
-pub struct ArgBuilder(ArgBuilderState);
+pub struct ArgBuilder(ArgBuilderState, Examples);
 
 #[derive(Debug, Default)]
 enum ArgBuilderState {
@@ -80,12 +82,15 @@ impl ArgBuilder {
     /// both subcommand and non-subcommand methods are invoked on the same
     /// builder instance).
     pub fn build(self) -> Result<Args, TryFromError> {
-        Ok(Args(match self.0 {
+        let Self(state, examples) = self;
+        let trie = match state {
             ArgBuilderState::Default => Trie::default(),
             ArgBuilderState::Leaf(args, arg_order) => Trie::Leaf { args, arg_order },
             ArgBuilderState::Branch(height, children) => Trie::Branch { height, children },
             ArgBuilderState::Error(e) => return Err(TryFromError(e)),
-        }))
+        };
+
+        Ok(Args(trie, examples))
     }
 }
 
@@ -326,6 +331,13 @@ impl ArgBuilder {
         }
     }
 
+    /// Add a usage example to this (sub)command, written as the parameters a
+    /// user would enter (e.g. `"path:BUDDY.flac"`)
+    ///
+    /// Examples are not sent to Discord, but are shown alongside errors
+    /// parsing this (sub)command.
+    pub fn example(&mut self, example: impl Into<String>) { self.1.push(example.into()); }
+
     /// Add a new subcommand to this (sub)command
     ///
     /// **NOTE:** The builder state will become invalid if this (sub)command has
@@ -333,8 +345,14 @@ impl ArgBuilder {
     pub fn subcmd(&mut self, name: impl Into<String>, desc: impl Into<String>, args: Args) {
         let name = name.into();
         let desc = desc.into();
-        let Args(node) = args;
-        self.insert_subcommand(name, Subcommand { desc, node });
+        let Args(node, examples) = args;
+        self.1.nest(&name, examples);
+        self.insert_subcommand(name, Subcommand {
+            desc,
+            name_locales: Localizations::new(),
+            desc_locales: Localizations::new(),
+            node,
+        });
     }
 
     /// Add a new subcommand to this (sub)command using the given closure
@@ -0,0 +1,204 @@
+use serde_json::{json, Map, Value};
+use serenity::model::channel::ChannelType;
+
+use super::{Arg, ArgType, Choice, CommandInfo, Data, Trie};
+
+impl CommandInfo {
+    /// Describe this command as a JSON object for external documentation,
+    /// including its parameters, localizations and usage examples
+    #[must_use]
+    pub fn catalog(&self) -> Value {
+        let Self {
+            name,
+            name_locales,
+            can_dm,
+            namespace,
+            data,
+            examples: _,
+        } = self;
+
+        let mut obj = json!({
+            "name": name,
+            "name_localizations": name_locales,
+            "namespace": namespace,
+            "dm_permission": can_dm,
+        });
+        let map = obj.as_object_mut().unwrap_or_else(|| unreachable!());
+
+        match data {
+            Data::Slash {
+                desc,
+                desc_locales,
+                trie,
+            } => {
+                map.insert("type".into(), "slash".into());
+                map.insert("description".into(), desc.as_str().into());
+                map.insert("description_localizations".into(), json!(desc_locales));
+                self.catalog_node(&mut vec![], trie, map);
+            },
+            Data::User => {
+                map.insert("type".into(), "user".into());
+            },
+            Data::Message => {
+                map.insert("type".into(), "message".into());
+            },
+        }
+
+        obj
+    }
+
+    fn catalog_node<'a>(
+        &self,
+        path: &mut Vec<&'a str>,
+        node: &'a Trie,
+        map: &mut Map<String, Value>,
+    ) {
+        match node {
+            Trie::Branch { children, .. } => {
+                let subcmds = children
+                    .iter()
+                    .map(|(name, cmd)| {
+                        path.push(name);
+                        let mut sub = json!({
+                            "name": name,
+                            "name_localizations": cmd.name_locales,
+                            "description": cmd.desc,
+                            "description_localizations": cmd.desc_locales,
+                        });
+                        self.catalog_node(
+                            path,
+                            &cmd.node,
+                            sub.as_object_mut().unwrap_or_else(|| unreachable!()),
+                        );
+                        path.pop();
+                        sub
+                    })
+                    .collect::<Vec<_>>();
+                map.insert("subcommands".into(), subcmds.into());
+            },
+            Trie::Leaf { args, arg_order } => {
+                let opts = arg_order
+                    .iter()
+                    .map(|name| args[name].catalog(name))
+                    .collect::<Vec<_>>();
+                map.insert("options".into(), opts.into());
+                map.insert(
+                    "examples".into(),
+                    self.examples(path).collect::<Vec<_>>().into(),
+                );
+            },
+        }
+    }
+}
+
+impl Arg {
+    fn catalog(&self, name: &str) -> Value {
+        let Self { desc, required, ty } = self;
+        let mut obj = json!({
+            "name": name,
+            "description": desc,
+            "required": required,
+        });
+        let map = obj.as_object_mut().unwrap_or_else(|| unreachable!());
+
+        let (kind, extra) = match ty {
+            ArgType::String {
+                autocomplete,
+                min_len,
+                max_len,
+            } => ("string", json!({
+                "autocomplete": autocomplete,
+                "min_length": min_len,
+                "max_length": max_len,
+            })),
+            ArgType::StringChoice(c) => ("string", choices(c, |v| v.as_str().into())),
+            ArgType::Int {
+                autocomplete,
+                min,
+                max,
+            } => ("integer", json!({
+                "autocomplete": autocomplete,
+                "min_value": min,
+                "max_value": max,
+            })),
+            ArgType::IntChoice(c) => ("integer", choices(c, |&v| v.into())),
+            ArgType::Bool => ("boolean", json!({})),
+            ArgType::User => ("user", json!({})),
+            ArgType::Channel(c) => ("channel", json!({
+                "channel_types": c.iter().map(ChannelType::name).collect::<Vec<_>>(),
+            })),
+            ArgType::Role => ("role", json!({})),
+            ArgType::Mention => ("mentionable", json!({})),
+            ArgType::Real {
+                autocomplete,
+                min,
+                max,
+            } => ("number", json!({
+                "autocomplete": autocomplete,
+                "min_value": min.map(Into::<f64>::into),
+                "max_value": max.map(Into::<f64>::into),
+            })),
+            ArgType::RealChoice(c) => ("number", choices(c, |v| v.0.into())),
+            ArgType::Attachment => ("attachment", json!({})),
+        };
+
+        map.insert("type".into(), kind.into());
+        if let Value::Object(extra) = extra {
+            map.extend(extra);
+        }
+
+        obj
+    }
+}
+
+fn choices<'a, T: 'a>(
+    choices: impl IntoIterator<Item = &'a Choice<T>>,
+    f: impl Fn(&T) -> Value,
+) -> Value {
+    let choices = choices
+        .into_iter()
+        .map(|Choice { name, val }| json!({ "name": name, "value": f(val) }))
+        .collect::<Vec<_>>();
+
+    json!({ "choices": choices })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::{prelude::*, CommandInfo};
+
+    #[test]
+    fn test_catalog() {
+        let info = CommandInfo::build_slash("say", "Say something", |a| {
+            a.string("message", "The message", true, ..)
+                .example("message:hi")
+        })
+        .unwrap()
+        .desc_localized("de", "Etwas sagen");
+
+        assert_eq!(
+            info.catalog(),
+            json!({
+                "name": "say",
+                "name_localizations": {},
+                "namespace": null,
+                "dm_permission": true,
+                "type": "slash",
+                "description": "Say something",
+                "description_localizations": { "de": "Etwas sagen" },
+                "options": [{
+                    "name": "message",
+                    "description": "The message",
+                    "required": true,
+                    "type": "string",
+                    "autocomplete": false,
+                    "min_length": null,
+                    "max_length": null,
+                }],
+                "examples": ["/say message:hi"],
+            })
+        );
+    }
+}
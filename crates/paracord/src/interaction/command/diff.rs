@@ -0,0 +1,133 @@
+use super::{Arg, CommandInfo, Data, Subcommand, Trie};
+
+/// The kind of update needed to bring a registered command in line with a new
+/// descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Change {
+    /// Only descriptions, localizations or DM permission differ, so the command
+    /// can be edited in place
+    Metadata,
+    /// The name, parameters or subcommands differ, but the command can still
+    /// be edited in place
+    Structure,
+    /// The command type differs, which Discord does not allow to be edited, so
+    /// the command must be deleted and created again
+    Recreate,
+}
+
+/// Classify the update needed to change a registered command into a new
+/// descriptor
+#[must_use]
+pub fn change(old: &CommandInfo, new: &CommandInfo) -> Change {
+    match (&old.data, &new.data) {
+        (Data::Slash { .. }, Data::Slash { .. })
+        | (Data::User, Data::User)
+        | (Data::Message, Data::Message) => (),
+        _ => return Change::Recreate,
+    }
+
+    if old.shape_eq(new) {
+        Change::Metadata
+    } else {
+        Change::Structure
+    }
+}
+
+/// Equality ignoring descriptions, localizations and permissions
+trait ShapeEq {
+    fn shape_eq(&self, rhs: &Self) -> bool;
+}
+
+impl ShapeEq for CommandInfo {
+    fn shape_eq(&self, rhs: &Self) -> bool {
+        self.name == rhs.name && self.data.shape_eq(&rhs.data)
+    }
+}
+
+impl ShapeEq for Data {
+    fn shape_eq(&self, rhs: &Self) -> bool {
+        match (self, rhs) {
+            (Self::Slash { trie: l, .. }, Self::Slash { trie: r, .. }) => l.shape_eq(r),
+            (Self::User, Self::User) | (Self::Message, Self::Message) => true,
+            _ => false,
+        }
+    }
+}
+
+impl ShapeEq for Trie {
+    fn shape_eq(&self, rhs: &Self) -> bool {
+        match (self, rhs) {
+            (
+                Self::Branch {
+                    height: l_height,
+                    children: l_chld,
+                },
+                Self::Branch {
+                    height: r_height,
+                    children: r_chld,
+                },
+            ) => {
+                l_height == r_height
+                    && l_chld.len() == r_chld.len()
+                    && l_chld
+                        .iter()
+                        .zip(r_chld)
+                        .all(|((lk, lv), (rk, rv))| lk == rk && lv.shape_eq(rv))
+            },
+            (
+                Self::Leaf {
+                    args: l_args,
+                    arg_order: l_order,
+                },
+                Self::Leaf {
+                    args: r_args,
+                    arg_order: r_order,
+                },
+            ) => {
+                l_order == r_order
+                    && l_args.len() == r_args.len()
+                    && l_args
+                        .iter()
+                        .zip(r_args)
+                        .all(|((lk, lv), (rk, rv))| lk == rk && lv.shape_eq(rv))
+            },
+            _ => false,
+        }
+    }
+}
+
+impl ShapeEq for Subcommand {
+    fn shape_eq(&self, rhs: &Self) -> bool { self.node.shape_eq(&rhs.node) }
+}
+
+impl ShapeEq for Arg {
+    fn shape_eq(&self, rhs: &Self) -> bool { self.required == rhs.required && self.ty == rhs.ty }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::prelude::*, change, Change, CommandInfo};
+
+    #[test]
+    fn test_change() {
+        let old = CommandInfo::build_slash("say", "Say something", |a| {
+            a.string("message", "The message", true, ..)
+        })
+        .unwrap();
+
+        let reworded = CommandInfo::build_slash("say", "Say anything", |a| {
+            a.string("message", "What to say", true, ..)
+        })
+        .unwrap();
+        assert_eq!(change(&old, &reworded), Change::Metadata);
+
+        let extended = CommandInfo::build_slash("say", "Say something", |a| {
+            a.string("message", "The message", true, ..)
+                .bool("quiet", "Whether to whisper", false)
+        })
+        .unwrap();
+        assert_eq!(change(&old, &extended), Change::Structure);
+
+        assert_eq!(change(&old, &CommandInfo::message("say")), Change::Recreate);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, num::NonZeroU8};
+use std::{cmp::Ordering, collections::BTreeMap, hash, num::NonZeroU8};
 
 use qcore::builder;
 use serenity::{
@@ -11,12 +11,18 @@ use serenity::{
 
 use super::{Arg, ArgBuilder, ArgType, TryFromError};
 
+/// Localized variants of a name or description, keyed by Discord locale code
+pub type Localizations = BTreeMap<String, String>;
+
 /// Metadata for an application command
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CommandInfo {
     pub(super) name: String,
+    pub(super) name_locales: Localizations,
     pub(super) can_dm: bool,
+    pub(super) namespace: Option<String>,
     pub(super) data: Data,
+    pub(super) examples: Examples,
 }
 
 impl CommandInfo {
@@ -26,11 +32,18 @@ impl CommandInfo {
     pub fn slash(name: impl Into<String>, desc: impl Into<String>, args: Args) -> Self {
         let name = name.into();
         let desc = desc.into();
-        let Args(trie) = args;
+        let Args(trie, examples) = args;
         Self {
             name,
-            data: Data::Slash { desc, trie },
+            name_locales: Localizations::new(),
+            data: Data::Slash {
+                desc,
+                desc_locales: Localizations::new(),
+                trie,
+            },
             can_dm: true,
+            namespace: None,
+            examples,
         }
     }
 
@@ -55,8 +68,11 @@ impl CommandInfo {
         let name = name.into();
         Self {
             name,
+            name_locales: Localizations::new(),
             data: Data::User,
             can_dm: true,
+            namespace: None,
+            examples: Examples::default(),
         }
     }
 
@@ -66,8 +82,11 @@ impl CommandInfo {
         let name = name.into();
         Self {
             name,
+            name_locales: Localizations::new(),
             data: Data::Message,
             can_dm: true,
+            namespace: None,
+            examples: Examples::default(),
         }
     }
 
@@ -76,15 +95,97 @@ impl CommandInfo {
     #[must_use]
     pub fn name(&self) -> &String { &self.name }
 
+    /// Get the name of the namespace this command should be registered under,
+    /// if any
+    #[inline]
+    #[must_use]
+    pub fn namespace(&self) -> Option<&String> { self.namespace.as_ref() }
+
+    /// Get the usage examples declared for the subcommand at the given path,
+    /// formatted as full invocations (e.g. `/q sound play path:BUDDY.flac`)
+    pub fn examples<'a>(&'a self, path: &'a [&str]) -> impl Iterator<Item = String> + 'a {
+        let prefix = self
+            .namespace
+            .iter()
+            .chain([&self.name])
+            .map(String::as_str)
+            .chain(path.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        self.examples
+            .0
+            .iter()
+            .find(|(p, _)| p.iter().map(String::as_str).eq(path.iter().copied()))
+            .into_iter()
+            .flat_map(|(_, e)| e)
+            .map(move |e| {
+                if e.is_empty() {
+                    format!("/{prefix}")
+                } else {
+                    format!("/{prefix} {e}")
+                }
+            })
+    }
+
+    /// Suggest a registered subcommand at the given subcommand path with a
+    /// name similar to `name`, if `name` is not itself registered
+    #[must_use]
+    pub fn suggest_subcommand(&self, path: &[&str], name: &str) -> Option<&str> {
+        let Data::Slash { ref trie, .. } = self.data else {
+            return None;
+        };
+
+        match trie.lookup(path)? {
+            Trie::Branch { children, .. } => {
+                super::sim::closest(name, children.keys().map(String::as_str))
+            },
+            Trie::Leaf { .. } => None,
+        }
+    }
+
+    /// Suggest a registered option with a name similar to `name`, if `name`
+    /// is not itself registered under any subcommand
+    #[must_use]
+    pub fn suggest_option(&self, name: &str) -> Option<&str> {
+        let Data::Slash { ref trie, .. } = self.data else {
+            return None;
+        };
+
+        let mut names = vec![];
+        trie.visit_args(&mut |a| names.push(a));
+        super::sim::closest(name, names)
+    }
+
     /// Apply the data contained within this command description to a
     /// [`serenity`] command builder
+    ///
+    /// **NOTE:** Namespace membership is not encoded by this method, the
+    /// command should be merged into its namespace before being built.
     pub fn build(self, cmd: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
-        let Self { name, can_dm, data } = self;
+        let Self {
+            name,
+            name_locales,
+            can_dm,
+            namespace: _,
+            data,
+            examples: _,
+        } = self;
         cmd.name(name).dm_permission(can_dm);
+        for (locale, name) in name_locales {
+            cmd.name_localized(locale, name);
+        }
 
         match data {
-            Data::Slash { desc, trie } => {
+            Data::Slash {
+                desc,
+                desc_locales,
+                trie,
+            } => {
                 cmd.description(desc);
+                for (locale, desc) in desc_locales {
+                    cmd.description_localized(locale, desc);
+                }
                 match trie {
                     Trie::Branch { height, children } => {
                         for pair in children {
@@ -120,18 +221,86 @@ impl CommandInfo {
     /// Set whether this command should be usable in DM (i.e. non-guild)
     /// channels
     pub fn can_dm(&mut self, can_dm: bool) { self.can_dm = can_dm; }
+
+    /// Add a localized variant of this command's name
+    pub fn name_localized(&mut self, locale: impl Into<String>, name: impl Into<String>) {
+        self.name_locales.insert(locale.into(), name.into());
+    }
+
+    /// Add a localized variant of this command's description
+    ///
+    /// **NOTE:** This has no effect on non-chat-input commands.
+    pub fn desc_localized(&mut self, locale: impl Into<String>, desc: impl Into<String>) {
+        if let Data::Slash { desc_locales, .. } = &mut self.data {
+            desc_locales.insert(locale.into(), desc.into());
+        }
+    }
+
+    /// Register this command as a subcommand of the given namespace rather
+    /// than as a top-level command
+    ///
+    /// **NOTE:** Only chat input commands may be placed in a namespace, and the
+    /// namespace must be declared in the [`Handlers`] the command is
+    /// registered with.
+    ///
+    /// [`Handlers`]: super::super::handler::Handlers
+    pub fn in_namespace(&mut self, namespace: impl Into<String>) {
+        self.namespace = Some(namespace.into());
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(super) enum Data {
-    Slash { desc: String, trie: Trie },
+    Slash {
+        desc: String,
+        desc_locales: Localizations,
+        trie: Trie,
+    },
     User,
     Message,
 }
 
 /// Metadata for chat input command parameters and/or subcommands
 #[derive(Debug, Default)]
-pub struct Args(pub(super) Trie);
+pub struct Args(pub(super) Trie, pub(super) Examples);
+
+/// Usage examples for a command, keyed by subcommand path
+///
+/// Examples are never sent to Discord, so they are ignored when comparing
+/// commands against their registered counterparts.
+#[derive(Debug, Default)]
+pub(super) struct Examples(pub(super) BTreeMap<Vec<String>, Vec<String>>);
+
+impl Examples {
+    pub(super) fn push(&mut self, example: String) {
+        self.0.entry(vec![]).or_default().push(example);
+    }
+
+    pub(super) fn nest(&mut self, name: &str, child: Self) {
+        for (mut path, examples) in child.0 {
+            path.insert(0, name.into());
+            self.0.entry(path).or_default().extend(examples);
+        }
+    }
+}
+
+impl PartialEq for Examples {
+    fn eq(&self, _: &Self) -> bool { true }
+}
+
+impl Eq for Examples {}
+
+impl PartialOrd for Examples {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Examples {
+    fn cmp(&self, _: &Self) -> Ordering { Ordering::Equal }
+}
+
+impl hash::Hash for Examples {
+    fn hash<H: hash::Hasher>(&self, _: &mut H) {}
+}
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(super) enum Trie {
@@ -162,7 +331,9 @@ impl Trie {
             let CommandOption {
                 kind,
                 name,
+                name_localizations,
                 description: desc,
+                description_localizations,
                 required,
                 choices,
                 options,
@@ -187,7 +358,12 @@ impl Trie {
                     assert!(arg_order.is_empty());
                     let mut children = BTreeMap::new();
                     let node = Trie::try_build(options)?;
-                    children.insert(name, Subcommand { desc, node });
+                    children.insert(name, Subcommand {
+                        desc,
+                        name_locales: name_localizations.into_iter().flatten().collect(),
+                        desc_locales: description_localizations.into_iter().flatten().collect(),
+                        node,
+                    });
                     let height = children
                         .iter()
                         .map(|c| c.1.node.height())
@@ -212,7 +388,17 @@ impl Trie {
                             .and_then(NonZeroU8::new)
                             .unwrap_or_else(|| unreachable!()),
                     );
-                    assert!(children.insert(name, Subcommand { desc, node }).is_none());
+                    assert!(children
+                        .insert(name, Subcommand {
+                            desc,
+                            name_locales: name_localizations.into_iter().flatten().collect(),
+                            desc_locales: description_localizations
+                                .into_iter()
+                                .flatten()
+                                .collect(),
+                            node,
+                        })
+                        .is_none());
                     Ok(Trie::Branch { height, children })
                 },
                 (
@@ -241,6 +427,25 @@ impl Trie {
         })
     }
 
+    fn lookup(&self, path: &[&str]) -> Option<&Self> {
+        match (path, self) {
+            ([], t) => Some(t),
+            ([head, rest @ ..], Self::Branch { children, .. }) => {
+                children.get(*head)?.node.lookup(rest)
+            },
+            (_, Self::Leaf { .. }) => None,
+        }
+    }
+
+    fn visit_args<'a>(&'a self, f: &mut impl FnMut(&'a str)) {
+        match self {
+            Self::Branch { children, .. } => {
+                children.values().for_each(|c| c.node.visit_args(f));
+            },
+            Self::Leaf { arg_order, .. } => arg_order.iter().for_each(|a| f(a)),
+        }
+    }
+
     #[inline]
     pub(super) fn height(&self) -> u8 {
         match *self {
@@ -253,6 +458,8 @@ impl Trie {
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(super) struct Subcommand {
     pub(super) desc: String,
+    pub(super) name_locales: Localizations,
+    pub(super) desc_locales: Localizations,
     pub(super) node: Trie,
 }
 
@@ -261,8 +468,19 @@ impl Subcommand {
         self,
         opt: &mut CreateApplicationCommandOption,
     ) -> &mut CreateApplicationCommandOption {
-        let Self { desc, node } = self;
+        let Self {
+            desc,
+            name_locales,
+            desc_locales,
+            node,
+        } = self;
         opt.description(desc);
+        for (locale, name) in name_locales {
+            opt.name_localized(locale, name);
+        }
+        for (locale, desc) in desc_locales {
+            opt.description_localized(locale, desc);
+        }
 
         match node {
             Trie::Branch { height, children } => {
@@ -3,20 +3,30 @@
 
 mod arg;
 mod arg_builder;
+mod catalog;
+mod diff;
 mod info;
+mod namespace;
 mod registered;
 mod sim;
 pub(self) mod try_from_value;
+mod validate;
 
 pub use arg::*;
 pub use arg_builder::*;
+pub use diff::*;
 pub use info::*;
+pub use namespace::*;
 pub(super) use registered::*;
 pub use sim::*;
+pub use validate::*;
 
 /// Helper traits for working with command metadata
 pub mod prelude {
-    pub use super::{arg_builder::ArgBuilderExt as _, info::CommandInfoExt as _};
+    pub use super::{
+        arg_builder::ArgBuilderExt as _, info::CommandInfoExt as _,
+        namespace::NamespaceInfoExt as _,
+    };
 }
 
 /// An error resulting from converting a value into a [`CommandInfo`] with
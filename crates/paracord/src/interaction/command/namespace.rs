@@ -0,0 +1,142 @@
+use std::{collections::BTreeMap, num::NonZeroU8};
+
+use qcore::builder;
+
+use super::{CommandInfo, Data, Examples, Localizations, Subcommand, Trie};
+
+/// An error arising from merging commands into a namespace
+#[derive(Debug, thiserror::Error)]
+pub enum NamespaceError {
+    /// A command was declared under a namespace that was not registered
+    #[error("Command {1:?} declared under unknown namespace {0:?}")]
+    Undeclared(String, String),
+    /// Two namespaces were declared with the same name
+    #[error("Namespace {0:?} declared more than once")]
+    DuplicateNamespace(String),
+    /// A non-chat-input command was declared under a namespace
+    #[error("Command {1:?} in namespace {0:?} is not a chat input command")]
+    NotSlash(String, String),
+    /// Two commands with the same name were declared under one namespace
+    #[error("Command {1:?} declared more than once in namespace {0:?}")]
+    Duplicate(String, String),
+    /// A command declared under a namespace has subcommand groups, which
+    /// cannot be nested any further
+    #[error("Command {1:?} in namespace {0:?} has too many levels of subcommands")]
+    TooDeep(String, String),
+}
+
+/// Metadata for a top-level chat input command under which other chat input
+/// commands can be grouped as subcommands
+#[derive(Debug)]
+pub struct NamespaceInfo {
+    name: String,
+    name_locales: Localizations,
+    desc: String,
+    desc_locales: Localizations,
+}
+
+impl NamespaceInfo {
+    /// Construct a new namespace description
+    #[inline]
+    pub fn new(name: impl Into<String>, desc: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            name_locales: Localizations::new(),
+            desc: desc.into(),
+            desc_locales: Localizations::new(),
+        }
+    }
+
+    /// Get the unique, non-localized name of this namespace
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &String { &self.name }
+
+    /// Merge the given member commands into a single top-level command for
+    /// this namespace
+    ///
+    /// # Errors
+    /// This method returns an error if any member is not a chat input command,
+    /// cannot be nested under a namespace, or shares a name with another
+    /// member.
+    pub fn merge(
+        &self,
+        members: impl IntoIterator<Item = CommandInfo>,
+    ) -> Result<CommandInfo, NamespaceError> {
+        let mut children = BTreeMap::new();
+        let mut can_dm = false;
+
+        for member in members {
+            let CommandInfo {
+                name,
+                name_locales,
+                can_dm: member_dm,
+                namespace,
+                data,
+                examples: _,
+            } = member;
+            debug_assert_eq!(namespace.as_ref(), Some(&self.name));
+
+            let Data::Slash {
+                desc,
+                desc_locales,
+                trie,
+            } = data
+            else {
+                return Err(NamespaceError::NotSlash(self.name.clone(), name));
+            };
+
+            if trie.height() >= 2 {
+                return Err(NamespaceError::TooDeep(self.name.clone(), name));
+            }
+
+            if children.contains_key(&name) {
+                return Err(NamespaceError::Duplicate(self.name.clone(), name));
+            }
+
+            can_dm |= member_dm;
+            children.insert(name, Subcommand {
+                desc,
+                name_locales,
+                desc_locales,
+                node: trie,
+            });
+        }
+
+        let height = children
+            .values()
+            .map(|c| c.node.height())
+            .max()
+            .unwrap_or(0)
+            .checked_add(1)
+            .and_then(NonZeroU8::new)
+            .unwrap_or_else(|| unreachable!());
+
+        Ok(CommandInfo {
+            name: self.name.clone(),
+            name_locales: self.name_locales.clone(),
+            can_dm,
+            namespace: None,
+            examples: Examples::default(),
+            data: Data::Slash {
+                desc: self.desc.clone(),
+                desc_locales: self.desc_locales.clone(),
+                trie: Trie::Branch { height, children },
+            },
+        })
+    }
+}
+
+#[builder(trait_name = NamespaceInfoExt)]
+/// Helper methods for mutating [`NamespaceInfo`]
+impl NamespaceInfo {
+    /// Add a localized variant of this namespace's name
+    pub fn name_localized(&mut self, locale: impl Into<String>, name: impl Into<String>) {
+        self.name_locales.insert(locale.into(), name.into());
+    }
+
+    /// Add a localized variant of this namespace's description
+    pub fn desc_localized(&mut self, locale: impl Into<String>, desc: impl Into<String>) {
+        self.desc_locales.insert(locale.into(), desc.into());
+    }
+}
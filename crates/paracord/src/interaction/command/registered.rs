@@ -4,7 +4,7 @@ use serenity::model::{
     prelude::command::CommandType,
 };
 
-use super::{CommandInfo, Data, Trie, TryFromError};
+use super::{CommandInfo, Data, Examples, Trie, TryFromError};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(in super::super) struct RegisteredCommand {
@@ -25,7 +25,9 @@ impl TryFrom<Command> for RegisteredCommand {
             application_id,
             guild_id,
             name,
+            name_localizations,
             description,
+            description_localizations,
             options,
             dm_permission,
             version,
@@ -35,6 +37,7 @@ impl TryFrom<Command> for RegisteredCommand {
         let data = match kind {
             CommandType::ChatInput => Data::Slash {
                 desc: description,
+                desc_locales: description_localizations.into_iter().flatten().collect(),
                 trie: Trie::try_build(options)?,
             },
             CommandType::User => Data::User,
@@ -49,8 +52,11 @@ impl TryFrom<Command> for RegisteredCommand {
             version,
             info: CommandInfo {
                 name,
+                name_locales: name_localizations.into_iter().flatten().collect(),
                 data,
                 can_dm: dm_permission.unwrap_or(true),
+                namespace: None,
+                examples: Examples::default(),
             },
         })
     }
@@ -9,6 +9,31 @@ use super::{Arg, ArgType, CommandInfo, Data, Subcommand, Trie};
 #[must_use]
 pub fn similarity(l: &CommandInfo, r: &CommandInfo) -> f64 { l.sim(r) }
 
+/// Minimum normalized edit-distance similarity for a name to be suggested
+const SUGGEST_THRESHOLD: f64 = 0.5;
+
+/// Find the candidate most similar to `name`, if `name` is not itself a
+/// candidate and any candidate is similar enough to be worth suggesting
+pub(super) fn closest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let mut best = None;
+
+    for cand in candidates {
+        if cand == name {
+            return None;
+        }
+
+        let sim = normalized_damerau_levenshtein(name, cand);
+        if sim >= SUGGEST_THRESHOLD && best.map_or(true, |(s, _)| sim > s) {
+            best = Some((sim, cand));
+        }
+    }
+
+    best.map(|(_, c)| c)
+}
+
 #[allow(clippy::cast_precision_loss)]
 fn ngdl<T: Clone + Eq + Hash>(l: &[T], r: &[T]) -> f64 {
     let score = generic_damerau_levenshtein(l, r);
@@ -41,6 +66,10 @@ impl<T: Clone + Eq + Hash> Sim for Vec<T> {
 #[allow(clippy::cast_precision_loss)]
 impl<K: Ord, V: Sim> Sim for BTreeMap<K, V> {
     fn sim(&self, rhs: &Self) -> f64 {
+        if self.is_empty() && rhs.is_empty() {
+            return 1.0;
+        }
+
         self.iter()
             .filter_map(|(k, l)| {
                 let r = rhs.get(k)?;
@@ -56,16 +85,27 @@ impl Sim for CommandInfo {
     fn sim(&self, rhs: &Self) -> f64 {
         let Self {
             name: l_name,
+            name_locales: l_locales,
             can_dm: l_dm,
+            namespace: _,
             data: l_data,
+            examples: _,
         } = self;
         let Self {
             name: r_name,
+            name_locales: r_locales,
             can_dm: r_dm,
+            namespace: _,
             data: r_data,
+            examples: _,
         } = rhs;
 
-        avg([l_name.sim(r_name), l_dm.sim(r_dm), l_data.sim(r_data)])
+        avg([
+            l_name.sim(r_name),
+            l_locales.sim(r_locales),
+            l_dm.sim(r_dm),
+            l_data.sim(r_data),
+        ])
     }
 }
 
@@ -76,13 +116,19 @@ impl Sim for Data {
             (
                 Self::Slash {
                     desc: l_desc,
+                    desc_locales: l_locales,
                     trie: l_trie,
                 },
                 Self::Slash {
                     desc: r_desc,
+                    desc_locales: r_locales,
                     trie: r_trie,
                 },
-            ) => avg([l_desc.sim(r_desc), l_trie.sim(r_trie)]),
+            ) => avg([
+                l_desc.sim(r_desc),
+                l_locales.sim(r_locales),
+                l_trie.sim(r_trie),
+            ]),
             (Self::User, Self::User) | (Self::Message, Self::Message) => 1.0,
             _ => 0.0,
         }
@@ -122,14 +168,23 @@ impl Sim for Subcommand {
     fn sim(&self, rhs: &Self) -> f64 {
         let Self {
             desc: l_desc,
+            name_locales: l_name_locales,
+            desc_locales: l_desc_locales,
             node: l_node,
         } = self;
         let Self {
             desc: r_desc,
+            name_locales: r_name_locales,
+            desc_locales: r_desc_locales,
             node: r_node,
         } = rhs;
 
-        avg([l_desc.sim(r_desc), l_node.sim(r_node)])
+        avg([
+            l_desc.sim(r_desc),
+            l_name_locales.sim(r_name_locales),
+            l_desc_locales.sim(r_desc_locales),
+            l_node.sim(r_node),
+        ])
     }
 }
 
@@ -0,0 +1,425 @@
+use super::{
+    super::args::{ArgKind, Visited, VisitedArg},
+    Arg, ArgType, CommandInfo, Data, Localizations, Subcommand, Trie,
+};
+
+const MAX_NAME_LEN: usize = 32;
+const MAX_DESC_LEN: usize = 100;
+const MAX_OPTIONS: usize = 25;
+const MAX_CHOICES: usize = 25;
+const MAX_CHOICE_LEN: usize = 100;
+const MAX_TOTAL_LEN: usize = 4000;
+
+/// A violation of one of Discord's documented limits on command metadata
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationError {
+    /// A name was empty, too long, or contained invalid characters
+    #[error("{0}: invalid name {1:?}")]
+    Name(String, String),
+    /// A description was empty or too long
+    #[error("{0}: description must be 1-{MAX_DESC_LEN} characters long, found {1}")]
+    Description(String, usize),
+    /// A command or subcommand had too many options or subcommands
+    #[error("{0}: at most {MAX_OPTIONS} options are allowed, found {1}")]
+    TooManyOptions(String, usize),
+    /// A parameter had too many choices
+    #[error("{0}: at most {MAX_CHOICES} choices are allowed, found {1}")]
+    TooManyChoices(String, usize),
+    /// A choice name or value was empty or too long
+    #[error("{0}: invalid choice {1:?}")]
+    Choice(String, String),
+    /// A required parameter was listed after an optional one
+    #[error("{0}: required option {1:?} follows an optional option")]
+    RequiredAfterOptional(String, String),
+    /// The combined length of all text in a command was too long
+    #[error("{0}: combined length of names and descriptions must be at most {MAX_TOTAL_LEN}, \
+             found {1}")]
+    TooLong(String, usize),
+    /// A parameter or subcommand was read by the handler but not registered
+    #[error("{0}: read by the handler but never registered")]
+    Unregistered(String),
+    /// A parameter or subcommand was registered but never read by the handler
+    #[error("{0}: registered but never read by the handler")]
+    Unread(String),
+    /// The handler expected subcommands where parameters were registered, or
+    /// vice versa
+    #[error("{0}: registered and read with different subcommand structure")]
+    Shape(String),
+    /// A parameter was read as a different kind of value than it was
+    /// registered as
+    #[error("{0}: registered as {1:?} but read as {2:?}")]
+    Kind(String, ArgKind, ArgKind),
+    /// A parameter was read as required but registered as optional, or vice
+    /// versa
+    #[error("{0}: registered with required = {1} but read with required = {2}")]
+    Required(String, bool, bool),
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<ValidationError>,
+    total_len: usize,
+}
+
+fn char_len(s: &str) -> usize { s.chars().count() }
+
+impl Validator {
+    fn slash_name(&mut self, path: &str, name: &str) {
+        self.total_len += char_len(name);
+
+        let len = char_len(name);
+        if len == 0
+            || len > MAX_NAME_LEN
+            || !name
+                .chars()
+                .all(|c| (c.is_alphanumeric() && !c.is_uppercase()) || matches!(c, '-' | '_'))
+        {
+            self.errors
+                .push(ValidationError::Name(path.into(), name.into()));
+        }
+    }
+
+    fn menu_name(&mut self, path: &str, name: &str) {
+        self.total_len += char_len(name);
+
+        let len = char_len(name);
+        if len == 0 || len > MAX_NAME_LEN {
+            self.errors
+                .push(ValidationError::Name(path.into(), name.into()));
+        }
+    }
+
+    fn desc(&mut self, path: &str, desc: &str) {
+        let len = char_len(desc);
+        self.total_len += len;
+
+        if len == 0 || len > MAX_DESC_LEN {
+            self.errors
+                .push(ValidationError::Description(path.into(), len));
+        }
+    }
+
+    fn locales(&mut self, path: &str, names: &Localizations, slash: bool) {
+        for (locale, name) in names {
+            let path = format!("{path} ({locale})");
+            if slash {
+                self.slash_name(&path, name);
+            } else {
+                self.menu_name(&path, name);
+            }
+        }
+    }
+
+    fn desc_locales(&mut self, path: &str, descs: &Localizations) {
+        for (locale, desc) in descs {
+            self.desc(&format!("{path} ({locale})"), desc);
+        }
+    }
+
+    fn trie(&mut self, path: &str, trie: &Trie) {
+        match trie {
+            Trie::Branch { children, .. } => {
+                if children.len() > MAX_OPTIONS {
+                    self.errors
+                        .push(ValidationError::TooManyOptions(path.into(), children.len()));
+                }
+
+                for (name, sub) in children {
+                    let Subcommand {
+                        desc,
+                        name_locales,
+                        desc_locales,
+                        node,
+                    } = sub;
+                    let path = format!("{path} {name}");
+
+                    self.slash_name(&path, name);
+                    self.locales(&path, name_locales, true);
+                    self.desc(&path, desc);
+                    self.desc_locales(&path, desc_locales);
+                    self.trie(&path, node);
+                }
+            },
+            Trie::Leaf { args, arg_order } => {
+                if args.len() > MAX_OPTIONS {
+                    self.errors
+                        .push(ValidationError::TooManyOptions(path.into(), args.len()));
+                }
+
+                let mut seen_optional = false;
+                for name in arg_order {
+                    let arg = &args[name];
+                    let path = format!("{path} {name}");
+
+                    if arg.required && seen_optional {
+                        self.errors.push(ValidationError::RequiredAfterOptional(
+                            path.clone(),
+                            name.clone(),
+                        ));
+                    }
+                    seen_optional |= !arg.required;
+
+                    self.slash_name(&path, name);
+                    self.arg(&path, arg);
+                }
+            },
+        }
+    }
+
+    fn arg(&mut self, path: &str, arg: &Arg) {
+        let Arg { desc, required: _, ty } = arg;
+        self.desc(path, desc);
+
+        let choices: Vec<_> = match ty {
+            ArgType::StringChoice(c) => c.iter().map(|c| (&c.name, c.val.clone())).collect(),
+            ArgType::IntChoice(c) => c.iter().map(|c| (&c.name, c.val.to_string())).collect(),
+            ArgType::RealChoice(c) => c.iter().map(|c| (&c.name, c.val.to_string())).collect(),
+            ArgType::String { .. }
+            | ArgType::Int { .. }
+            | ArgType::Bool
+            | ArgType::User
+            | ArgType::Channel(_)
+            | ArgType::Role
+            | ArgType::Mention
+            | ArgType::Real { .. }
+            | ArgType::Attachment => return,
+        };
+
+        if choices.len() > MAX_CHOICES {
+            self.errors
+                .push(ValidationError::TooManyChoices(path.into(), choices.len()));
+        }
+
+        for (name, val) in choices {
+            let name_len = char_len(name);
+            let val_len = char_len(&val);
+            self.total_len += name_len + val_len;
+
+            if name_len == 0 || name_len > MAX_CHOICE_LEN || val_len > MAX_CHOICE_LEN {
+                self.errors
+                    .push(ValidationError::Choice(path.into(), name.clone()));
+            }
+        }
+    }
+}
+
+fn arg_kind(ty: &ArgType) -> ArgKind {
+    match ty {
+        ArgType::String { .. } | ArgType::StringChoice(_) => ArgKind::String,
+        ArgType::Int { .. } | ArgType::IntChoice(_) => ArgKind::Int,
+        ArgType::Real { .. } | ArgType::RealChoice(_) => ArgKind::Real,
+        ArgType::Bool => ArgKind::Bool,
+        ArgType::User => ArgKind::User,
+        ArgType::Channel(_) => ArgKind::Channel,
+        ArgType::Role => ArgKind::Role,
+        ArgType::Mention => ArgKind::Mention,
+        ArgType::Attachment => ArgKind::Attachment,
+    }
+}
+
+fn check_visited(errors: &mut Vec<ValidationError>, path: &str, trie: &Trie, visited: &Visited) {
+    match (trie, visited) {
+        (Trie::Branch { children, .. }, Visited::Branch(subs)) => {
+            for (name, sub) in subs {
+                let path = format!("{path} {name}");
+                match children.get(*name) {
+                    Some(child) => check_visited(errors, &path, &child.node, sub),
+                    None => errors.push(ValidationError::Unregistered(path)),
+                }
+            }
+
+            for name in children.keys() {
+                if !subs.iter().any(|(n, _)| *n == *name) {
+                    errors.push(ValidationError::Unread(format!("{path} {name}")));
+                }
+            }
+        },
+        (Trie::Leaf { args, arg_order }, Visited::Leaf(visited)) => {
+            for VisitedArg {
+                name,
+                kind,
+                required,
+            } in visited
+            {
+                let path = format!("{path} {name}");
+                let Some(arg) = args.get(*name) else {
+                    errors.push(ValidationError::Unregistered(path));
+                    continue;
+                };
+
+                let registered = arg_kind(&arg.ty);
+                if registered != *kind {
+                    errors.push(ValidationError::Kind(path.clone(), registered, *kind));
+                }
+
+                if arg.required != *required {
+                    errors.push(ValidationError::Required(path, arg.required, *required));
+                }
+            }
+
+            for name in arg_order {
+                if !visited.iter().any(|a| a.name == *name) {
+                    errors.push(ValidationError::Unread(format!("{path} {name}")));
+                }
+            }
+        },
+        _ => errors.push(ValidationError::Shape(path.into())),
+    }
+}
+
+impl CommandInfo {
+    /// Check this command against Discord's documented limits for command
+    /// registration, returning every violation found
+    ///
+    /// # Errors
+    /// This method returns an error if any limit is exceeded.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let Self {
+            name,
+            name_locales,
+            can_dm: _,
+            namespace: _,
+            data,
+            examples: _,
+        } = self;
+        let mut v = Validator::default();
+
+        match data {
+            Data::Slash {
+                desc,
+                desc_locales,
+                trie,
+            } => {
+                v.slash_name(name, name);
+                v.locales(name, name_locales, true);
+                v.desc(name, desc);
+                v.desc_locales(name, desc_locales);
+                v.trie(name, trie);
+            },
+            Data::User | Data::Message => {
+                v.menu_name(name, name);
+                v.locales(name, name_locales, false);
+            },
+        }
+
+        if v.total_len > MAX_TOTAL_LEN {
+            v.errors
+                .push(ValidationError::TooLong(name.clone(), v.total_len));
+        }
+
+        if v.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(v.errors)
+        }
+    }
+
+    /// Check that the subcommands and parameters read by a handler, as
+    /// reported by [`CommandArgs::visited`](super::super::args::CommandArgs::visited),
+    /// match those registered for this command
+    ///
+    /// # Errors
+    /// This method returns an error listing every parameter or subcommand that
+    /// is registered and read differently.
+    pub fn validate_args(&self, visited: &Visited) -> Result<(), Vec<ValidationError>> {
+        let path = match self.namespace {
+            Some(ref ns) => format!("{ns} {}", self.name),
+            None => self.name.clone(),
+        };
+        let mut errors = vec![];
+
+        match self.data {
+            Data::Slash { ref trie, .. } => check_visited(&mut errors, &path, trie, visited),
+            Data::User | Data::Message => {
+                if *visited != Visited::Leaf(vec![]) {
+                    errors.push(ValidationError::Shape(path));
+                }
+            },
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            super::args::{ArgKind, Visited, VisitedArg},
+            prelude::*,
+        },
+        CommandInfo, ValidationError,
+    };
+
+    #[test]
+    fn test_validate() {
+        let ok = CommandInfo::build_slash("say", "Say something", |a| {
+            a.string("message", "The message", true, ..)
+                .bool("quiet", "Whether to whisper", false)
+        })
+        .unwrap();
+        assert_eq!(ok.validate(), Ok(()));
+
+        let bad = CommandInfo::build_slash("Say", "", |a| {
+            a.bool("quiet", "Whether to whisper", false)
+                .string("message", "The message", true, ..)
+        })
+        .unwrap();
+        assert_eq!(
+            bad.validate(),
+            Err(vec![
+                ValidationError::Name("Say".into(), "Say".into()),
+                ValidationError::Description("Say".into(), 0),
+                ValidationError::RequiredAfterOptional("Say message".into(), "message".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_args() {
+        let info = CommandInfo::build_slash("say", "Say something", |a| {
+            a.string("message", "The message", true, ..)
+                .bool("quiet", "Whether to whisper", false)
+                .int("times", "How many times", false, ..)
+        })
+        .unwrap();
+
+        let arg = |name, kind, required| VisitedArg {
+            name,
+            kind,
+            required,
+        };
+
+        assert_eq!(
+            info.validate_args(&Visited::Leaf(vec![
+                arg("message", ArgKind::String, true),
+                arg("quiet", ArgKind::Bool, false),
+                arg("times", ArgKind::Int, false),
+            ])),
+            Ok(())
+        );
+
+        assert_eq!(
+            info.validate_args(&Visited::Leaf(vec![
+                arg("message", ArgKind::String, false),
+                arg("quiet", ArgKind::String, false),
+                arg("loud", ArgKind::Bool, false),
+            ])),
+            Err(vec![
+                ValidationError::Required("say message".into(), true, false),
+                ValidationError::Kind("say quiet".into(), ArgKind::Bool, ArgKind::String),
+                ValidationError::Unregistered("say loud".into()),
+                ValidationError::Unread("say times".into()),
+            ])
+        );
+
+        assert_eq!(
+            info.validate_args(&Visited::Branch(vec![])),
+            Err(vec![ValidationError::Shape("say".into())])
+        );
+    }
+}
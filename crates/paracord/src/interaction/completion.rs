@@ -1,5 +1,14 @@
 //! Types for responding to autocomplete interactions
 
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::{GuildId, UserId};
+
 /// A single completion list entry
 #[derive(Debug, serde::Serialize)]
 pub struct Completion {
@@ -41,11 +50,195 @@ impl From<f64> for CompletionValue {
     fn from(value: f64) -> Self { Self::Real(value) }
 }
 
+/// The user and guild a completion list is being ranked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RankTarget {
+    /// The user requesting completions
+    pub user: UserId,
+    /// The guild the completions were requested in, if any
+    pub guild: Option<GuildId>,
+}
+
+/// A pluggable strategy for ordering completion entries
+pub trait Ranking: fmt::Debug + Send + Sync {
+    /// Sort the given entries in place, most relevant first
+    ///
+    /// Entries ranked equally keep their relative order, so callers can rank
+    /// a list already sorted by another measure, such as similarity to what
+    /// the user has typed.
+    fn rank(&self, target: RankTarget, entries: &mut [Completion]);
+}
+
+/// The number of entries a [`History`] retains per user
+const USER_HISTORY: usize = 64;
+/// The number of entries a [`History`] retains per guild
+const GUILD_HISTORY: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+struct Counter {
+    score: f64,
+    at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Counters(HashMap<String, Counter>);
+
+impl Counters {
+    fn get(&self, name: &str, now: Instant, half_life: Duration) -> f64 {
+        self.0.get(name).map_or(0.0, |c| {
+            let halves = now.saturating_duration_since(c.at).as_secs_f64()
+                / half_life.as_secs_f64();
+            c.score * 0.5_f64.powf(halves)
+        })
+    }
+
+    fn bump(&mut self, name: &str, now: Instant, half_life: Duration, cap: usize) {
+        let score = self.get(name, now, half_life) + 1.0;
+        self.0.insert(name.into(), Counter { score, at: now });
+
+        if self.0.len() > cap {
+            let weakest = self
+                .0
+                .keys()
+                .min_by(|a, b| {
+                    self.get(a, now, half_life)
+                        .total_cmp(&self.get(b, now, half_life))
+                })
+                .cloned();
+
+            if let Some(weakest) = weakest {
+                self.0.remove(&weakest);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct HistoryInner {
+    users: HashMap<UserId, Counters>,
+    guilds: HashMap<GuildId, Counters>,
+}
+
+/// A [`Ranking`] ordering entries by how often and how recently the requesting
+/// user chose them, then by how popular they are in the guild, then by name
+///
+/// Choices count for half as much after each `half_life` has passed, and only
+/// the most relevant entries are kept for each user and guild.  Entries are
+/// matched by name.
+///
+/// **NOTE:** History is held in memory, and is lost on restart.
+#[derive(Debug)]
+pub struct History {
+    half_life: Duration,
+    inner: Mutex<HistoryInner>,
+}
+
+impl History {
+    /// Construct an empty history with the given decay rate
+    #[must_use]
+    pub fn new(half_life: Duration) -> Self {
+        Self {
+            half_life,
+            inner: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<HistoryInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record that the given user chose the entry with the given name
+    pub fn record(&self, target: RankTarget, name: &str) {
+        let now = Instant::now();
+        let mut inner = self.lock();
+
+        inner
+            .users
+            .entry(target.user)
+            .or_default()
+            .bump(name, now, self.half_life, USER_HISTORY);
+
+        if let Some(guild) = target.guild {
+            inner
+                .guilds
+                .entry(guild)
+                .or_default()
+                .bump(name, now, self.half_life, GUILD_HISTORY);
+        }
+    }
+
+    /// List the names of every entry the given user has chosen, most relevant
+    /// first
+    #[must_use]
+    pub fn user_entries(&self, user: UserId) -> Vec<String> {
+        let now = Instant::now();
+        let inner = self.lock();
+        let Some(counters) = inner.users.get(&user) else {
+            return vec![];
+        };
+
+        let mut names: Vec<_> = counters.0.keys().cloned().collect();
+        names.sort_by(|a, b| {
+            counters
+                .get(b, now, self.half_life)
+                .total_cmp(&counters.get(a, now, self.half_life))
+                .then_with(|| a.cmp(b))
+        });
+        names
+    }
+
+    /// Remove an entry from the given user's history, returning true if it was
+    /// present
+    ///
+    /// The entry still counts towards its popularity in the guilds it was
+    /// chosen in.
+    pub fn forget(&self, user: UserId, name: &str) -> bool {
+        self.lock()
+            .users
+            .get_mut(&user)
+            .map_or(false, |c| c.0.remove(name).is_some())
+    }
+}
+
+impl Ranking for History {
+    fn rank(&self, target: RankTarget, entries: &mut [Completion]) {
+        let now = Instant::now();
+        let inner = self.lock();
+        let empty = Counters::default();
+        let user = inner.users.get(&target.user).unwrap_or(&empty);
+        let guild = target
+            .guild
+            .and_then(|g| inner.guilds.get(&g))
+            .unwrap_or(&empty);
+
+        let score = |c: &Completion| {
+            (
+                user.get(&c.name, now, self.half_life),
+                guild.get(&c.name, now, self.half_life),
+            )
+        };
+
+        entries.sort_by(|a, b| {
+            let (a_user, a_guild) = score(a);
+            let (b_user, b_guild) = score(b);
+
+            b_user
+                .total_cmp(&a_user)
+                .then_with(|| b_guild.total_cmp(&a_guild))
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use serenity::builder::CreateAutocompleteResponse;
+    use std::time::Duration;
+
+    use serenity::{
+        builder::CreateAutocompleteResponse,
+        model::id::{GuildId, UserId},
+    };
 
-    use super::Completion;
+    use super::{Completion, History, RankTarget, Ranking};
 
     fn assert(
         actual: &[Completion],
@@ -83,4 +276,40 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn test_history() {
+        let history = History::new(Duration::from_secs(3600));
+        let me = RankTarget {
+            user: UserId(1),
+            guild: Some(GuildId(1)),
+        };
+        let them = RankTarget {
+            user: UserId(2),
+            ..me
+        };
+
+        history.record(me, "b");
+        history.record(them, "c");
+        history.record(them, "c");
+        history.record(them, "d");
+
+        let mut entries: Vec<_> = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| Completion {
+                name: s.into(),
+                value: s.into(),
+            })
+            .collect();
+        history.rank(me, &mut entries);
+
+        let names: Vec<_> = entries.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["b", "c", "d", "a", "e"]);
+
+        assert_eq!(history.user_entries(them.user), ["c", "d"]);
+        assert!(history.forget(them.user, "c"));
+        assert!(!history.forget(them.user, "c"));
+        assert_eq!(history.user_entries(them.user), ["d"]);
+        assert!(history.user_entries(UserId(3)).is_empty());
+    }
 }
@@ -0,0 +1,205 @@
+//! Diagnostics for custom ID payloads which could not be dispatched, or whose
+//! handler could not parse the interaction they arrived with
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::{self, Write},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use super::{response::id, rpc::Label};
+
+/// The number of failure samples retained by a [`DecodeLog`]
+pub const SAMPLE_CAPACITY: usize = 32;
+
+/// The kind of interaction whose payload failed to dispatch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    /// A message component interaction
+    Component,
+    /// A modal-submit interaction
+    Modal,
+}
+
+/// The reason a payload failed to dispatch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Reason {
+    /// The payload could not be decoded
+    Malformed,
+    /// The payload was decoded but contained no recognized variant
+    Unknown,
+    /// The payload was decoded but no handler was registered for it
+    Unhandled,
+    /// The payload was dispatched, but its handler could not parse the
+    /// interaction
+    Rejected,
+}
+
+/// The dimensions a [`DecodeLog`] counts failures by
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bucket {
+    /// The kind of interaction the payload belonged to
+    pub kind: Kind,
+    /// Why the payload could not be dispatched
+    pub reason: Reason,
+    /// The format byte of the payload, if one could be read
+    pub format: Option<u8>,
+    /// The handler the payload was addressed to, if it could be decoded
+    pub handler: Option<String>,
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            kind,
+            reason,
+            format,
+            handler,
+        } = self;
+
+        write!(f, "{kind:?}/{reason:?} fmt={format:?}")?;
+        if let Some(handler) = handler {
+            write!(f, " handler={handler}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A single recorded dispatch failure
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// The bucket this failure was counted in
+    pub bucket: Bucket,
+    /// The error message produced by the decoder or handler, if any
+    pub error: Option<String>,
+    /// The raw (pre-decompression) bytes of the payload
+    pub raw: Vec<u8>,
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { bucket, error, raw } = self;
+
+        write!(f, "{bucket}")?;
+        if let Some(error) = error {
+            write!(f, " ({error})")?;
+        }
+        f.write_char(':')?;
+        for b in raw {
+            write!(f, " {b:02x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    counts: BTreeMap<Bucket, u64>,
+    samples: VecDeque<Sample>,
+}
+
+/// A thread-safe record of custom ID payloads which could not be dispatched,
+/// counting failures by kind, reason, format and handler and retaining the most
+/// recent samples
+#[derive(Debug, Default)]
+pub struct DecodeLog(Mutex<Inner>);
+
+impl DecodeLog {
+    fn lock(&self) -> MutexGuard<Inner> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(super) fn record(
+        &self,
+        kind: Kind,
+        reason: Reason,
+        id: &id::Id<'_>,
+        handler: Option<&Label>,
+        error: Option<&dyn fmt::Display>,
+    ) {
+        let raw = id::read_raw(id).unwrap_or_default();
+        let bucket = Bucket {
+            kind,
+            reason,
+            format: raw.first().copied(),
+            handler: handler.map(|l| l.handler.clone().into_owned()),
+        };
+        let sample = Sample {
+            bucket: bucket.clone(),
+            error: error.map(ToString::to_string),
+            raw,
+        };
+
+        let mut inner = self.lock();
+        *inner.counts.entry(bucket).or_default() += 1;
+        if inner.samples.len() >= SAMPLE_CAPACITY {
+            inner.samples.pop_front();
+        }
+        inner.samples.push_back(sample);
+    }
+
+    /// Get the number of failures recorded in each bucket
+    #[must_use]
+    pub fn counts(&self) -> BTreeMap<Bucket, u64> { self.lock().counts.clone() }
+
+    /// Get the most recently recorded failures, oldest first
+    #[must_use]
+    pub fn samples(&self) -> Vec<Sample> {
+        self.lock().samples.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bucket, DecodeLog, Kind, Reason, SAMPLE_CAPACITY};
+    use crate::interaction::{
+        response::id::{self, Field, Id},
+        rpc::Label,
+        testing::{Component, ComponentPayload},
+    };
+
+    #[test]
+    fn test_counts() {
+        let log = DecodeLog::default();
+        let valid = id::write(
+            &Component {
+                payload: Some(ComponentPayload::A(1)),
+            },
+            Field::CustomId,
+        )
+        .unwrap();
+        // SAFETY: the ID is only read back
+        let invalid = unsafe { Id::from_inner("".into()) };
+        let (a, b) = (Label::new("a"), Label::new("b").variant("x"));
+
+        for _ in 0..SAMPLE_CAPACITY {
+            log.record(Kind::Component, Reason::Rejected, &valid, Some(&a), Some(&"oops"));
+        }
+        log.record(Kind::Component, Reason::Rejected, &valid, Some(&b), None);
+        log.record(Kind::Modal, Reason::Malformed, &invalid, None, None);
+
+        let format = id::read_raw(&valid).unwrap().first().copied();
+        let bucket = |kind, reason, format, handler: Option<&str>| Bucket {
+            kind,
+            reason,
+            format,
+            handler: handler.map(Into::into),
+        };
+        assert_eq!(log.counts().into_iter().collect::<Vec<_>>(), [
+            (
+                bucket(Kind::Component, Reason::Rejected, format, Some("a")),
+                u64::try_from(SAMPLE_CAPACITY).unwrap()
+            ),
+            (bucket(Kind::Component, Reason::Rejected, format, Some("b")), 1),
+            (bucket(Kind::Modal, Reason::Malformed, None, None), 1),
+        ]);
+
+        // Only the newest samples are kept
+        let samples = log.samples();
+        assert_eq!(samples.len(), SAMPLE_CAPACITY);
+        assert_eq!(samples[0].error.as_deref(), Some("oops"));
+        assert_eq!(samples[SAMPLE_CAPACITY - 1].bucket.kind, Kind::Modal);
+    }
+}
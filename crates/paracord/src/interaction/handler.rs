@@ -1,9 +1,10 @@
 //! Traits for defining handler logic for various interactions
 
-use std::{fmt, sync::Arc};
+use std::{any::Any, fmt, sync::Arc, time::Duration};
 
 use serenity::{
-    client::Context,
+    client::{bridge::gateway::ShardMessenger, Context},
+    http::HttpBuilder,
     model::{
         application::interaction::{
             application_command::ApplicationCommandInteraction,
@@ -14,7 +15,17 @@ use serenity::{
     },
 };
 
-use super::{command::CommandInfo, completion::Completion, response, rpc, visitor};
+use super::{
+    args,
+    command::{CommandInfo, NamespaceInfo},
+    completion::Completion,
+    decode_log::DecodeLog,
+    legacy::LegacyIds,
+    response,
+    response::{sink::MemorySink, BorrowedResponder, BorrowingResponder, InitResponder},
+    rpc, visitor,
+};
+use tracing::Instrument;
 
 /// Helper trait for constructing an error response
 pub trait IntoErr<E> {
@@ -26,12 +37,18 @@ pub trait IntoErr<E> {
 /// be created
 #[derive(Debug)]
 pub struct Handlers<S: rpc::Schema> {
+    /// Top-level namespaces available for grouping chat input commands
+    pub namespaces: Vec<NamespaceInfo>,
     /// Command (and autocomplete) interaction handlers
     pub commands: Vec<Arc<dyn CommandHandler<S>>>,
     /// Component interaction handlers
     pub components: Vec<Arc<dyn RpcHandler<S, S::ComponentKey>>>,
     /// Modal-submit interaction handlers
     pub modals: Vec<Arc<dyn RpcHandler<S, S::ModalKey>>>,
+    /// Log to record component and modal payloads which fail to dispatch
+    pub decode_log: Arc<DecodeLog>,
+    /// Shims for component IDs predating the current payload scheme
+    pub legacy: Arc<LegacyIds<S>>,
 }
 
 // TODO: Component and Modal should have dedicated visitors
@@ -56,6 +73,50 @@ pub enum HandlerError<'a, S, I> {
     /// An unhandled error occurred
     #[error("Unexpected error: {0}")]
     Other(#[from] anyhow::Error),
+    /// The interaction was passed on to the handler for another payload
+    #[error("Interaction forwarded to {0:?}")]
+    Forward(Forward),
+}
+
+impl<'a, S: rpc::Schema> HandlerError<'a, S, MessageComponentInteraction>
+where S::ComponentPayload: Send + Sync + 'static
+{
+    /// Pass a component interaction on to the handler registered for another
+    /// payload
+    ///
+    /// This allows a generic component (such as a confirmation dialog or a
+    /// paginator) to hand control back to the command that created it: the
+    /// generic component's payload can embed the originating command's payload
+    /// as a continuation, and return it from here once finished.  The registry
+    /// dispatches the forwarded payload as if it had been decoded from the
+    /// interaction's custom ID, with the same visitor and responder, so the
+    /// target handler may still respond as long as the forwarding handler has
+    /// not.
+    #[inline]
+    #[must_use]
+    pub fn forward(payload: S::ComponentPayload) -> Self {
+        Self::Forward(Forward(Box::new(payload)))
+    }
+}
+
+trait ForwardPayload: fmt::Debug + Send + Sync {
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: fmt::Debug + Send + Sync + 'static> ForwardPayload for T {
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+}
+
+/// An RPC payload passed from one handler to another
+///
+/// See [`HandlerError::forward`].
+#[derive(Debug)]
+pub struct Forward(Box<dyn ForwardPayload>);
+
+impl Forward {
+    pub(super) fn downcast<P: 'static>(self) -> Option<P> {
+        self.0.into_any().downcast().ok().map(|p| *p)
+    }
 }
 
 impl<'a, S, I> IntoErr<HandlerError<'a, S, I>> for response::CreatedResponder<'a, S, I> {
@@ -110,6 +171,19 @@ pub trait CommandHandler<S>: fmt::Debug + Send + Sync {
         None
     }
 
+    /// Describe the subcommands and parameters read by
+    /// [`respond`](Self::respond), so
+    /// [`Registry::validate_commands`](super::registry::Registry::validate_commands)
+    /// can check them against the registered ones
+    ///
+    /// Handlers which extract their parameters with a
+    /// [`CommandArgs`](super::args::CommandArgs) type should return its
+    /// [`visited`](super::args::CommandArgs::visited) description.  The
+    /// default behavior of this method is to return `None`, skipping the
+    /// check.
+    #[inline]
+    fn visited_args(&self) -> Option<args::Visited> { None }
+
     /// Respond to an autocomplete interaction
     ///
     /// The default behavior of this method is to return an empty list.
@@ -124,6 +198,21 @@ pub trait CommandHandler<S>: fmt::Debug + Send + Sync {
         Ok(vec![])
     }
 
+    /// Load any expensive resources this command needs ahead of its first use
+    ///
+    /// This is run in the background by [`Registry::warmup`] after startup.
+    /// Handlers should still load their resources lazily, since an
+    /// interaction may arrive before warmup finishes.  The default behavior of
+    /// this method is to do nothing.
+    ///
+    /// [`Registry::warmup`]: super::registry::Registry::warmup
+    #[inline]
+    async fn warmup(&self, ctx: &Context) -> Result<(), anyhow::Error> {
+        #[allow(let_underscore_drop)]
+        let _ = (ctx,);
+        Ok(())
+    }
+
     /// Respond to a command interaction
     // TODO: set timeout for non-deferred commands?
     async fn respond<'a>(
@@ -134,6 +223,193 @@ pub trait CommandHandler<S>: fmt::Debug + Send + Sync {
     ) -> CommandResult<'a, S>;
 }
 
+/// How long a [`Shadow`] candidate may run before it is abandoned
+const SHADOW_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A command handler which serves interactions with an existing handler while
+/// running a candidate replacement against the same interactions
+///
+/// The candidate runs in the background once the existing handler has
+/// returned, so it never delays the user's response, and is abandoned if it
+/// runs for longer than a minute.  It responds through a [`MemorySink`], so
+/// its responses are never sent, and any difference between the outcomes of
+/// the two handlers is logged.  Registration data and autocomplete are always
+/// taken from the existing handler.
+///
+/// The candidate is given a sandboxed client context: it shares the cache,
+/// but every HTTP request it makes fails, its shard messenger is
+/// disconnected, and its client data is empty.  A candidate depending on
+/// shared client data (such as a voice client) will therefore fail where the
+/// existing handler succeeds, and should be shadowed with that dependency
+/// passed to it directly instead.
+#[derive(Debug)]
+pub struct Shadow<S> {
+    primary: Arc<dyn CommandHandler<S>>,
+    candidate: Arc<dyn CommandHandler<S>>,
+}
+
+impl<S> Shadow<S> {
+    /// Serve interactions with `primary`, shadowing it with `candidate`
+    #[inline]
+    #[must_use]
+    pub fn new(primary: Arc<dyn CommandHandler<S>>, candidate: Arc<dyn CommandHandler<S>>) -> Self {
+        Self { primary, candidate }
+    }
+}
+
+/// Build a copy of a client context which can read the cache but can't act on
+/// anything
+fn sandbox(ctx: &Context) -> Context {
+    let (tx, _) = serenity::futures::channel::mpsc::unbounded();
+    // Port zero is never listening, so requests fail without leaving the host
+    let http = HttpBuilder::new("")
+        .proxy("http://127.0.0.1:0")
+        .unwrap_or_else(|e| unreachable!("{e}"))
+        .ratelimiter_disabled(true)
+        .build();
+
+    Context {
+        data: Arc::default(),
+        shard: ShardMessenger::new(tx),
+        shard_id: ctx.shard_id,
+        http: Arc::new(http),
+        cache: Arc::clone(&ctx.cache),
+    }
+}
+
+/// The result of a handler, reduced to what a [`Shadow`] candidate should
+/// reproduce
+#[derive(Debug)]
+enum Outcome {
+    Ok,
+    Parse(String),
+    User(&'static str),
+    Other(String),
+    Forward,
+}
+
+impl Outcome {
+    fn of<T, S, I>(res: &Result<T, HandlerError<'_, S, I>>) -> Self {
+        match res {
+            Ok(_) => Self::Ok,
+            Err(HandlerError::Parse(e)) => Self::Parse(e.to_string()),
+            Err(HandlerError::User(m, _)) => Self::User(m),
+            Err(HandlerError::Other(e)) => Self::Other(format!("{e:#}")),
+            Err(HandlerError::Forward(_)) => Self::Forward,
+        }
+    }
+
+    /// Compare two outcomes
+    ///
+    /// The messages of parse and unexpected errors often embed IDs or other
+    /// details of the interaction, so only their kinds are compared.
+    fn matches(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Ok, Self::Ok)
+            | (Self::Parse(_), Self::Parse(_))
+            | (Self::Other(_), Self::Other(_))
+            | (Self::Forward, Self::Forward) => true,
+            (Self::User(a), Self::User(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<S: rpc::Schema + Send + Sync + 'static> Shadow<S> {
+    fn spawn_candidate(
+        &self,
+        ctx: &Context,
+        visitor: &CommandVisitor<'_>,
+    ) -> tokio::task::JoinHandle<Outcome> {
+        let candidate = Arc::clone(&self.candidate);
+        let ctx = sandbox(ctx);
+        let int = visitor.int.clone();
+        let namespaced = visitor.is_namespaced();
+
+        tokio::task::spawn(
+            async move {
+                let sink = MemorySink::default();
+                let mut vis = if namespaced {
+                    CommandVisitor::new_namespaced(&int)
+                } else {
+                    CommandVisitor::new(&int)
+                };
+                let mut responder = BorrowedResponder::Init(InitResponder::new(&sink, &int));
+
+                let res = candidate
+                    .respond(&ctx, &mut vis, BorrowingResponder::new(&mut responder))
+                    .await;
+                let res = res.and_then(|_| vis.finish().map_err(Into::into));
+                tracing::trace!(calls = ?sink.calls(), "Shadow handler responses");
+
+                Outcome::of(&res)
+            }
+            .instrument(tracing::Span::current()),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: rpc::Schema + Send + Sync + 'static> CommandHandler<S> for Shadow<S> {
+    #[inline]
+    fn register_global(&self) -> CommandInfo { self.primary.register_global() }
+
+    #[inline]
+    fn register_guild(&self, id: GuildId) -> Option<CommandInfo> { self.primary.register_guild(id) }
+
+    #[inline]
+    fn visited_args(&self) -> Option<args::Visited> { self.primary.visited_args() }
+
+    #[inline]
+    async fn complete(
+        &self,
+        ctx: &Context,
+        visitor: &mut CompletionVisitor<'_>,
+    ) -> CompletionResult {
+        self.primary.complete(ctx, visitor).await
+    }
+
+    async fn warmup(&self, ctx: &Context) -> Result<(), anyhow::Error> {
+        if let Err(err) = self.candidate.warmup(ctx).await {
+            tracing::warn!(?err, "Error warming up shadow handler");
+        }
+
+        self.primary.warmup(ctx).await
+    }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a, S>,
+    ) -> CommandResult<'a, S> {
+        let res = self.primary.respond(ctx, visitor, responder).await;
+        let primary = Outcome::of(&res);
+        let mut shadow = self.spawn_candidate(ctx, visitor);
+
+        tokio::task::spawn(
+            async move {
+                match tokio::time::timeout(SHADOW_TIMEOUT, &mut shadow).await {
+                    Ok(Ok(candidate)) if primary.matches(&candidate) => {
+                        tracing::debug!(?primary, ?candidate, "Shadow handler outcome matched");
+                    },
+                    Ok(Ok(candidate)) => {
+                        tracing::warn!(?primary, ?candidate, "Shadow handler outcome differed");
+                    },
+                    Ok(Err(err)) => tracing::error!(%err, "Shadow handler panicked"),
+                    Err(_) => {
+                        shadow.abort();
+                        tracing::warn!("Shadow handler timed out");
+                    },
+                }
+            }
+            .instrument(tracing::Span::current()),
+        );
+
+        res
+    }
+}
+
 /// An error returned from a component interaction handler
 pub type ComponentError<'a, S> = HandlerError<'a, S, MessageComponentInteraction>;
 /// An error returned from a modal-submit interaction handler
@@ -169,3 +445,106 @@ pub trait RpcHandler<S, K: rpc::Key>: fmt::Debug + Send + Sync {
         responder: response::BorrowingResponder<'_, 'a, S, K::Interaction>,
     ) -> ResponseResult<'a, S, K::Interaction>;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serenity::{client::Context, json::json, prelude::TypeMapKey};
+    use tokio::sync::mpsc;
+
+    use super::{
+        CommandHandler, CommandResponder, CommandResult, CommandVisitor, HandlerError, Outcome,
+        Shadow,
+    };
+    use crate::interaction::{
+        command::CommandInfo,
+        response::{sink::MemorySink, BorrowedResponder, BorrowingResponder, InitResponder},
+        testing::{self, Schema},
+    };
+
+    struct Marker;
+
+    impl TypeMapKey for Marker {
+        type Value = ();
+    }
+
+    fn info() -> CommandInfo { CommandInfo::build_slash("test", "Test", |a| a).unwrap() }
+
+    #[derive(Debug)]
+    struct Reply;
+
+    #[async_trait::async_trait]
+    impl CommandHandler<Schema> for Reply {
+        fn register_global(&self) -> CommandInfo { info() }
+
+        async fn respond<'a>(
+            &self,
+            _: &Context,
+            _: &mut CommandVisitor<'_>,
+            responder: CommandResponder<'_, 'a, Schema>,
+        ) -> CommandResult<'a, Schema> {
+            let responder = responder
+                .create_message(crate::interaction::response::Message::plain("hi"))
+                .await
+                .map_err(anyhow::Error::from)?;
+
+            Ok(responder.into())
+        }
+    }
+
+    /// Reports what it could see of its context, then never finishes
+    #[derive(Debug)]
+    struct Stall(mpsc::UnboundedSender<(bool, bool)>);
+
+    #[async_trait::async_trait]
+    impl CommandHandler<Schema> for Stall {
+        fn register_global(&self) -> CommandInfo { info() }
+
+        async fn respond<'a>(
+            &self,
+            ctx: &Context,
+            _: &mut CommandVisitor<'_>,
+            _: CommandResponder<'_, 'a, Schema>,
+        ) -> CommandResult<'a, Schema> {
+            let data = ctx.data.read().await.contains_key::<Marker>();
+            let http = ctx.http.get_current_user().await.is_ok();
+            self.0.send((data, http)).unwrap();
+
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shadow() {
+        let ctx = testing::context();
+        ctx.data.write().await.insert::<Marker>(());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let shadow = Shadow::new(Arc::new(Reply), Arc::new(Stall(tx)));
+
+        let int = testing::command("test", &json!([]));
+        let sink = MemorySink::default();
+        let mut vis = CommandVisitor::new(&int);
+        let mut responder = BorrowedResponder::Init(InitResponder::new(&sink, &int));
+
+        // The primary's result is returned without waiting on the candidate
+        shadow
+            .respond(&ctx, &mut vis, BorrowingResponder::new(&mut responder))
+            .await
+            .unwrap();
+        assert_eq!(sink.calls().len(), 1);
+
+        // The candidate can see neither the client data nor the API
+        assert_eq!(rx.recv().await, Some((false, false)));
+    }
+
+    #[test]
+    fn test_outcome() {
+        type Res = Result<(), HandlerError<'static, Schema, ()>>;
+
+        let other = |m: &str| Outcome::of(&Res::Err(anyhow::anyhow!("{m}").into()));
+        assert!(other("Unknown message 1234").matches(&other("Unknown message 5678")));
+        assert!(!other("oops").matches(&Outcome::of(&Res::Ok(()))));
+    }
+}
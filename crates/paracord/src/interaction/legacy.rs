@@ -0,0 +1,98 @@
+//! Compatibility shims for component custom IDs written before the current
+//! payload scheme
+//!
+//! Buttons on old messages keep the custom ID they were created with, so
+//! changing how IDs are encoded would otherwise break them.  A [`LegacyIds`]
+//! set maps the old ID patterns to payloads, and counts how often each is
+//! used so that it is clear when a shim can be dropped.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use super::rpc::Schema;
+
+type Constructor<P> = Box<dyn Fn(&str) -> Option<P> + Send + Sync>;
+
+struct Shim<P> {
+    prefix: String,
+    ctor: Constructor<P>,
+}
+
+/// A set of legacy custom ID patterns and the payload constructors they map to
+pub struct LegacyIds<S: Schema> {
+    components: Vec<Shim<S::ComponentPayload>>,
+    hits: Mutex<BTreeMap<String, u64>>,
+}
+
+impl<S: Schema> Default for LegacyIds<S> {
+    fn default() -> Self {
+        Self {
+            components: vec![],
+            hits: Mutex::default(),
+        }
+    }
+}
+
+impl<S: Schema> fmt::Debug for LegacyIds<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LegacyIds")
+            .field(
+                "components",
+                &self.components.iter().map(|s| &s.prefix).collect::<Vec<_>>(),
+            )
+            .field("hits", &*self.lock())
+            .finish()
+    }
+}
+
+impl<S: Schema> LegacyIds<S> {
+    fn lock(&self) -> MutexGuard<BTreeMap<String, u64>> {
+        self.hits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Add a shim for component IDs starting with `prefix`
+    ///
+    /// The constructor receives the remainder of the ID after the prefix, and
+    /// may return `None` to reject it.  Shims are tried in the order they were
+    /// added, and only for IDs which are not valid current payloads.
+    #[must_use]
+    pub fn component(
+        mut self,
+        prefix: impl Into<String>,
+        ctor: impl Fn(&str) -> Option<S::ComponentPayload> + Send + Sync + 'static,
+    ) -> Self {
+        self.components.push(Shim {
+            prefix: prefix.into(),
+            ctor: Box::new(ctor),
+        });
+        self
+    }
+
+    pub(super) fn resolve_component(&self, id: &str) -> Option<S::ComponentPayload> {
+        let (prefix, payload) = self.components.iter().find_map(|Shim { prefix, ctor }| {
+            id.strip_prefix(prefix.as_str())
+                .and_then(ctor)
+                .map(|p| (prefix, p))
+        })?;
+
+        tracing::info!(prefix, "Resolved legacy component ID");
+        *self.lock().entry(prefix.clone()).or_default() += 1;
+        Some(payload)
+    }
+
+    /// Get the number of times each shim has been used, keyed by prefix
+    ///
+    /// Shims with no recorded uses are included with a count of zero.
+    #[must_use]
+    pub fn hits(&self) -> BTreeMap<String, u64> {
+        let mut hits = self.lock().clone();
+        for Shim { prefix, .. } in &self.components {
+            hits.entry(prefix.clone()).or_default();
+        }
+        hits
+    }
+}
+
@@ -1,11 +1,16 @@
 //! Types and support traits for responding to application interaction events
 
+pub mod args;
 pub mod command;
 pub mod completion;
+pub mod decode_log;
 pub mod handler;
+pub mod legacy;
 mod registry;
 pub mod response;
 pub mod rpc;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod visitor;
 
 pub use registry::Registry;
@@ -1,13 +1,15 @@
 use std::{
-    collections::{BinaryHeap, HashMap, HashSet},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
     fmt::{self, Write},
     sync::Arc,
+    time::Instant,
 };
 
 use anyhow::Context as _;
 use ordered_float::OrderedFloat;
 use serenity::{
     client::{Cache, Context},
+    http::Http,
     model::{
         application::{
             command::{Command, CommandOptionType, CommandType},
@@ -32,7 +34,9 @@ use tokio::sync::RwLock;
 use super::{
     command,
     command::RegisteredCommand,
+    decode_log::{DecodeLog, Kind, Reason},
     handler,
+    legacy::LegacyIds,
     response::{
         id, prelude::*, BorrowedResponder, BorrowingResponder, InitResponder, Message, ModalSource,
         ResponseError,
@@ -239,6 +243,26 @@ fn mc_name<S: Schema>(mc: &MessageComponentInteraction) -> String {
 #[inline]
 fn mc_id(mc: &MessageComponentInteraction) -> String { format!("{}:{}", mc.id, mc.message.id) }
 
+/// Get the position of the interacted component among all the components of
+/// its message
+fn mc_index(mc: &MessageComponentInteraction) -> Option<usize> {
+    use serenity::model::application::component::ActionRowComponent;
+
+    mc.message
+        .components
+        .iter()
+        .flat_map(|r| &r.components)
+        .position(|c| {
+            let id = match c {
+                ActionRowComponent::Button(b) => b.custom_id.as_deref(),
+                ActionRowComponent::SelectMenu(m) => m.custom_id.as_deref(),
+                ActionRowComponent::InputText(t) => Some(t.custom_id.as_str()),
+                _ => None,
+            };
+            id == Some(mc.data.custom_id.as_str())
+        })
+}
+
 #[inline]
 fn mc_issuer(cache: &Cache, mc: &MessageComponentInteraction) -> String {
     write_string(|s| write_issuer(s, cache, &mc.user, mc.guild_id, mc.channel_id))
@@ -266,8 +290,30 @@ fn ms_issuer(cache: &Cache, ms: &ModalSubmitInteraction) -> String {
     write_string(|s| write_issuer(s, cache, &ms.user, ms.guild_id, ms.channel_id))
 }
 
+/// The maximum number of times a single component interaction may be
+/// forwarded between handlers, to guard against forwarding cycles
+const MAX_FORWARDS: usize = 8;
+
 type CommandHandler<S> = Arc<dyn handler::CommandHandler<S>>;
-type CommandHandlerMap<S> = HashMap<CommandId, CommandHandler<S>>;
+type CommandHandlerMap<S> = HashMap<CommandId, CommandEntry<S>>;
+type CommandEntryMap<S> = HashMap<String, (CommandEntry<S>, command::CommandInfo)>;
+
+#[derive(Debug)]
+enum CommandEntry<S> {
+    Command(CommandHandler<S>),
+    Namespace(HashMap<String, CommandHandler<S>>),
+}
+
+impl<S> Clone for CommandEntry<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Command(h) => Self::Command(Arc::clone(h)),
+            Self::Namespace(m) => {
+                Self::Namespace(m.iter().map(|(k, v)| (k.clone(), Arc::clone(v))).collect())
+            },
+        }
+    }
+}
 type RpcHandler<S, K> = Arc<dyn handler::RpcHandler<S, K>>;
 type RpcHandlerMap<S, K> = HashMap<K, RpcHandler<S, K>>;
 
@@ -292,9 +338,9 @@ pub struct Registry<S: Schema> {
 }
 
 impl<S: Schema> Registry<S> {
-    #[tracing::instrument(level = "info", skip(ctx))]
+    #[tracing::instrument(level = "info", skip(http))]
     async fn patch_commands(
-        ctx: &Context,
+        http: &Http,
         init: &handler::Handlers<S>,
         guild: Option<GuildId>,
     ) -> Result<CommandHandlerMap<S>, anyhow::Error> {
@@ -302,10 +348,11 @@ impl<S: Schema> Registry<S> {
             todo!("handle guild {guild}");
         }
 
-        let handler::Handlers { commands, .. } = init;
+        let mut new = Self::collate_commands(init)?;
+        let count = new.len();
         let mut handlers = HashMap::new();
 
-        let existing = Command::get_global_application_commands(&ctx.http)
+        let existing = Command::get_global_application_commands_with_localizations(http)
             .await
             .context("Error fetching initial command list")?
             .into_iter()
@@ -315,26 +362,20 @@ impl<S: Schema> Registry<S> {
 
         let mut unpaired_existing: HashMap<_, _> = existing.iter().map(|r| (&r.info, r)).collect();
 
-        let mut new: HashMap<_, _> = commands
-            .iter()
-            .map(|c| {
-                let inf = c.register_global();
-                (inf.name().clone(), (c, inf))
-            })
-            .collect();
-        assert_eq!(new.len(), commands.len());
-
         let mut unpaired_new = HashSet::new();
 
         for (name, (cmd, inf)) in &new {
             if let Some(reg) = unpaired_existing.remove(inf) {
-                handlers.insert(reg.id, Arc::clone(cmd));
+                handlers.insert(reg.id, cmd.clone());
                 continue;
             }
 
             unpaired_new.insert(name.clone());
         }
 
+        let unchanged = handlers.len();
+        let mut changes: HashMap<command::Change, usize> = HashMap::new();
+
         let mut sims: BinaryHeap<_> = unpaired_existing
             .iter()
             .flat_map(|(existing, &reg)| {
@@ -358,30 +399,52 @@ impl<S: Schema> Registry<S> {
             }
 
             let (cmd, inf) = new.remove(&new_name).unwrap_or_else(|| unreachable!());
+            let change = command::change(&existing.info, &inf);
+            *changes.entry(change).or_default() += 1;
+
+            if change == command::Change::Recreate {
+                tracing::info!(
+                    ?sim,
+                    id = ?existing.id,
+                    old = ?existing.info.name(),
+                    "Recreating global command {new_name:?}"
+                );
+                Command::delete_global_application_command(http, existing.id)
+                    .await
+                    .with_context(|| format!("Error deleting command {new_name:?}"))?;
+                let res = Command::create_global_application_command(http, |c| inf.build(c))
+                    .await
+                    .with_context(|| format!("Error recreating command {new_name:?}"))?;
+                assert!(handlers.insert(res.id, cmd).is_none());
+                continue;
+            }
+
             tracing::info!(
                 ?sim,
+                ?change,
                 id = ?existing.id,
                 old = ?existing.info.name(),
                 "Updating global command {new_name:?}"
             );
-            let res =
-                Command::edit_global_application_command(&ctx.http, existing.id, |c| inf.build(c))
-                    .await
-                    .with_context(|| format!("Error updating command {new_name:?}"))?;
+            let res = Command::edit_global_application_command(http, existing.id, |c| inf.build(c))
+                .await
+                .with_context(|| format!("Error updating command {new_name:?}"))?;
             assert_eq!(existing.id, res.id);
-            assert!(handlers.insert(res.id, Arc::clone(cmd)).is_none());
+            assert!(handlers.insert(res.id, cmd).is_none());
         }
 
         assert!(unpaired_new.is_empty() || unpaired_existing.is_empty());
 
+        let (created, deleted) = (unpaired_new.len(), unpaired_existing.len());
+
         for name in unpaired_new {
             let (cmd, inf) = new.remove(&name).unwrap_or_else(|| unreachable!());
             tracing::info!("Creating global command {name:?}");
-            let res = Command::create_global_application_command(&ctx.http, |c| inf.build(c))
+            let res = Command::create_global_application_command(http, |c| inf.build(c))
                 .await
                 .with_context(|| format!("Error creating command {name:?}"))?;
 
-            assert!(handlers.insert(res.id, Arc::clone(cmd)).is_none());
+            assert!(handlers.insert(res.id, cmd).is_none());
         }
 
         for (inf, reg) in unpaired_existing {
@@ -390,15 +453,89 @@ impl<S: Schema> Registry<S> {
                 inf.name(),
                 reg.id,
             );
-            Command::delete_global_application_command(&ctx.http, reg.id)
+            Command::delete_global_application_command(http, reg.id)
                 .await
                 .with_context(|| format!("Error deleting command {:?}", inf.name()))?;
         }
 
-        assert_eq!(handlers.len(), commands.len());
+        let changed = |c| changes.get(&c).copied().unwrap_or(0);
+        tracing::info!(
+            unchanged,
+            metadata = changed(command::Change::Metadata),
+            structure = changed(command::Change::Structure),
+            recreated = changed(command::Change::Recreate),
+            created,
+            deleted,
+            "Global commands synced",
+        );
+
+        assert_eq!(handlers.len(), count);
         Ok(handlers)
     }
 
+    fn collate_commands(init: &handler::Handlers<S>) -> Result<CommandEntryMap<S>, anyhow::Error> {
+        let handler::Handlers {
+            namespaces,
+            commands,
+            ..
+        } = init;
+
+        let mut members = HashMap::new();
+        for ns in namespaces {
+            if members.insert(ns.name(), (ns, vec![])).is_some() {
+                return Err(command::NamespaceError::DuplicateNamespace(ns.name().clone()).into());
+            }
+        }
+
+        let mut entries = HashMap::new();
+        for cmd in commands {
+            let inf = cmd.register_global();
+
+            if let Some(ns) = inf.namespace() {
+                let Some((_, cmds)) = members.get_mut(ns) else {
+                    return Err(command::NamespaceError::Undeclared(
+                        ns.clone(),
+                        inf.name().clone(),
+                    )
+                    .into());
+                };
+
+                cmds.push((cmd, inf));
+                continue;
+            }
+
+            let name = inf.name().clone();
+            if entries
+                .insert(name.clone(), (CommandEntry::Command(Arc::clone(cmd)), inf))
+                .is_some()
+            {
+                anyhow::bail!("Command {name:?} registered more than once");
+            }
+        }
+
+        for (name, (ns, cmds)) in members {
+            if cmds.is_empty() {
+                tracing::warn!("Skipping namespace {name:?} with no commands");
+                continue;
+            }
+
+            let (cmds, infos): (HashMap<_, _>, Vec<_>) = cmds
+                .into_iter()
+                .map(|(cmd, inf)| ((inf.name().clone(), Arc::clone(cmd)), inf))
+                .unzip();
+            let inf = ns.merge(infos)?;
+
+            if entries
+                .insert(name.clone(), (CommandEntry::Namespace(cmds), inf))
+                .is_some()
+            {
+                anyhow::bail!("Namespace {name:?} conflicts with a command of the same name");
+            }
+        }
+
+        Ok(entries)
+    }
+
     fn collate_rpc<K: Key>(handlers: &[RpcHandler<S, K>]) -> RpcHandlerMap<S, K> {
         let mut map = HashMap::new();
 
@@ -413,23 +550,45 @@ impl<S: Schema> Registry<S> {
 
     fn resolve_command<'a>(
         map: &'a tokio::sync::RwLockReadGuard<'a, Option<CommandHandlerMap<S>>>,
-        id: CommandId,
-    ) -> Result<&'a CommandHandler<S>, &'static str> {
+        data: &CommandData,
+    ) -> Result<(&'a CommandHandler<S>, bool), &'static str> {
         let Some(ref map) = **map else {
             tracing::warn!("Rejecting command due to uninitialized registry");
             return Err("Still starting!  Please try again later.");
         };
 
-        let Some(handler) = map.get(&id) else {
+        let Some(entry) = map.get(&data.id) else {
             tracing::warn!("Rejecting unknown command");
             return Err("Unknown command - this may be a bug.");
         };
 
-        Ok(handler)
+        match entry {
+            CommandEntry::Command(handler) => Ok((handler, false)),
+            CommandEntry::Namespace(cmds) => {
+                let Some(handler) = data
+                    .options
+                    .first()
+                    .filter(|o| {
+                        matches!(
+                            o.kind,
+                            CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+                        )
+                    })
+                    .and_then(|o| cmds.get(&o.name))
+                else {
+                    tracing::warn!("Rejecting unknown namespaced command");
+                    return Err("Unknown command - this may be a bug.");
+                };
+
+                Ok((handler, true))
+            },
+        }
     }
 
     fn resolve_component<'a>(
         map: &'a tokio::sync::RwLockReadGuard<'a, Option<RpcHandlerMap<S, S::ComponentKey>>>,
+        log: &DecodeLog,
+        legacy: &LegacyIds<S>,
         id: &id::Id<'_>,
     ) -> Result<ComponentInfo<'a, S>, &'static str> {
         let Some(ref map) = **map else {
@@ -440,20 +599,25 @@ impl<S: Schema> Registry<S> {
         let payload = match id::read::<S::Component>(id)
             .map_err(Some)
             .and_then(|i| i.try_into_parts().ok_or(None))
+            .or_else(|e| legacy.resolve_component(id.as_str()).ok_or(e))
         {
             Ok(p) => p,
             Err(Some(err)) => {
                 tracing::error!(%err, "Unable to parse component ID");
+                log.record(Kind::Component, Reason::Malformed, id, None, Some(&err));
                 return Err("Unrecognized component ID format - this is a bug.");
             },
             Err(None) => {
                 tracing::warn!("Rejecting unknown (deprecated?) component ID");
+                log.record(Kind::Component, Reason::Unknown, id, None, None);
                 return Err("Invalid component ID - this feature may have been removed.");
             },
         };
 
         let Some(handler) = map.get(&(&payload).into()) else {
             tracing::warn!("Rejecting unknown component");
+            let label = S::ComponentKey::label(&payload);
+            log.record(Kind::Component, Reason::Unhandled, id, Some(&label), None);
             return Err("Unknown component - this may be a bug.");
         };
 
@@ -462,6 +626,7 @@ impl<S: Schema> Registry<S> {
 
     fn resolve_modal<'a>(
         map: &'a tokio::sync::RwLockReadGuard<'a, Option<RpcHandlerMap<S, S::ModalKey>>>,
+        log: &DecodeLog,
         id: &id::Id<'_>,
     ) -> Result<ModalInfo<'a, S>, &'static str> {
         let Some(ref map) = **map else {
@@ -476,25 +641,71 @@ impl<S: Schema> Registry<S> {
             Ok(p) => p,
             Err(Some(err)) => {
                 tracing::error!(%err, "Unable to parse modal ID");
+                log.record(Kind::Modal, Reason::Malformed, id, None, Some(&err));
                 return Err("Unrecognized modal ID format - this is a bug.");
             },
             Err(None) => {
                 tracing::warn!("Rejecting unknown (deprecated?) modal ID");
+                log.record(Kind::Modal, Reason::Unknown, id, None, None);
                 return Err("Invalid modal ID - this feature may have been removed.");
             },
         };
 
         let Some(handler) = map.get(&(&payload).into()) else {
             tracing::warn!("Rejecting unknown modal");
+            let label = S::ModalKey::label(&payload);
+            log.record(Kind::Modal, Reason::Unhandled, id, Some(&label), None);
             return Err("Unknown modal - this may be a bug.");
         };
 
         Ok((handler, source, payload))
     }
 
+    fn suggest(info: &command::CommandInfo, err: &visitor::Error) -> Option<String> {
+        match err {
+            visitor::Error::UnhandledSubcommand(path) => {
+                let (name, parent) = path.split_last()?;
+                let mut path: Vec<_> = parent.iter().map(String::as_str).collect();
+                let sugg = info.suggest_subcommand(&path, name)?;
+                path.push(sugg);
+                Some(path.join(" "))
+            },
+            visitor::Error::MissingOption(name) => info.suggest_option(name).map(Into::into),
+            visitor::Error::Trailing(names) => names
+                .iter()
+                .find_map(|n| info.suggest_option(n))
+                .map(Into::into),
+            _ => None,
+        }
+    }
+
+    fn subcmd_path(opts: &[CommandDataOption], namespaced: bool) -> Vec<&str> {
+        let mut opts = if namespaced {
+            opts.first().map_or(&[][..], |o| &o.options)
+        } else {
+            opts
+        };
+        let mut path = vec![];
+
+        while let [opt] = opts {
+            if !matches!(
+                opt.kind,
+                CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+            ) {
+                break;
+            }
+
+            path.push(opt.name.as_str());
+            opts = &opt.options;
+        }
+
+        path
+    }
+
     fn pretty_handler_error<'a, I>(
         err: handler::HandlerError<S, I>,
         desc: &'static str,
+        info: Option<(&command::CommandInfo, &[&str])>,
     ) -> Option<Message<'a, S::Component, id::Error>> {
         match err {
             handler::HandlerError::Parse(err) => match err {
@@ -522,11 +733,23 @@ impl<S: Schema> Registry<S> {
                 },
                 err => {
                     tracing::error!(%err, "Unexpected error parsing {desc}");
+                    let hint = info.and_then(|(i, _)| Self::suggest(i, &err));
+                    let example = info.and_then(|(i, p)| i.examples(p).next());
                     Message::rich(|b| {
                         b.push("Unexpected error parsing ")
                             .push(desc)
                             .push(": ")
-                            .push_mono_safe(err)
+                            .push_mono_safe(err);
+
+                        if let Some(hint) = hint {
+                            b.push("\nDid you mean ").push_mono_safe(hint).push("?");
+                        }
+
+                        if let Some(example) = example {
+                            b.push("\nExample: ").push_mono_safe(example);
+                        }
+
+                        b
                     })
                     .ephemeral(true)
                     .into()
@@ -542,6 +765,12 @@ impl<S: Schema> Registry<S> {
                     .ephemeral(true)
                     .into()
             },
+            handler::HandlerError::Forward(fwd) => {
+                tracing::error!(?fwd, "Handler for {desc} attempted to forward interaction");
+                Message::plain("Unexpected error: this interaction cannot be forwarded.")
+                    .ephemeral(true)
+                    .into()
+            },
         }
     }
 
@@ -556,27 +785,108 @@ impl<S: Schema> Registry<S> {
         }
     }
 
+    /// Collate all registered commands as they would be sent to Discord and
+    /// check them against Discord's documented limits, without contacting the
+    /// API
+    ///
+    /// Commands whose handlers report the parameters they read (see
+    /// [`CommandHandler::visited_args`](handler::CommandHandler::visited_args))
+    /// are also checked against their registered parameters.  Returns the
+    /// name and violations of each invalid command.
+    ///
+    /// # Errors
+    /// This method returns an error if the commands could not be collated,
+    /// e.g. due to a name collision.
+    pub fn validate_commands(
+        &self,
+    ) -> Result<Vec<(String, Vec<command::ValidationError>)>, anyhow::Error> {
+        let mut invalid: BTreeMap<_, _> = Self::collate_commands(&self.handlers)?
+            .into_iter()
+            .filter_map(|(name, (_, inf))| inf.validate().err().map(|e| (name, e)))
+            .collect();
+
+        for cmd in &self.handlers.commands {
+            let Some(visited) = cmd.visited_args() else {
+                continue;
+            };
+            let inf = cmd.register_global();
+
+            if let Err(errs) = inf.validate_args(&visited) {
+                let name = inf.namespace().unwrap_or_else(|| inf.name()).clone();
+                invalid.entry(name).or_insert_with(Vec::new).extend(errs);
+            }
+        }
+
+        Ok(invalid.into_iter().collect())
+    }
+
+    /// Collate all registered commands as they would be sent to Discord and
+    /// describe them as a JSON document, without contacting the API
+    ///
+    /// The document is intended for generating external command references;
+    /// see [`CommandInfo::catalog`](command::CommandInfo::catalog) for the
+    /// format of each command.
+    ///
+    /// # Errors
+    /// This method returns an error if the commands could not be collated,
+    /// e.g. due to a name collision.
+    pub fn command_catalog(&self) -> Result<serde_json::Value, anyhow::Error> {
+        let mut infos: Vec<_> = Self::collate_commands(&self.handlers)?
+            .into_values()
+            .map(|(_, inf)| inf)
+            .collect();
+        infos.sort_by(|a, b| a.name().cmp(b.name()));
+
+        Ok(serde_json::json!({
+            "commands": infos.iter().map(command::CommandInfo::catalog).collect::<Vec<_>>(),
+        }))
+    }
+
     /// Initialize dispatch logic and register all necessary metadata with
     /// Discord
     ///
+    /// Only the HTTP client is needed, so this can run while the gateway is
+    /// still connecting.  Its application ID must already be set.
+    ///
     /// # Errors
     /// This method returns an error if an API error response is received during
     /// registration.
     #[inline]
-    pub async fn init(&self, ctx: &Context) -> Result<(), anyhow::Error> {
+    pub async fn init(&self, http: &Http) -> Result<(), anyhow::Error> {
+        let start = Instant::now();
         let mut commands = self.commands.write().await;
         let mut components = self.components.write().await;
         let mut modals = self.modals.write().await;
 
-        *commands = Some(Self::patch_commands(ctx, &self.handlers, None).await?);
+        let map = Box::pin(Self::patch_commands(http, &self.handlers, None)).await?;
+        *commands = Some(map);
         *components = Some(Self::collate_rpc(&self.handlers.components));
         *modals = Some(Self::collate_rpc(&self.handlers.modals));
 
         // TODO: handle guild commands
 
+        tracing::info!(elapsed = ?start.elapsed(), "Registry initialized");
+
         Ok(())
     }
 
+    /// Run the [`warmup`](handler::CommandHandler::warmup) method of every
+    /// command handler concurrently, logging how long each one took
+    pub async fn warmup(&self, ctx: &Context) {
+        futures_util::future::join_all(self.handlers.commands.iter().map(|handler| async move {
+            let name = handler.register_global().name().clone();
+            let start = Instant::now();
+            let res = handler.warmup(ctx).await;
+            let elapsed = start.elapsed();
+
+            match res {
+                Ok(()) => tracing::info!(name, ?elapsed, "Command warmup completed"),
+                Err(err) => tracing::error!(name, ?elapsed, ?err, "Command warmup failed"),
+            }
+        }))
+        .await;
+    }
+
     #[tracing::instrument(level = "error", name = "handle_command", err, skip(self, ctx, aci))]
     async fn try_handle_command(
         &self,
@@ -589,8 +899,8 @@ impl<S: Schema> Registry<S> {
         tracing::info!("Handling application command");
 
         let map = self.commands.read().await;
-        let responder = InitResponder::new(&ctx.http, &aci);
-        let handler = match Self::resolve_command(&map, aci.data.id) {
+        let responder = InitResponder::new(&*ctx.http, &aci);
+        let (handler, namespaced) = match Self::resolve_command(&map, &aci.data) {
             Ok(h) => h,
             Err(e) => {
                 return responder
@@ -601,23 +911,67 @@ impl<S: Schema> Registry<S> {
         };
         tracing::debug!(?handler, "Command handler selected");
 
-        let mut vis = visitor::CommandVisitor::new(&aci);
+        let mut vis = if namespaced {
+            visitor::CommandVisitor::new_namespaced(&aci)
+        } else {
+            visitor::CommandVisitor::new(&aci)
+        };
         let mut responder = BorrowedResponder::Init(responder);
         let res = handler
             .respond(ctx, &mut vis, BorrowingResponder::new(&mut responder))
             .await;
         let res = res.and_then(|_| vis.finish().map_err(Into::into));
 
-        if let Some(msg) = res
-            .err()
-            .and_then(|e| Self::pretty_handler_error(e, "command"))
-        {
+        if let Some(msg) = res.err().and_then(|e| {
+            let path = Self::subcmd_path(&aci.data.options, namespaced);
+            Self::pretty_handler_error(e, "command", Some((&handler.register_global(), &path)))
+        }) {
             responder.create_or_followup(msg).await?;
         }
 
         Ok(())
     }
 
+    /// Run a component handler, dispatching any payload it forwards to the
+    /// handler registered for that payload
+    async fn respond_component<'a>(
+        ctx: &Context,
+        map: Option<&RpcHandlerMap<S, S::ComponentKey>>,
+        mut handler: &RpcHandler<S, S::ComponentKey>,
+        mut payload: S::ComponentPayload,
+        vis: &mut visitor::BasicVisitor<'_, MessageComponentInteraction>,
+        responder: &mut BorrowedResponder<'a, S, MessageComponentInteraction>,
+    ) -> handler::ComponentResult<'a, S>
+    where S::ComponentPayload: 'static {
+        let mut hops = 0_usize;
+
+        loop {
+            let res = handler
+                .respond(ctx, payload, vis, BorrowingResponder::new(responder))
+                .await;
+
+            let Err(handler::HandlerError::Forward(fwd)) = res else {
+                break res;
+            };
+
+            hops += 1;
+            if hops > MAX_FORWARDS {
+                break Err(anyhow::anyhow!("Component forwarded too many times").into());
+            }
+
+            let Some(next) = fwd.downcast::<S::ComponentPayload>() else {
+                break Err(anyhow::anyhow!("Forwarded payload has the wrong type").into());
+            };
+
+            let Some(next_handler) = map.and_then(|m| m.get(&(&next).into())) else {
+                break Err(anyhow::anyhow!("No handler for forwarded payload {next:?}").into());
+            };
+
+            tracing::debug!(handler = ?next_handler, payload = ?next, "Component forwarded");
+            (handler, payload) = (next_handler, next);
+        }
+    }
+
     #[tracing::instrument(level = "error", name = "handle_component", err, skip(self, ctx, mc))]
     async fn try_handle_component(
         &self,
@@ -626,14 +980,18 @@ impl<S: Schema> Registry<S> {
         name: String,
         id: String,
         issuer: String,
-    ) -> Result<(), ResponseError> {
+    ) -> Result<(), ResponseError>
+    where S::ComponentPayload: 'static {
         tracing::info!("Handling message component");
 
         let map = self.components.read().await;
-        let responder = InitResponder::new(&ctx.http, &mc);
-        let (handler, payload) = match Self::resolve_component(&map, unsafe {
-            &id::Id::from_inner(mc.data.custom_id.as_str().into())
-        }) {
+        let responder = InitResponder::new(&*ctx.http, &mc);
+        let (handler, payload) = match Self::resolve_component(
+            &map,
+            &self.handlers.decode_log,
+            &self.handlers.legacy,
+            unsafe { &id::Id::from_inner(mc.data.custom_id.as_str().into()) },
+        ) {
             Ok(h) => h,
             Err(e) => {
                 return responder
@@ -643,21 +1001,34 @@ impl<S: Schema> Registry<S> {
             },
         };
         tracing::debug!(?handler, ?payload, "Component handler selected");
+        let label = S::ComponentKey::label(&payload);
+        tracing::info!(%label, index = ?mc_index(&mc), "Component used");
 
-        let mut vis = visitor::BasicVisitor { int: &mc };
+        let mut vis = visitor::BasicVisitor::new(&mc);
         let mut responder = BorrowedResponder::Init(responder);
-        let res = handler
-            .respond(
-                ctx,
-                payload,
-                &mut vis,
-                BorrowingResponder::new(&mut responder),
-            )
-            .await;
+        let res = Self::respond_component(
+            ctx,
+            map.as_ref(),
+            handler,
+            payload,
+            &mut vis,
+            &mut responder,
+        )
+        .await;
+
+        if let Err(handler::HandlerError::Parse(ref err)) = res {
+            self.handlers.decode_log.record(
+                Kind::Component,
+                Reason::Rejected,
+                unsafe { &id::Id::from_inner(mc.data.custom_id.as_str().into()) },
+                Some(&label),
+                Some(err),
+            );
+        }
 
         if let Some(msg) = res
             .err()
-            .and_then(|e| Self::pretty_handler_error(e, "component"))
+            .and_then(|e| Self::pretty_handler_error(e, "component", None))
         {
             responder.create_or_followup(msg).await?;
         }
@@ -682,10 +1053,15 @@ impl<S: Schema> Registry<S> {
         tracing::trace!("Handling command autocomplete");
 
         let map = self.commands.read().await;
-        let handler = Self::resolve_command(&map, ac.data.id).ok();
+        let handler = Self::resolve_command(&map, &ac.data).ok();
+
+        let choices = if let Some((handler, namespaced)) = handler {
+            let mut vis = if namespaced {
+                visitor::CommandVisitor::new_namespaced(&ac)
+            } else {
+                visitor::CommandVisitor::new(&ac)
+            };
 
-        let mut vis = visitor::CommandVisitor::new(&ac);
-        let choices = if let Some(handler) = handler {
             handler
                 .complete(ctx, &mut vis)
                 .await
@@ -723,8 +1099,8 @@ impl<S: Schema> Registry<S> {
         tracing::info!("Handling modal submit");
 
         let map = self.modals.read().await;
-        let responder = InitResponder::new(&ctx.http, &ms);
-        let (handler, src, payload) = match Self::resolve_modal(&map, unsafe {
+        let responder = InitResponder::new(&*ctx.http, &ms);
+        let (handler, src, payload) = match Self::resolve_modal(&map, &self.handlers.decode_log, unsafe {
             &id::Id::from_inner(ms.data.custom_id.as_str().into())
         }) {
             Ok(p) => p,
@@ -735,10 +1111,11 @@ impl<S: Schema> Registry<S> {
                     .map(|_| ());
             },
         };
+        // TODO: use src
         tracing::debug!(?handler, ?src, ?payload, "Modal handler selected");
-        let src = src; // TODO: use this
+        let label = S::ModalKey::label(&payload);
 
-        let mut vis = visitor::BasicVisitor { int: &ms };
+        let mut vis = visitor::BasicVisitor::new(&ms);
         let mut responder = BorrowedResponder::Init(responder);
         let res = handler
             .respond(
@@ -749,9 +1126,19 @@ impl<S: Schema> Registry<S> {
             )
             .await;
 
+        if let Err(handler::HandlerError::Parse(ref err)) = res {
+            self.handlers.decode_log.record(
+                Kind::Modal,
+                Reason::Rejected,
+                unsafe { &id::Id::from_inner(ms.data.custom_id.as_str().into()) },
+                Some(&label),
+                Some(err),
+            );
+        }
+
         if let Some(msg) = res
             .err()
-            .and_then(|e| Self::pretty_handler_error(e, "modal"))
+            .and_then(|e| Self::pretty_handler_error(e, "modal", None))
         {
             responder.create_or_followup(msg).await?;
         }
@@ -771,7 +1158,8 @@ impl<S: Schema> Registry<S> {
     /// Dispatch a component interaction to the proper handler and submit a
     /// response
     #[inline]
-    pub async fn handle_component(&self, ctx: &Context, mc: MessageComponentInteraction) {
+    pub async fn handle_component(&self, ctx: &Context, mc: MessageComponentInteraction)
+    where S::ComponentPayload: 'static {
         let cache = &ctx.cache;
         let (name, id, iss) = (mc_name::<S>(&mc), mc_id(&mc), mc_issuer(cache, &mc));
         self.try_handle_component(ctx, mc, name, id, iss).await.ok();
@@ -797,3 +1185,155 @@ impl<S: Schema> Registry<S> {
         self.try_handle_modal(ctx, ms, name, id, iss).await.ok();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use serenity::client::Context;
+    use tokio::sync::RwLock;
+
+    use super::{Registry, RpcHandlerMap, MAX_FORWARDS};
+    use crate::interaction::{
+        decode_log::{DecodeLog, Kind, Reason},
+        handler::{
+            ComponentResponder, ComponentResult, ComponentVisitor, HandlerError, RpcHandler,
+        },
+        legacy::LegacyIds,
+        response::{
+            id,
+            sink::{Call, MemorySink},
+            BorrowedResponder, InitResponder, Message,
+        },
+        testing::{self, Component, ComponentKey, ComponentPayload, Schema},
+        visitor::BasicVisitor,
+    };
+
+    /// Counts `A` payloads down to zero by forwarding, then forwards to `B`
+    #[derive(Debug)]
+    struct Countdown;
+
+    #[async_trait::async_trait]
+    impl RpcHandler<Schema, ComponentKey> for Countdown {
+        fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::A] }
+
+        async fn respond<'a>(
+            &self,
+            _: &Context,
+            payload: ComponentPayload,
+            _: &mut ComponentVisitor<'_>,
+            _: ComponentResponder<'_, 'a, Schema>,
+        ) -> ComponentResult<'a, Schema> {
+            match payload {
+                ComponentPayload::A(0) => Err(HandlerError::forward(ComponentPayload::B(0))),
+                ComponentPayload::A(n) => Err(HandlerError::forward(ComponentPayload::A(n - 1))),
+                ComponentPayload::B(_) => unreachable!(),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Done;
+
+    #[async_trait::async_trait]
+    impl RpcHandler<Schema, ComponentKey> for Done {
+        fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::B] }
+
+        async fn respond<'a>(
+            &self,
+            _: &Context,
+            _: ComponentPayload,
+            _: &mut ComponentVisitor<'_>,
+            responder: ComponentResponder<'_, 'a, Schema>,
+        ) -> ComponentResult<'a, Schema> {
+            Ok(responder
+                .create_message(Message::plain("done"))
+                .await
+                .map_err(anyhow::Error::from)?
+                .into())
+        }
+    }
+
+    fn handlers() -> RpcHandlerMap<Schema, ComponentKey> {
+        HashMap::from([
+            (ComponentKey::A, Arc::new(Countdown) as Arc<dyn RpcHandler<_, _>>),
+            (ComponentKey::B, Arc::new(Done) as Arc<dyn RpcHandler<_, _>>),
+        ])
+    }
+
+    async fn countdown(from: u32) -> (Result<(), String>, Vec<Call>) {
+        let map = handlers();
+        let handler = &map[&ComponentKey::A];
+
+        let ctx = testing::context();
+        let int = testing::component("");
+        let sink = MemorySink::default();
+        let mut vis = BasicVisitor::new(&int);
+        let mut responder = BorrowedResponder::Init(InitResponder::new(&sink, &int));
+
+        let res = Registry::respond_component(
+            &ctx,
+            Some(&map),
+            handler,
+            ComponentPayload::A(from),
+            &mut vis,
+            &mut responder,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+
+        (res, sink.calls())
+    }
+
+    #[tokio::test]
+    async fn test_forward() {
+        // A countdown from n forwards n + 1 times
+        let from = u32::try_from(MAX_FORWARDS).unwrap() - 1;
+        let (res, calls) = countdown(from).await;
+        assert_eq!(res, Ok(()));
+        assert!(matches!(&calls[..], [Call::CreateResponse { .. }]));
+
+        let (res, calls) = countdown(from + 1).await;
+        assert_eq!(
+            res,
+            Err("Unexpected error: Component forwarded too many times".into())
+        );
+        assert!(calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_component() {
+        let map = RwLock::new(Some(handlers()));
+        let map = map.read().await;
+        let log = DecodeLog::default();
+        let legacy = LegacyIds::<Schema>::default()
+            .component("b:", |s| s.parse().ok().map(ComponentPayload::B));
+        let resolve = |s: &str| {
+            // SAFETY: the ID is only read back
+            let id = unsafe { id::Id::from_inner(s.to_owned().into()) };
+            Registry::resolve_component(&map, &log, &legacy, &id).map(|(_, p)| p)
+        };
+
+        assert!(matches!(resolve("b:7"), Ok(ComponentPayload::B(7))));
+        assert!(resolve("b:x").is_err());
+        assert!(resolve("c:7").is_err());
+
+        // Current IDs are not passed to the shims
+        let current = Component {
+            payload: Some(ComponentPayload::B(8)),
+        };
+        let current = id::write(&current, id::Field::CustomId).unwrap();
+        assert!(matches!(resolve(current.as_str()), Ok(ComponentPayload::B(8))));
+
+        assert_eq!(legacy.hits().into_iter().collect::<Vec<_>>(), [(
+            "b:".to_owned(),
+            1
+        )]);
+        // Both rejected IDs are logged
+        let samples = log.samples();
+        assert_eq!(samples.len(), 2);
+        assert!(samples.iter().all(|s| s.bucket.kind == Kind::Component
+            && s.bucket.reason == Reason::Malformed));
+    }
+}
@@ -8,8 +8,8 @@ use qcore::{build_range::BuildRange, builder};
 use serenity::{
     builder::{
         CreateActionRow, CreateButton, CreateInputText, CreateInteractionResponseData,
-        CreateInteractionResponseFollowup, CreateSelectMenu, CreateSelectMenuOption,
-        EditInteractionResponse,
+        CreateInteractionResponseFollowup, CreateMessage, CreateSelectMenu,
+        CreateSelectMenuOption, EditInteractionResponse, EditMessage,
     },
     model::{
         application::component::{ButtonStyle as ButtonStyleModel, InputTextStyle},
@@ -85,6 +85,22 @@ impl<I, T: private::BuildComponent> Components<I, T, Infallible> {
     ) -> &'a mut CreateInteractionResponseFollowup<'b> {
         build_components!(self, fup).components(|c| c)
     }
+
+    #[inline]
+    pub(super) fn build_create_message<'a, 'b>(
+        self,
+        msg: &'b mut CreateMessage<'a>,
+    ) -> &'b mut CreateMessage<'a> {
+        build_components!(self, msg)
+    }
+
+    #[inline]
+    pub(super) fn build_edit_message<'a, 'b>(
+        self,
+        msg: &'b mut EditMessage<'a>,
+    ) -> &'b mut EditMessage<'a> {
+        build_components!(self, msg)
+    }
 }
 
 #[builder(trait_name = ComponentsExt)]
@@ -135,12 +151,17 @@ impl<I, T, E> Default for ActionRow<I, T, E> {
         Self {
             err: RowError(None),
             components: vec![],
-            id: PhantomData::default(),
+            id: PhantomData,
         }
     }
 }
 
 impl<I, T, E> ActionRow<I, T, E> {
+    /// Returns true if this row contains no components
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.components.is_empty() }
+
     #[inline]
     fn prepare(self) -> Result<ActionRow<I, T, Infallible>, E> {
         let Self {
@@ -173,7 +194,7 @@ impl<I: ComponentId> ActionRow<I, MessageComponent, id::Error> {
             let (min_count, max_count) = count.build_range().into_inner();
             self.components.push(MessageComponent {
                 ty: MessageComponentType::Menu {
-                    id: id::write(&I::from_parts(payload))?,
+                    id: id::write(&I::from_parts(payload), id::Field::CustomId)?,
                     ty: ty?,
                     placeholder: placeholder.into(),
                     min_count: min_count.unwrap_or(0),
@@ -187,6 +208,17 @@ impl<I: ComponentId> ActionRow<I, MessageComponent, id::Error> {
     }
 }
 
+impl<I, E> ActionRow<I, MessageComponent, E> {
+    /// Disable every component in this row
+    #[must_use]
+    pub fn disable_all(mut self) -> Self {
+        for c in &mut self.components {
+            c.disabled = true;
+        }
+        self
+    }
+}
+
 #[builder(trait_name = MessageActionRow)]
 /// Helper methods for mutating an [`ActionRow`] for messages
 impl<I: ComponentId> ActionRow<I, MessageComponent, id::Error> {
@@ -203,7 +235,7 @@ impl<I: ComponentId> ActionRow<I, MessageComponent, id::Error> {
                 ty: MessageComponentType::Button {
                     label: label.into(),
                     ty: ButtonType::Custom {
-                        id: id::write(&I::from_parts(payload))?,
+                        id: id::write(&I::from_parts(payload), id::Field::CustomId)?,
                         style,
                     },
                 },
@@ -243,7 +275,7 @@ impl<I: ComponentId> ActionRow<I, MessageComponent, id::Error> {
         let res = options.into_iter().try_fold(
             (HashMap::new(), vec![]),
             |(mut items, mut order), (payload, item)| {
-                let id = id::write(&I::from_parts(payload))?;
+                let id = id::write(&I::from_parts(payload), id::Field::SelectValue)?;
 
                 order.push(id.clone());
                 assert!(items.insert(id, item.into()).is_none());
@@ -580,7 +612,7 @@ impl<I: ComponentId> TextInput<I> {
         label: impl Into<String>,
     ) -> Result<Self, id::Error> {
         Ok(Self {
-            id: id::write(&I::from_parts(payload))?,
+            id: id::write(&I::from_parts(payload), id::Field::CustomId)?,
             style,
             label: label.into(),
             min_len: None,
@@ -588,7 +620,7 @@ impl<I: ComponentId> TextInput<I> {
             required: true,
             value: String::new(),
             placeholder: None,
-            rpc_id: PhantomData::default(),
+            rpc_id: PhantomData,
         })
     }
 
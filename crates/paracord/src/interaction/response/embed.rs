@@ -2,7 +2,7 @@ use qcore::builder;
 use serenity::{
     builder::{
         CreateEmbed, CreateInteractionResponseData, CreateInteractionResponseFollowup,
-        EditInteractionResponse,
+        CreateMessage, EditInteractionResponse, EditMessage,
     },
     utils::{Color, MessageBuilder},
 };
@@ -83,6 +83,22 @@ impl Embeds {
     ) -> &'b mut CreateInteractionResponseFollowup<'a> {
         build_embeds!(self, fup)
     }
+
+    #[inline]
+    pub(super) fn build_create_message<'a, 'b>(
+        self,
+        msg: &'b mut CreateMessage<'a>,
+    ) -> &'b mut CreateMessage<'a> {
+        build_embeds!(self, msg)
+    }
+
+    #[inline]
+    pub(super) fn build_edit_message<'a, 'b>(
+        self,
+        msg: &'b mut EditMessage<'a>,
+    ) -> &'b mut EditMessage<'a> {
+        build_embeds!(self, msg)
+    }
 }
 
 impl<'a> ResponseData<'a> for Embeds {
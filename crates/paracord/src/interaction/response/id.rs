@@ -1,4 +1,16 @@
 //! Support code for encoding and decoding compact custom IDs
+//!
+//! An ID is a format byte followed by a protobuf payload, optionally
+//! compressed with zstd, all encoded with [`base64k`].  The format byte is the
+//! version tag for the codec itself: a new encoding gets a new format byte,
+//! and [`read()`] rejects any it doesn't recognize.  Changes to the payloads
+//! are versioned by their protobuf field numbers, with shims registered in
+//! [`legacy`](crate::interaction::legacy) for anything protobuf can't express.
+//!
+//! **NOTE:** IDs carry no MAC.  Discord only delivers custom IDs which the bot
+//! itself attached to a message or modal, so a MAC would only guard against a
+//! leaked bot token while taking bytes out of every field's length budget.  If
+//! an ID ever needs to be verified, add it as a new format byte.
 
 use std::{borrow::Cow, convert::Infallible, fmt, io::prelude::*};
 
@@ -11,6 +23,29 @@ pub enum Error {
     /// A protobuf error originating from [`prost`]
     #[error("Error decoding message payload")]
     Protobuf(#[from] prost::DecodeError),
+    /// An encoded ID exceeded the length limit of the field it was written for
+    #[error("Encoded ID is {1} characters long, but {0:?} allows at most {max}", max = .0.max_len())]
+    TooLong(Field, usize),
+}
+
+/// A Discord field that can hold an encoded [`Id`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    /// The custom ID of a message component or modal
+    CustomId,
+    /// The value of a string select menu option
+    SelectValue,
+}
+
+impl Field {
+    /// Get the maximum length, in characters, of this field
+    #[inline]
+    #[must_use]
+    pub const fn max_len(self) -> usize {
+        match self {
+            Self::CustomId | Self::SelectValue => 100,
+        }
+    }
 }
 
 impl From<Infallible> for Error {
@@ -38,6 +73,10 @@ impl<'a> Id<'a> {
     /// Produce an ID that borrows from `self`
     #[must_use]
     pub fn as_ref(&self) -> Id<'_> { Id(Cow::Borrowed(self.0.as_ref())) }
+
+    /// Get the raw string representation of this ID
+    #[must_use]
+    pub(crate) fn as_str(&self) -> &str { self.0.as_ref() }
 }
 
 const FORMAT_RAW: u8 = 0;
@@ -45,6 +84,14 @@ const FORMAT_ZSTD: u8 = 1;
 
 const ZSTD_WINDOW_LOG: u32 = 10; // The minimum, but larger than our target payload size
 
+/// Decode the raw bytes of the given [`Id`], including its format byte,
+/// without decompressing or parsing them
+pub(crate) fn read_raw(i: &Id<'_>) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![];
+    base64k::Decoder::new(i.0.chars()).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
 /// Decode the given [`Id`] into a custom ID message
 ///
 /// # Errors
@@ -84,12 +131,12 @@ pub fn read<M: prost::Message + Default>(i: &Id<'_>) -> Result<M, Error> {
     M::decode(&*msg_buf).map_err(Error::Protobuf)
 }
 
-/// Encode the given message into an [`Id`]
+/// Encode the given message into an [`Id`] to be stored in the given field
 ///
 /// # Errors
 /// This function fails if an unrecoverable format error occurs while writing
-/// the output string.
-pub fn write(id: &impl prost::Message) -> Result<Id<'static>, Error> {
+/// the output string, or if the output is too long for `field`.
+pub fn write(id: &impl prost::Message, field: Field) -> Result<Id<'static>, Error> {
     let raw = id.encode_to_vec();
 
     let mut z_enc = zstd::stream::Encoder::new(vec![], 22)?; // TODO
@@ -117,13 +164,21 @@ pub fn write(id: &impl prost::Message) -> Result<Id<'static>, Error> {
         _ => unreachable!(),
     }
 
-    Ok(Id(Cow::Owned(enc.finish())))
+    let s: String = enc.finish();
+    let len = s.chars().count();
+    if len > field.max_len() {
+        return Err(Error::TooLong(field, len));
+    }
+
+    Ok(Id(Cow::Owned(s)))
 }
 
 #[cfg(test)]
 mod test {
     use anyhow::Context as _;
 
+    use super::Field;
+
     #[derive(prost::Message)]
     struct Msg {
         #[prost(string, tag = "1")]
@@ -133,17 +188,31 @@ mod test {
     #[test]
     fn test_roundtrip() -> Result<(), anyhow::Error> {
         let s = "1234";
-        let id = super::write(&Msg { s: s.to_owned() }).context("Error writing short message")?;
+        let id = super::write(&Msg { s: s.to_owned() }, Field::CustomId)
+            .context("Error writing short message")?;
 
         let Msg { s: s2 } = super::read(&id).context("Error reading short message")?;
         assert_eq!(s, s2);
 
         let mut s = String::new();
         (0..1000).for_each(|_| s.push_str("0123456789"));
-        let id = super::write(&Msg { s: s.clone() }).context("Error writing long message")?;
+        let id = super::write(&Msg { s: s.clone() }, Field::CustomId)
+            .context("Error writing long message")?;
 
         let Msg { s: s2 } = super::read(&id).context("Error reading long message")?;
         assert_eq!(s, s2);
+
+        let mut x = 1_u32;
+        let s: String = (0..1000)
+            .map(|_| {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                char::from(b'a' + u8::try_from((x >> 16) % 26).unwrap())
+            })
+            .collect();
+        assert!(matches!(
+            super::write(&Msg { s }, Field::CustomId),
+            Err(super::Error::TooLong(Field::CustomId, _))
+        ));
         Ok(())
     }
 }
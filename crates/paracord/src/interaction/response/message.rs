@@ -6,7 +6,8 @@ use std::{
 use qcore::builder;
 use serenity::{
     builder::{
-        CreateInteractionResponseData, CreateInteractionResponseFollowup, EditInteractionResponse,
+        CreateInteractionResponseData, CreateInteractionResponseFollowup, CreateMessage,
+        EditInteractionResponse, EditMessage, ParseValue,
     },
     model::{
         id::{RoleId, UserId},
@@ -17,14 +18,26 @@ use serenity::{
 
 use super::{Components, Embed, Embeds, MessageComponent, ResponseData};
 
+/// A kind of mention which can be allowed to ping wherever it appears in a
+/// message, rather than only for an explicit list of targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// Mentions of any user
+    Users,
+    /// Mentions of any guild role
+    Roles,
+}
+
 /// The body of a message
 #[derive(Debug, qcore::Borrow)]
 pub struct MessageBody<I, E> {
     content: MessageBuilder,
     embeds: Embeds,
     ping_replied: bool,
+    ping_everyone: bool,
     ping_users: Vec<UserId>,
     ping_roles: Vec<RoleId>,
+    parse: Vec<ParseMode>,
     #[borrow(mut)]
     components: Components<I, MessageComponent, E>,
 }
@@ -35,14 +48,33 @@ macro_rules! build_body {
             content,
             embeds,
             ping_replied,
+            ping_everyone,
             ping_users,
             ping_roles,
+            parse,
             components,
         } = $self;
         components.$fn(embeds.$fn($builder.content(content)).allowed_mentions(|m| {
-            m.replied_user(ping_replied)
-                .users(ping_users)
-                .roles(ping_roles)
+            m.replied_user(ping_replied).empty_parse();
+            if ping_everyone {
+                m.parse(ParseValue::Everyone);
+            }
+
+            // Discord rejects a parse mode alongside an explicit list of the
+            // same kind
+            if parse.contains(&ParseMode::Users) {
+                m.parse(ParseValue::Users);
+            } else {
+                m.users(ping_users);
+            }
+
+            if parse.contains(&ParseMode::Roles) {
+                m.parse(ParseValue::Roles);
+            } else {
+                m.roles(ping_roles);
+            }
+
+            m
         }))
     }};
 }
@@ -57,8 +89,10 @@ impl<I, E> MessageBody<I, E> {
             content,
             embeds: Embeds::default(),
             ping_replied: false,
+            ping_everyone: false,
             ping_users: vec![],
             ping_roles: vec![],
+            parse: vec![],
             components: Components::default(),
         }
     }
@@ -79,16 +113,20 @@ impl<I, E> MessageBody<I, E> {
             content,
             embeds,
             ping_replied,
+            ping_everyone,
             ping_users,
             ping_roles,
+            parse,
             components,
         } = self;
         Ok(MessageBody {
             content,
             embeds,
             ping_replied,
+            ping_everyone,
             ping_users,
             ping_roles,
+            parse,
             components: components.prepare()?,
         })
     }
@@ -112,6 +150,25 @@ impl<I> MessageBody<I, Infallible> {
     ) -> &'b mut CreateInteractionResponseFollowup<'a> {
         build_body!(self, fup, build_followup)
     }
+
+    /// Apply the values of this message body to a channel message builder
+    #[inline]
+    pub fn build_create_message<'a, 'b>(
+        self,
+        msg: &'b mut CreateMessage<'a>,
+    ) -> &'b mut CreateMessage<'a> {
+        build_body!(self, msg, build_create_message)
+    }
+
+    /// Apply the values of this message body to a channel message edit
+    /// builder
+    #[inline]
+    pub fn build_edit_message<'a, 'b>(
+        self,
+        msg: &'b mut EditMessage<'a>,
+    ) -> &'b mut EditMessage<'a> {
+        build_body!(self, msg, build_edit_message)
+    }
 }
 
 #[builder(trait_name = MessageBodyExt)]
@@ -120,12 +177,28 @@ impl<I, E> MessageBody<I, E> {
     /// Set whether the replied-to user is allowed to be pinged
     pub fn ping_replied(&mut self, ping_replied: bool) { self.ping_replied = ping_replied; }
 
+    /// Set whether `@everyone` and `@here` are allowed to ping
+    ///
+    /// **NOTE:** This is disabled by default, and is not enabled by any
+    /// [`ParseMode`].
+    pub fn ping_everyone(&mut self, ping_everyone: bool) { self.ping_everyone = ping_everyone; }
+
     /// Set which users are allowed to be pinged
+    ///
+    /// **NOTE:** This has no effect if [`ParseMode::Users`] is enabled.
     pub fn ping_users(&mut self, ping_users: Vec<UserId>) { self.ping_users = ping_users; }
 
     /// Set which guild roles are allowed to be pinged
+    ///
+    /// **NOTE:** This has no effect if [`ParseMode::Roles`] is enabled.
     pub fn ping_roles(&mut self, ping_roles: Vec<RoleId>) { self.ping_roles = ping_roles; }
 
+    /// Set which kinds of mention are allowed to ping wherever they appear in
+    /// the message content, overriding the corresponding explicit lists
+    pub fn parse_modes(&mut self, modes: impl IntoIterator<Item = ParseMode>) {
+        self.parse = modes.into_iter().collect();
+    }
+
     /// Add an embed to this message
     pub fn embed(&mut self, embed: Embed) { self.embeds.0.push(embed); }
 
@@ -303,3 +376,36 @@ impl<'a, I> ResponseData<'a> for Message<'a, I, Infallible> {
         build_msg!(self, data, build_response_data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serenity::{builder::CreateInteractionResponseFollowup, json::json, model::id::UserId};
+
+    use super::{MessageBody, MessageBodyExt, ParseMode};
+
+    type Body = MessageBody<(), std::convert::Infallible>;
+
+    fn allowed_mentions(body: Body) -> serenity::json::Value {
+        let mut fup = CreateInteractionResponseFollowup::default();
+        body.build_followup(&mut fup);
+        fup.0.remove("allowed_mentions").unwrap()
+    }
+
+    #[test]
+    fn test_allowed_mentions() {
+        assert_eq!(
+            allowed_mentions(Body::plain("@everyone").ping_users(vec![UserId(1)])),
+            json!({ "replied_user": false, "parse": [], "users": ["1"], "roles": [] })
+        );
+
+        assert_eq!(
+            allowed_mentions(
+                Body::plain("@everyone")
+                    .ping_everyone(true)
+                    .ping_users(vec![UserId(1)])
+                    .parse_modes([ParseMode::Users])
+            ),
+            json!({ "replied_user": false, "parse": ["everyone", "users"], "roles": [] })
+        );
+    }
+}
@@ -7,7 +7,9 @@ mod embed;
 pub mod id;
 mod message;
 mod modal;
+pub mod page;
 mod responder;
+pub mod sink;
 
 pub use component::*;
 pub use data::*;
@@ -38,10 +38,10 @@ impl<S: Schema> Modal<S, id::Error> {
         title: impl Into<String>,
     ) -> Self {
         Self {
-            id: id::write(&S::Modal::from_parts(source.0, payload)),
+            id: id::write(&S::Modal::from_parts(source.0, payload), id::Field::CustomId),
             title: title.into(),
             components: Components::default(),
-            key: PhantomData::default(),
+            key: PhantomData,
         }
     }
 }
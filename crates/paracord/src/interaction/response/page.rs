@@ -0,0 +1,465 @@
+//! Paginated messages navigated with buttons
+//!
+//! A [`View`] renders one page at a time and describes how to encode
+//! navigation into its schema's component payloads.  The state needed to
+//! re-render the view (the page being requested and when navigation expires)
+//! travels in the custom IDs of the navigation buttons, so no server-side
+//! session is required.  Once navigation expires, requests are rejected and
+//! the message's components can be disabled with [`expire`].
+
+use std::{sync::Arc, time::Duration};
+
+use serenity::{
+    builder::EditInteractionResponse,
+    json::Value,
+    model::application::interaction::message_component::MessageComponentInteraction,
+};
+use tokio::task::JoinHandle;
+
+use super::{
+    super::rpc::{ComponentId, Schema},
+    id,
+    prelude::*,
+    sink::{InteractionSource, ResponseSink, SinkError, Source},
+    ActionRow, BorrowingResponder, ButtonStyle, CreatedResponder, Message, MessageBody,
+    MessageComponent, ResponseError,
+};
+
+/// How long Discord accepts edits to an interaction response for
+const TOKEN_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// A navigation button on a paginated message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NavButton {
+    /// Jump to the first page
+    First,
+    /// Go back one page
+    Prev,
+    /// Go forward one page
+    Next,
+    /// Jump to the last page
+    Last,
+}
+
+impl NavButton {
+    const ALL: [Self; 4] = [Self::First, Self::Prev, Self::Next, Self::Last];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::First => "«",
+            Self::Prev => "‹",
+            Self::Next => "›",
+            Self::Last => "»",
+        }
+    }
+
+    /// Get the page this button navigates to from the given page, or `None` if
+    /// it would not change the page
+    #[must_use]
+    pub fn target(self, page: u32, pages: u32) -> Option<u32> {
+        let last = pages.saturating_sub(1);
+        let page = page.min(last);
+        let target = match self {
+            Self::First => 0,
+            Self::Prev => page.saturating_sub(1),
+            Self::Next => page.saturating_add(1).min(last),
+            Self::Last => last,
+        };
+
+        (target != page).then_some(target)
+    }
+}
+
+/// The state of a paginated message, encoded into its navigation buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageState {
+    /// The zero-based index of the page being displayed
+    pub page: u32,
+    /// The Unix timestamp after which navigation is disabled, if any
+    pub expires: Option<i64>,
+}
+
+impl PageState {
+    /// Construct the state for the first page of a new view, optionally
+    /// disabling navigation after the given timeout
+    #[must_use]
+    pub fn new(timeout: Option<Duration>) -> Self {
+        let expires = timeout.map(|t| {
+            chrono::Utc::now()
+                .timestamp()
+                .saturating_add(t.as_secs().try_into().unwrap_or(i64::MAX))
+        });
+
+        Self { page: 0, expires }
+    }
+
+    /// Returns true if navigation for this view has expired
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .map_or(false, |e| chrono::Utc::now().timestamp() > e)
+    }
+
+    /// Get the time left until navigation for this view expires, or `None` if
+    /// it never does
+    #[must_use]
+    pub fn remaining(&self) -> Option<Duration> {
+        self.expires.map(|e| {
+            let secs = e.saturating_sub(chrono::Utc::now().timestamp());
+            // is_expired only returns true once the expiry second has passed
+            Duration::from_secs(u64::try_from(secs).unwrap_or(0).saturating_add(1))
+        })
+    }
+}
+
+/// A navigation request received from a paginated message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nav {
+    /// The state to display
+    pub state: PageState,
+    /// The button that was pressed
+    ///
+    /// This is included in the payload because Discord requires the custom IDs
+    /// within a message to be unique, and two buttons may share a target page.
+    pub button: NavButton,
+}
+
+/// A message split across several pages
+pub trait View<I: ComponentId> {
+    /// The number of pages in this view
+    fn pages(&self) -> u32;
+
+    /// Render the body of the given page
+    fn render(&self, page: u32) -> MessageBody<I, id::Error>;
+
+    /// Encode a navigation request into a component payload, to be decoded and
+    /// passed to [`navigate`] by the handler for this view's component key
+    fn payload(&self, nav: Nav) -> I::Payload;
+
+    /// Add any extra action buttons to display below the navigation buttons
+    ///
+    /// No row is added if this method leaves `row` empty.
+    #[inline]
+    fn actions(
+        &self,
+        page: u32,
+        row: ActionRow<I, MessageComponent, id::Error>,
+    ) -> ActionRow<I, MessageComponent, id::Error> {
+        #[allow(let_underscore_drop)]
+        let _ = (page,);
+        row
+    }
+}
+
+/// Render a page of a view, attaching navigation buttons and any action
+/// buttons
+///
+/// If navigation has expired, all components are disabled.
+pub fn render<I: ComponentId, V: View<I> + ?Sized>(
+    view: &V,
+    state: PageState,
+) -> MessageBody<I, id::Error> {
+    let pages = view.pages();
+    let page = state.page.min(pages.saturating_sub(1));
+    let expired = state.is_expired();
+    let mut body = view.render(page);
+
+    if pages > 1 {
+        body = body.build_row(|row| {
+            NavButton::ALL.into_iter().fold(row, |row, button| {
+                let target = button.target(page, pages);
+                let state = PageState {
+                    page: target.unwrap_or(page),
+                    ..state
+                };
+                row.button(
+                    view.payload(Nav { state, button }),
+                    ButtonStyle::Secondary,
+                    button.label(),
+                    expired || target.is_none(),
+                )
+            })
+        });
+    }
+
+    let actions = view.actions(page, ActionRow::default());
+    if !actions.is_empty() {
+        body = body.row(if expired {
+            actions.disable_all()
+        } else {
+            actions
+        });
+    }
+
+    body
+}
+
+/// Respond to a navigation request by re-rendering the originating message at
+/// the requested page
+///
+/// If navigation has expired, the message is left on its current page and the
+/// user is told so instead.
+///
+/// # Errors
+/// This method returns an error if the message contains errors or an API
+/// error is received.
+pub async fn navigate<'a, 'b, S: Schema, V: View<S::Component> + Sync + ?Sized>(
+    view: &V,
+    nav: Nav,
+    responder: BorrowingResponder<'a, 'b, S, MessageComponentInteraction>,
+) -> Result<CreatedResponder<'b, S, MessageComponentInteraction>, ResponseError> {
+    if nav.state.is_expired() {
+        return responder
+            .create_message(Message::plain("This message has expired.").ephemeral(true))
+            .await;
+    }
+
+    let body = render(view, nav.state);
+    responder.update_message(Message::from(body)).await
+}
+
+/// Disable every component in a set of message action rows, returning them
+/// ready to be sent back to Discord
+fn disable_rows(mut rows: Value) -> Value {
+    for component in rows
+        .as_array_mut()
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r.get_mut("components").and_then(Value::as_array_mut))
+        .flatten()
+        .filter_map(Value::as_object_mut)
+    {
+        component.retain(|_, v| !v.is_null());
+        component.insert("disabled".into(), true.into());
+    }
+
+    rows
+}
+
+async fn disable(sink: &(impl ResponseSink + ?Sized), src: Source<'_>) -> Result<(), SinkError> {
+    let msg = sink.get_response(src).await?;
+    let rows = disable_rows(msg.get("components").cloned().unwrap_or_default());
+
+    let mut edit = EditInteractionResponse::default();
+    edit.0.insert("components", rows);
+    sink.edit_response(src, edit.try_into()?).await?;
+
+    Ok(())
+}
+
+/// Schedule an edit disabling the components of a paginated message once its
+/// navigation expires
+///
+/// The page displayed at that point isn't known, so the message's current
+/// components are fetched and disabled in place.  The edit is made through the
+/// interaction that created the message, whose token is only valid for 15
+/// minutes, so nothing is scheduled for views which expire later than that (or
+/// never).  [`navigate`] still rejects requests for those once they expire.
+pub fn expire<K: ResponseSink + ?Sized + 'static, S, I: InteractionSource>(
+    sink: Arc<K>,
+    responder: &CreatedResponder<'_, S, I>,
+    state: PageState,
+) -> Option<JoinHandle<()>> {
+    let delay = state.remaining().filter(|&r| r < TOKEN_LIFETIME)?;
+    let Source { id, token } = responder.source();
+    let token = token.to_owned();
+
+    Some(tokio::task::spawn(async move {
+        tokio::time::sleep(delay).await;
+
+        disable(&*sink, Source { id, token: &token })
+            .await
+            .map_err(|err| tracing::warn!(?err, "Error disabling expired page components"))
+            .ok();
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::{
+        builder::EditInteractionResponse,
+        json::{json, Value},
+    };
+
+    use std::sync::Arc;
+
+    use super::{
+        disable_rows, expire, id, render, ActionRow, ButtonStyle, MessageBody, MessageComponent,
+        Nav, NavButton, PageState, View,
+    };
+    use crate::interaction::{
+        response::{
+            prelude::*,
+            sink::{Call, MemorySink},
+            InitResponder, Message,
+        },
+        testing::{self, Component, ComponentPayload, Schema},
+    };
+
+    /// A view whose pages are numbered, with one action button
+    struct Numbers(u32);
+
+    impl View<Component> for Numbers {
+        fn pages(&self) -> u32 { self.0 }
+
+        fn render(&self, page: u32) -> MessageBody<Component, id::Error> {
+            MessageBody::plain(format!("Page {page}"))
+        }
+
+        fn payload(&self, nav: Nav) -> ComponentPayload {
+            let button = NavButton::ALL.iter().position(|&b| b == nav.button).unwrap();
+            ComponentPayload::A(nav.state.page * 4 + u32::try_from(button).unwrap())
+        }
+
+        fn actions(
+            &self,
+            _: u32,
+            row: ActionRow<Component, MessageComponent, id::Error>,
+        ) -> ActionRow<Component, MessageComponent, id::Error> {
+            row.button(ComponentPayload::B(0), ButtonStyle::Primary, "Act", false)
+        }
+    }
+
+    fn render_json(pages: u32, state: PageState) -> (Value, Vec<Vec<bool>>) {
+        let mut res = EditInteractionResponse::default();
+        render(&Numbers(pages), state)
+            .prepare()
+            .unwrap()
+            .build_edit_response(&mut res);
+
+        let disabled = res.0["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                r["components"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|c| c["disabled"].as_bool().unwrap())
+                    .collect()
+            })
+            .collect();
+
+        (res.0.remove("content").unwrap(), disabled)
+    }
+
+    #[test]
+    fn test_render() {
+        let state = |page, expires| PageState { page, expires };
+
+        assert_eq!(
+            render_json(3, state(1, None)),
+            (json!("Page 1"), vec![vec![false; 4], vec![false]])
+        );
+        assert_eq!(
+            render_json(3, state(0, None)),
+            (json!("Page 0"), vec![vec![true, true, false, false], vec![false]])
+        );
+        // Out-of-range pages are clamped to the last page
+        assert_eq!(
+            render_json(3, state(7, None)),
+            (json!("Page 2"), vec![vec![false, false, true, true], vec![false]])
+        );
+        assert_eq!(
+            render_json(3, state(1, Some(0))),
+            (json!("Page 1"), vec![vec![true; 4], vec![true]])
+        );
+        // Navigation is omitted for single-page views
+        assert_eq!(render_json(1, state(0, None)), (json!("Page 0"), vec![vec![false]]));
+    }
+
+    #[test]
+    fn test_disable_rows() {
+        let rows = json!([{
+            "type": 1,
+            "components": [
+                { "type": 2, "style": 2, "label": "›", "custom_id": "a", "disabled": false },
+                {
+                    "type": 2,
+                    "style": 5,
+                    "label": "Link",
+                    "custom_id": null,
+                    "url": "https://example.com",
+                },
+            ],
+        }]);
+
+        assert_eq!(
+            disable_rows(rows),
+            json!([{
+                "type": 1,
+                "components": [
+                    { "type": 2, "style": 2, "label": "›", "custom_id": "a", "disabled": true },
+                    {
+                        "type": 2,
+                        "style": 5,
+                        "label": "Link",
+                        "url": "https://example.com",
+                        "disabled": true,
+                    },
+                ],
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expire() {
+        let sink = Arc::new(MemorySink::default());
+        let int = testing::command("test", &json!([]));
+        let state = PageState {
+            page: 1,
+            expires: Some(chrono::Utc::now().timestamp() - 1),
+        };
+        let responder = InitResponder::<Schema, _>::new(&*sink, &int)
+            .create_message(Message::from(render(&Numbers(3), PageState {
+                expires: None,
+                ..state
+            })))
+            .await
+            .unwrap();
+
+        // Views expiring after the interaction token are left alone
+        let never = PageState {
+            expires: None,
+            ..state
+        };
+        assert!(expire(Arc::clone(&sink), &responder, never).is_none());
+        let late = PageState {
+            expires: Some(chrono::Utc::now().timestamp() + 60 * 60),
+            ..state
+        };
+        assert!(expire(Arc::clone(&sink), &responder, late).is_none());
+
+        expire(Arc::clone(&sink), &responder, state)
+            .unwrap()
+            .await
+            .unwrap();
+
+        let calls = sink.calls();
+        let [Call::CreateResponse { .. }, Call::GetResponse, Call::EditResponse { body }] =
+            &calls[..]
+        else {
+            panic!("Unexpected calls {calls:?}");
+        };
+        let disabled: Vec<_> = body
+            .get("components")
+            .and_then(Value::as_array)
+            .unwrap()
+            .iter()
+            .flat_map(|r| r["components"].as_array().unwrap())
+            .map(|c| c["disabled"].as_bool().unwrap())
+            .collect();
+        assert_eq!(disabled, [true; 5]);
+    }
+
+    #[test]
+    fn test_target() {
+        let targets = |page| NavButton::ALL.map(|b| b.target(page, 3));
+
+        assert_eq!(targets(0), [None, None, Some(1), Some(2)]);
+        assert_eq!(targets(1), [Some(0), Some(0), Some(2), Some(2)]);
+        assert_eq!(targets(2), [Some(0), Some(1), None, None]);
+        assert_eq!(NavButton::Next.target(0, 1), None);
+    }
+}
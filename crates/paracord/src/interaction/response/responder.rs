@@ -61,147 +61,25 @@
 mod private {
     use std::marker::PhantomData;
 
-    use serenity::{
-        builder::{
-            CreateInteractionResponse, CreateInteractionResponseFollowup, EditInteractionResponse,
-        },
-        http::Http,
-        model::{
-            application::interaction::{
-                application_command::ApplicationCommandInteraction,
-                message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
-            },
-            channel::Message,
-            id::MessageId,
-        },
+    use serenity::model::application::interaction::{
+        application_command::ApplicationCommandInteraction,
+        message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
     };
 
-    use super::super::modal;
-
-    // serenity why
-    #[async_trait::async_trait]
-    pub trait Interaction: Sync {
-        async fn create_response<'a>(
-            &self,
-            http: &Http,
-            f: impl for<'b> FnOnce(
-                &'b mut CreateInteractionResponse<'a>,
-            ) -> &'b mut CreateInteractionResponse<'a>
-            + Send,
-        ) -> Result<(), serenity::Error>;
-
-        async fn edit_response(
-            &self,
-            http: &Http,
-            f: impl for<'a> FnOnce(&'a mut EditInteractionResponse) -> &'a mut EditInteractionResponse
-            + Send,
-        ) -> Result<Message, serenity::Error>;
-
-        async fn delete_response(&self, http: &Http) -> Result<(), serenity::Error>;
-
-        async fn create_followup_message<'a>(
-            &self,
-            http: &Http,
-            f: impl for<'b> FnOnce(
-                &'b mut CreateInteractionResponseFollowup<'a>,
-            ) -> &'b mut CreateInteractionResponseFollowup<'a>
-            + Send,
-        ) -> Result<Message, serenity::Error>;
-
-        async fn edit_followup_message<'a>(
-            &self,
-            http: &Http,
-            id: MessageId,
-            f: impl for<'b> FnOnce(
-                &'b mut CreateInteractionResponseFollowup<'a>,
-            ) -> &'b mut CreateInteractionResponseFollowup<'a>
-            + Send,
-        ) -> Result<Message, serenity::Error>;
-
-        async fn delete_followup_message(
-            &self,
-            http: &Http,
-            id: MessageId,
-        ) -> Result<(), serenity::Error>;
-    }
-
-    macro_rules! interaction {
-        ($ty:ident) => {
-            #[async_trait::async_trait]
-            impl Interaction for $ty {
-                #[inline]
-                async fn create_response<'a>(
-                    &self,
-                    http: &Http,
-                    f: impl for<'b> FnOnce(
-                        &'b mut CreateInteractionResponse<'a>,
-                    ) -> &'b mut CreateInteractionResponse<'a>
-                    + Send,
-                ) -> Result<(), serenity::Error> {
-                    $ty::create_interaction_response(self, http, f).await
-                }
-
-                #[inline]
-                async fn edit_response(
-                    &self,
-                    http: &Http,
-                    f: impl for<'a> FnOnce(
-                        &'a mut EditInteractionResponse,
-                    ) -> &'a mut EditInteractionResponse
-                    + Send,
-                ) -> Result<Message, serenity::Error> {
-                    $ty::edit_original_interaction_response(self, http, f).await
-                }
-
-                #[inline]
-                async fn delete_response(&self, http: &Http) -> Result<(), serenity::Error> {
-                    $ty::delete_original_interaction_response(self, http).await
-                }
-
-                #[inline]
-                async fn create_followup_message<'a>(
-                    &self,
-                    http: &Http,
-                    f: impl for<'b> FnOnce(
-                        &'b mut CreateInteractionResponseFollowup<'a>,
-                    ) -> &'b mut CreateInteractionResponseFollowup<'a>
-                    + Send,
-                ) -> Result<Message, serenity::Error> {
-                    $ty::create_followup_message(self, http, f).await
-                }
-
-                #[inline]
-                async fn edit_followup_message<'a>(
-                    &self,
-                    http: &Http,
-                    id: MessageId,
-                    f: impl for<'b> FnOnce(
-                        &'b mut CreateInteractionResponseFollowup<'a>,
-                    ) -> &'b mut CreateInteractionResponseFollowup<'a>
-                    + Send,
-                ) -> Result<Message, serenity::Error> {
-                    $ty::edit_followup_message(self, http, id, f).await
-                }
+    use super::super::{
+        modal,
+        sink::{InteractionSource, ResponseSink},
+    };
 
-                #[inline]
-                async fn delete_followup_message(
-                    &self,
-                    http: &Http,
-                    id: MessageId,
-                ) -> Result<(), serenity::Error> {
-                    $ty::delete_followup_message(self, http, id).await
-                }
-            }
-        };
-    }
+    pub trait Interaction: InteractionSource {}
 
-    interaction!(ApplicationCommandInteraction);
-    interaction!(MessageComponentInteraction);
-    interaction!(ModalSubmitInteraction);
+    impl Interaction for ApplicationCommandInteraction {}
+    impl Interaction for MessageComponentInteraction {}
+    impl Interaction for ModalSubmitInteraction {}
 
     #[derive(Debug)]
     pub struct ResponderCore<'a, S, I> {
-        pub(super) http: &'a Http,
+        pub(super) sink: &'a dyn ResponseSink,
         pub(super) int: &'a I,
         pub(super) schema: PhantomData<fn(S)>,
     }
@@ -265,20 +143,27 @@ mod private {
 
 use std::{future::Future, marker::PhantomData, mem};
 
-use private::{Interaction, ResponderCore};
-use serenity::{http::Http, model::application::interaction::InteractionResponseType};
+use private::ResponderCore;
+use serenity::{
+    builder::{
+        CreateInteractionResponse, CreateInteractionResponseFollowup, EditInteractionResponse,
+    },
+    model::application::interaction::InteractionResponseType,
+};
 
 use super::{
-    super::rpc::Schema, id, Message, MessageBody, MessageOpts, Modal, ModalSourceHandle,
-    ResponseData,
+    super::rpc::Schema,
+    id,
+    sink::{InteractionSource, ResponseSink, SentMessage, SinkError, Source},
+    Message, MessageBody, MessageOpts, Modal, ModalSourceHandle, ResponseData,
 };
 
 /// An error arising from sending an interaction response
 #[derive(Debug, thiserror::Error)]
 pub enum ResponseError {
-    /// A [`serenity`] (or Discord) error occurred
-    #[error("Serenity error")]
-    Serenity(#[from] serenity::Error),
+    /// The response could not be delivered
+    #[error("Error sending response")]
+    Sink(#[from] SinkError),
     /// An error occurred transcoding an [`Id`](id::Id)
     #[error("Custom ID error for component or modal")]
     Id(#[from] id::Error),
@@ -287,7 +172,73 @@ pub enum ResponseError {
 /// A followup message returned from a responder
 #[derive(Debug)]
 #[repr(transparent)]
-pub struct Followup(serenity::model::channel::Message);
+#[allow(missing_copy_implementations)] // Followups are consumed when deleted
+pub struct Followup(SentMessage);
+
+/// How a [`ResponsePlan`] recovers when one of its followups fails to send
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OnFailure {
+    /// Stop at the first failure and delete any followups already sent
+    #[default]
+    Rollback,
+    /// Send the remaining followups, then append a followup summarizing
+    /// which ones failed
+    Continue,
+}
+
+/// A series of followup messages sent together by
+/// [`ResponderExt::send_plan`]
+#[derive(Debug)]
+pub struct ResponsePlan<'a, I, E> {
+    followups: Vec<Message<'a, I, E>>,
+    on_failure: OnFailure,
+}
+
+impl<'a, I, E> Default for ResponsePlan<'a, I, E> {
+    fn default() -> Self {
+        Self {
+            followups: vec![],
+            on_failure: OnFailure::default(),
+        }
+    }
+}
+
+impl<'a, I, E> ResponsePlan<'a, I, E> {
+    /// Append a followup message to this plan
+    #[must_use]
+    pub fn followup(mut self, msg: Message<'a, I, E>) -> Self {
+        self.followups.push(msg);
+        self
+    }
+
+    /// Set how this plan recovers from a followup failing to send
+    #[must_use]
+    pub fn on_failure(mut self, on_failure: OnFailure) -> Self {
+        self.on_failure = on_failure;
+        self
+    }
+}
+
+/// The result of sending a [`ResponsePlan`]
+#[derive(Debug)]
+pub struct PlanOutcome {
+    /// Followups still visible once the plan finished, including any error
+    /// summary
+    pub sent: Vec<Followup>,
+    /// The index into the plan and error of each followup that failed to send
+    pub failed: Vec<(usize, ResponseError)>,
+    /// Whether sent followups were deleted after a failure
+    pub rolled_back: bool,
+    /// Errors encountered deleting or summarizing followups after a failure
+    pub recovery: Vec<ResponseError>,
+}
+
+impl PlanOutcome {
+    /// Returns true if every followup in the plan was sent
+    #[inline]
+    #[must_use]
+    pub fn is_complete(&self) -> bool { self.failed.is_empty() }
+}
 
 /// Common methods for all responder types
 #[async_trait::async_trait]
@@ -304,12 +255,14 @@ pub trait ResponderExt<S: Schema>: private::Responder {
     {
         let msg = msg.prepare()?;
         let ResponderCore {
-            http,
+            sink,
             int,
             schema: _,
         } = self.core();
-        Ok(int
-            .create_followup_message(http, |f| msg.build_followup(f))
+        let mut fup = CreateInteractionResponseFollowup::default();
+        msg.build_followup(&mut fup);
+        Ok(sink
+            .create_followup(int.source(), fup.try_into()?)
             .await
             .map(Followup)?)
     }
@@ -327,12 +280,14 @@ pub trait ResponderExt<S: Schema>: private::Responder {
     {
         let msg = msg.prepare()?;
         let ResponderCore {
-            http,
+            sink,
             int,
             schema: _,
         } = self.core();
+        let mut edit = CreateInteractionResponseFollowup::default();
+        msg.build_followup(&mut edit);
         *fup = Followup(
-            int.edit_followup_message(http, fup.0.id, |f| msg.build_followup(f))
+            sink.edit_followup(int.source(), fup.0.id, edit.try_into()?)
                 .await?,
         );
 
@@ -341,14 +296,78 @@ pub trait ResponderExt<S: Schema>: private::Responder {
 
     /// Delete the given followup message for this interaction
     #[inline]
-    async fn delete_followup(&self, fup: Followup) -> Result<(), serenity::Error>
+    async fn delete_followup(&self, fup: Followup) -> Result<(), SinkError>
     where Self: private::CreateFollowup {
         let ResponderCore {
-            http,
+            sink,
             int,
             schema: _,
         } = self.core();
-        int.delete_followup_message(http, fup.0.id).await
+        sink.delete_followup(int.source(), fup.0.id).await
+    }
+
+    /// Send each followup in a plan in order, recovering from failures as
+    /// specified by the plan
+    async fn send_plan(&self, plan: ResponsePlan<'_, S::Component, id::Error>) -> PlanOutcome
+    where
+        Self: private::CreateFollowup + Sync,
+        S::Component: 'async_trait,
+    {
+        let ResponsePlan {
+            followups,
+            on_failure,
+        } = plan;
+        let total = followups.len();
+        let mut outcome = PlanOutcome {
+            sent: vec![],
+            failed: vec![],
+            rolled_back: false,
+            recovery: vec![],
+        };
+
+        for (i, msg) in followups.into_iter().enumerate() {
+            match self.create_followup(msg).await {
+                Ok(fup) => outcome.sent.push(fup),
+                Err(err) => {
+                    tracing::warn!(index = i, ?err, "Followup in response plan failed");
+                    outcome.failed.push((i, err));
+
+                    if on_failure == OnFailure::Rollback {
+                        break;
+                    }
+                },
+            }
+        }
+
+        if outcome.is_complete() {
+            return outcome;
+        }
+
+        match on_failure {
+            OnFailure::Rollback => {
+                for fup in mem::take(&mut outcome.sent) {
+                    if let Err(err) = self.delete_followup(fup).await {
+                        outcome.recovery.push(err.into());
+                    }
+                }
+                outcome.rolled_back = true;
+            },
+            OnFailure::Continue => {
+                let failed = outcome.failed.len();
+                let summary = if failed == 1 {
+                    format!("1 of {total} messages failed to send.")
+                } else {
+                    format!("{failed} of {total} messages failed to send.")
+                };
+
+                match self.create_followup(Message::plain(summary)).await {
+                    Ok(fup) => outcome.sent.push(fup),
+                    Err(err) => outcome.recovery.push(err),
+                }
+            },
+        }
+
+        outcome
     }
 }
 
@@ -363,14 +382,14 @@ impl<R: private::Responder> ResponderExt<R::Schema> for R {}
 pub struct InitResponder<'a, S, I>(ResponderCore<'a, S, I>);
 
 impl<'a, S, I> InitResponder<'a, S, I> {
-    /// Wrap an HTTP client and interaction reference in a new responder
+    /// Wrap a response sink and interaction reference in a new responder
     #[inline]
     #[must_use]
-    pub fn new(http: &'a Http, int: &'a I) -> Self {
+    pub fn new(sink: &'a dyn ResponseSink, int: &'a I) -> Self {
         Self(ResponderCore {
-            http,
+            sink,
             int,
-            schema: PhantomData::default(),
+            schema: PhantomData,
         })
     }
 }
@@ -382,19 +401,18 @@ impl<'a, S: Schema, I: private::Interaction> InitResponder<'a, S, I> {
         ty: InteractionResponseType,
         data: impl ResponseData<'_> + Send,
         next: impl FnOnce(ResponderCore<'a, S, I>) -> T,
-    ) -> Result<T, serenity::Error> {
+    ) -> Result<T, SinkError> {
         let Self(
             core @ ResponderCore {
-                http,
+                sink,
                 int,
                 schema: _,
             },
         ) = self;
-        int.create_response(http, |res| {
-            res.kind(ty)
-                .interaction_response_data(|d| data.build_response_data(d))
-        })
-        .await?;
+        let mut res = CreateInteractionResponse::default();
+        res.kind(ty)
+            .interaction_response_data(|d| data.build_response_data(d));
+        sink.create_response(int.source(), res.try_into()?).await?;
         Ok(next(core))
     }
 
@@ -426,7 +444,7 @@ impl<'a, S: Schema, I: private::Interaction> InitResponder<'a, S, I> {
     pub async fn defer_message(
         self,
         opts: MessageOpts,
-    ) -> Result<CreatedResponder<'a, S, I>, serenity::Error> {
+    ) -> Result<CreatedResponder<'a, S, I>, SinkError> {
         self.create(
             InteractionResponseType::DeferredChannelMessageWithSource,
             opts,
@@ -465,7 +483,7 @@ impl<'a, S: Schema, I: private::CreateUpdate> InitResponder<'a, S, I> {
     pub async fn defer_update(
         self,
         opts: MessageOpts, // TODO: is this usable?
-    ) -> Result<CreatedResponder<'a, S, I>, serenity::Error> {
+    ) -> Result<CreatedResponder<'a, S, I>, SinkError> {
         self.create(
             InteractionResponseType::DeferredUpdateMessage,
             opts,
@@ -503,6 +521,11 @@ impl<'a, S: Schema, I: private::CreateModal> InitResponder<'a, S, I> {
 #[repr(transparent)]
 pub struct CreatedResponder<'a, S, I>(ResponderCore<'a, S, I>);
 
+impl<'a, S, I: InteractionSource> CreatedResponder<'a, S, I> {
+    #[inline]
+    pub(super) fn source(&self) -> Source<'_> { self.0.int.source() }
+}
+
 impl<'a, S: Schema, I: private::Interaction> CreatedResponder<'a, S, I> {
     /// Void this responder, disallowing any response methods from being called
     #[inline]
@@ -518,12 +541,14 @@ impl<'a, S: Schema, I: private::Interaction> CreatedResponder<'a, S, I> {
     pub async fn edit(
         &self,
         res: MessageBody<S::Component, id::Error>,
-    ) -> Result<serenity::model::channel::Message, ResponseError> {
+    ) -> Result<SentMessage, ResponseError> {
         let res = res.prepare()?;
+        let mut edit = EditInteractionResponse::default();
+        res.build_edit_response(&mut edit);
         Ok(self
             .0
-            .int
-            .edit_response(self.0.http, |e| res.build_edit_response(e))
+            .sink
+            .edit_response(self.0.int.source(), edit.try_into()?)
             .await?)
     }
 
@@ -532,8 +557,8 @@ impl<'a, S: Schema, I: private::Interaction> CreatedResponder<'a, S, I> {
     /// # Errors
     /// This method returns an error if an API error is received.
     #[inline]
-    pub async fn delete(self) -> Result<(), serenity::Error> {
-        self.0.int.delete_response(self.0.http).await
+    pub async fn delete(self) -> Result<(), SinkError> {
+        self.0.sink.delete_response(self.0.int.source()).await
     }
 }
 
@@ -580,14 +605,14 @@ pub enum BorrowedResponder<'a, S, I> {
 }
 
 impl<'a, S, I> BorrowedResponder<'a, S, I> {
-    /// Wrap an HTTP client and interaction reference in a new responder
+    /// Wrap a response sink and interaction reference in a new responder
     #[inline]
     #[must_use]
-    pub fn new(http: &'a Http, int: &'a I) -> Self {
+    pub fn new(sink: &'a dyn ResponseSink, int: &'a I) -> Self {
         Self::Init(InitResponder(ResponderCore {
-            http,
+            sink,
             int,
-            schema: PhantomData::default(),
+            schema: PhantomData,
         }))
     }
 }
@@ -694,7 +719,7 @@ impl<'a, 'b, S: Schema, I: private::Interaction> BorrowingResponder<'a, 'b, S, I
     pub async fn defer_message(
         self,
         opts: MessageOpts,
-    ) -> Result<CreatedResponder<'b, S, I>, serenity::Error> {
+    ) -> Result<CreatedResponder<'b, S, I>, SinkError> {
         // SAFETY: this is a create response endpoint
         unsafe { self.take(|i| i.defer_message(opts)).await }
     }
@@ -723,7 +748,7 @@ impl<'a, 'b, S: Schema, I: private::CreateUpdate> BorrowingResponder<'a, 'b, S,
     pub async fn defer_update(
         self,
         opts: MessageOpts, // TODO: is this usable?
-    ) -> Result<CreatedResponder<'b, S, I>, serenity::Error> {
+    ) -> Result<CreatedResponder<'b, S, I>, SinkError> {
         // SAFETY: this is a create response endpoint
         unsafe { self.take(|i| i.defer_update(opts)).await }
     }
@@ -746,3 +771,197 @@ impl<'a, 'b, S: Schema, I: private::CreateModal> BorrowingResponder<'a, 'b, S, I
         unsafe { self.take(|i| i.modal(f)).await }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serenity::json::{json, Value};
+
+    use super::{
+        super::sink::{
+            Body, Call, MemorySink, MessageId, Payload, ResponseSink, SentMessage, SinkError,
+            Source,
+        },
+        InitResponder, Message, OnFailure, ResponderExt, ResponsePlan,
+    };
+    use crate::interaction::testing::{self, Schema};
+
+    /// Records requests like a [`MemorySink`], but fails to create followups
+    /// with the given content, and optionally to delete any followup
+    #[derive(Debug, Default)]
+    struct FlakySink {
+        inner: MemorySink,
+        fail: &'static [&'static str],
+        fail_deletes: bool,
+    }
+
+    fn failed() -> SinkError { SinkError::Transport("Simulated failure".into()) }
+
+    #[async_trait::async_trait]
+    impl ResponseSink for FlakySink {
+        async fn create_response(
+            &self,
+            src: Source<'_>,
+            payload: Payload<'_>,
+        ) -> Result<(), SinkError> {
+            self.inner.create_response(src, payload).await
+        }
+
+        async fn edit_response(
+            &self,
+            src: Source<'_>,
+            payload: Payload<'_>,
+        ) -> Result<SentMessage, SinkError> {
+            self.inner.edit_response(src, payload).await
+        }
+
+        async fn delete_response(&self, src: Source<'_>) -> Result<(), SinkError> {
+            self.inner.delete_response(src).await
+        }
+
+        async fn get_response(&self, src: Source<'_>) -> Result<Body, SinkError> {
+            self.inner.get_response(src).await
+        }
+
+        async fn create_followup(
+            &self,
+            src: Source<'_>,
+            payload: Payload<'_>,
+        ) -> Result<SentMessage, SinkError> {
+            let content = payload.body.get("content").and_then(Value::as_str);
+            if self.fail.iter().any(|f| content == Some(f)) {
+                return Err(failed());
+            }
+
+            self.inner.create_followup(src, payload).await
+        }
+
+        async fn edit_followup(
+            &self,
+            src: Source<'_>,
+            id: MessageId,
+            payload: Payload<'_>,
+        ) -> Result<SentMessage, SinkError> {
+            self.inner.edit_followup(src, id, payload).await
+        }
+
+        async fn delete_followup(&self, src: Source<'_>, id: MessageId) -> Result<(), SinkError> {
+            if self.fail_deletes {
+                return Err(failed());
+            }
+
+            self.inner.delete_followup(src, id).await
+        }
+    }
+
+    fn plan(on_failure: OnFailure) -> ResponsePlan<'static, testing::Component, super::id::Error> {
+        ResponsePlan::default()
+            .followup(Message::plain("a"))
+            .followup(Message::plain("b"))
+            .followup(Message::plain("c"))
+            .on_failure(on_failure)
+    }
+
+    fn followups(calls: &[Call]) -> Vec<Value> {
+        calls
+            .iter()
+            .filter_map(|c| match c {
+                Call::CreateFollowup { body, .. } => body.get("content").cloned(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_plan_complete() {
+        let int = testing::command("test", &json!([]));
+        let sink = FlakySink::default();
+        let responder = InitResponder::<Schema, _>::new(&sink, &int)
+            .create_message(Message::plain("hi"))
+            .await
+            .unwrap();
+
+        let outcome = responder.send_plan(plan(OnFailure::Rollback)).await;
+        assert!(outcome.is_complete());
+        assert!(!outcome.rolled_back);
+        assert_eq!(outcome.sent.len(), 3);
+        assert_eq!(followups(&sink.inner.calls()), [
+            json!("a"),
+            json!("b"),
+            json!("c")
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_plan_rollback() {
+        let int = testing::command("test", &json!([]));
+        let sink = FlakySink {
+            fail: &["b"],
+            ..FlakySink::default()
+        };
+        let responder = InitResponder::<Schema, _>::new(&sink, &int)
+            .create_message(Message::plain("hi"))
+            .await
+            .unwrap();
+
+        let outcome = responder.send_plan(plan(OnFailure::Rollback)).await;
+        assert!(!outcome.is_complete());
+        assert!(outcome.rolled_back);
+        assert!(outcome.sent.is_empty());
+        assert!(outcome.recovery.is_empty());
+        assert!(matches!(&outcome.failed[..], [(1, _)]));
+
+        // The first followup is deleted, and the last is never sent
+        let calls = sink.inner.calls();
+        assert_eq!(followups(&calls), [json!("a")]);
+        assert!(matches!(
+            &calls[..],
+            [
+                Call::CreateResponse { .. },
+                Call::CreateFollowup { .. },
+                Call::DeleteFollowup { id: MessageId(2) },
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_plan_rollback_failure() {
+        let int = testing::command("test", &json!([]));
+        let sink = FlakySink {
+            fail: &["c"],
+            fail_deletes: true,
+            ..FlakySink::default()
+        };
+        let responder = InitResponder::<Schema, _>::new(&sink, &int)
+            .create_message(Message::plain("hi"))
+            .await
+            .unwrap();
+
+        let outcome = responder.send_plan(plan(OnFailure::Rollback)).await;
+        assert!(outcome.rolled_back);
+        assert!(matches!(&outcome.failed[..], [(2, _)]));
+        assert_eq!(outcome.recovery.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_plan_continue() {
+        let int = testing::command("test", &json!([]));
+        let sink = FlakySink {
+            fail: &["a", "c"],
+            ..FlakySink::default()
+        };
+        let responder = InitResponder::<Schema, _>::new(&sink, &int)
+            .create_message(Message::plain("hi"))
+            .await
+            .unwrap();
+
+        let outcome = responder.send_plan(plan(OnFailure::Continue)).await;
+        assert!(!outcome.rolled_back);
+        assert!(outcome.recovery.is_empty());
+        assert!(matches!(&outcome.failed[..], [(0, _), (2, _)]));
+        assert_eq!(outcome.sent.len(), 2);
+        assert_eq!(followups(&sink.inner.calls()), [
+            json!("b"),
+            json!("2 of 3 messages failed to send.")
+        ]);
+    }
+}
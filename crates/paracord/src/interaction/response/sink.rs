@@ -0,0 +1,745 @@
+//! The boundary between responders and the underlying Discord client
+//!
+//! Responders never call into [`serenity`]'s HTTP client or interaction models
+//! directly.  Instead, each response is rendered to a [`Payload`] and handed
+//! to a [`ResponseSink`] along with the [`Source`] identifying the interaction
+//! being responded to, as provided by its [`InteractionSource`].  Sinks only
+//! see the types defined here, and report failures as a [`SinkError`].  IDs,
+//! uploads and request bodies are all represented by types owned by this
+//! module, and are only converted to and from serenity's types by the
+//! [`InteractionSource`] implementations for serenity's interactions and the
+//! [`Http`] sink.  Migrating to a new major version of serenity should only
+//! require updating those implementations, leaving the public builder and
+//! responder APIs untouched.
+//!
+//! Two sinks are provided: [`Http`], which sends requests to Discord, and
+//! [`MemorySink`], which records them for inspection in tests.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    path::Path,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use serde_json::{Map, Value};
+use serenity::{
+    builder::{
+        CreateInteractionResponse, CreateInteractionResponseFollowup, EditInteractionResponse,
+    },
+    constants::{EMBED_MAX_COUNT, EMBED_MAX_LENGTH, MESSAGE_CODE_LIMIT},
+    http::Http,
+    json,
+    model::{
+        application::interaction::{
+            application_command::ApplicationCommandInteraction,
+            message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+        },
+        channel::{AttachmentType, Message},
+    },
+};
+use url::Url;
+
+macro_rules! id {
+    ($($(#[$meta:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[repr(transparent)]
+            pub struct $name(pub u64);
+
+            impl fmt::Display for $name {
+                #[inline]
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+            }
+        )*
+    };
+}
+
+id!(
+    /// The ID of an interaction
+    InteractionId,
+    /// The ID of a message
+    MessageId,
+    /// The ID of a channel
+    ChannelId,
+);
+
+/// The JSON body of a request
+#[derive(Debug, Clone, Default, PartialEq)]
+#[repr(transparent)]
+pub struct Body(Map<String, Value>);
+
+impl Body {
+    /// Get the value of a top-level field of the body
+    #[inline]
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> { self.0.get(key) }
+
+    /// Get the body as a JSON object
+    #[inline]
+    #[must_use]
+    pub fn as_map(&self) -> &Map<String, Value> { &self.0 }
+
+    /// Consume the body, returning it as a JSON object
+    #[inline]
+    #[must_use]
+    pub fn into_map(self) -> Map<String, Value> { self.0 }
+}
+
+/// An error arising from delivering a response through a [`ResponseSink`]
+#[derive(Debug, thiserror::Error)]
+pub enum SinkError {
+    /// The message content exceeded Discord's length limit
+    #[error("Message content is {0} character(s) too long")]
+    ContentTooLong(usize),
+    /// The message contained more embeds than Discord allows
+    #[error("Message contains too many embeds")]
+    EmbedAmount,
+    /// An embed exceeded Discord's combined length limit
+    #[error("Embed is {0} character(s) too long")]
+    EmbedTooLarge(usize),
+    /// Files were attached to a request which cannot upload them
+    #[error("Files cannot be uploaded with this request")]
+    UnsupportedUpload,
+    /// A file was attached in a form this module doesn't know how to upload
+    #[error("Unsupported attachment type")]
+    UnknownUpload,
+    /// The underlying transport failed to deliver the request
+    #[error("Error delivering response")]
+    Transport(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+fn transport(err: serenity::Error) -> SinkError { SinkError::Transport(err.into()) }
+
+/// The identifying information of an interaction being responded to
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    /// The ID of the interaction
+    pub id: InteractionId,
+    /// The continuation token of the interaction
+    pub token: &'a str,
+}
+
+/// An interaction which can be responded to through a [`ResponseSink`]
+pub trait InteractionSource: Sync {
+    /// Get the information identifying this interaction to a sink
+    fn source(&self) -> Source<'_>;
+}
+
+macro_rules! interaction_source {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl InteractionSource for $ty {
+                #[inline]
+                fn source(&self) -> Source<'_> {
+                    Source {
+                        id: InteractionId(self.id.0),
+                        token: &self.token,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+interaction_source!(
+    ApplicationCommandInteraction,
+    MessageComponentInteraction,
+    ModalSubmitInteraction,
+);
+
+/// A file to upload with a response
+#[derive(Debug)]
+pub enum Upload<'a> {
+    /// A file held in memory
+    Bytes {
+        /// The contents of the file
+        data: Cow<'a, [u8]>,
+        /// The name to upload the file with
+        filename: String,
+    },
+    /// An open file
+    File {
+        /// The file to read
+        file: &'a tokio::fs::File,
+        /// The name to upload the file with
+        filename: String,
+    },
+    /// A file on disk, uploaded with its own name
+    Path(&'a Path),
+    /// A file to download and re-upload, named after the last segment of
+    /// its URL
+    Url(Url),
+}
+
+impl<'a> Upload<'a> {
+    /// Get the name the file will be uploaded with, if known
+    #[must_use]
+    pub fn filename(&self) -> Option<&str> {
+        match self {
+            Self::Bytes { filename, .. } | Self::File { filename, .. } => Some(filename),
+            Self::Path(p) => p.file_name().and_then(std::ffi::OsStr::to_str),
+            Self::Url(u) => u.path_segments().and_then(Iterator::last),
+        }
+    }
+}
+
+impl<'a> TryFrom<AttachmentType<'a>> for Upload<'a> {
+    type Error = SinkError;
+
+    fn try_from(att: AttachmentType<'a>) -> Result<Self, SinkError> {
+        Ok(match att {
+            AttachmentType::Bytes { data, filename } => Self::Bytes { data, filename },
+            AttachmentType::File { file, filename } => Self::File { file, filename },
+            AttachmentType::Path(p) => Self::Path(p),
+            AttachmentType::Image(u) => Self::Url(u),
+            _ => return Err(SinkError::UnknownUpload),
+        })
+    }
+}
+
+impl<'a> From<Upload<'a>> for AttachmentType<'a> {
+    fn from(upload: Upload<'a>) -> Self {
+        match upload {
+            Upload::Bytes { data, filename } => Self::Bytes { data, filename },
+            Upload::File { file, filename } => Self::File { file, filename },
+            Upload::Path(p) => Self::Path(p),
+            Upload::Url(u) => Self::Image(u),
+        }
+    }
+}
+
+/// A message created or edited by a [`ResponseSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentMessage {
+    /// The ID of the message
+    pub id: MessageId,
+    /// The ID of the channel the message was sent in
+    pub channel_id: ChannelId,
+}
+
+fn sent(msg: &Message) -> SentMessage {
+    SentMessage {
+        id: MessageId(msg.id.0),
+        channel_id: ChannelId(msg.channel_id.0),
+    }
+}
+
+/// A rendered request body, plus any files to upload with it
+///
+/// Payloads are checked against Discord's message length limits when they are
+/// rendered, so sinks never receive a message Discord would reject for its
+/// size.
+#[derive(Debug)]
+pub struct Payload<'a> {
+    /// The JSON body of the request
+    pub body: Body,
+    /// Files to attach to the request
+    pub files: Vec<Upload<'a>>,
+}
+
+impl<'a> Payload<'a> {
+    fn new(
+        body: HashMap<&'static str, Value>,
+        files: Vec<AttachmentType<'a>>,
+    ) -> Result<Self, SinkError> {
+        let body = json::hashmap_to_json_map(body);
+
+        // Interaction responses nest their message under a data field
+        match body.get("data") {
+            Some(Value::Object(data)) => check_lengths(data)?,
+            _ => check_lengths(&body)?,
+        }
+
+        Ok(Self {
+            body: Body(body),
+            files: files
+                .into_iter()
+                .map(Upload::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn check_content_length(body: &Map<String, Value>) -> Result<(), SinkError> {
+    if let Some(Value::String(content)) = body.get("content") {
+        let len = content.chars().count();
+        if len > MESSAGE_CODE_LIMIT {
+            return Err(SinkError::ContentTooLong(len - MESSAGE_CODE_LIMIT));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_embed_length(body: &Map<String, Value>) -> Result<(), SinkError> {
+    let Some(Value::Array(embeds)) = body.get("embeds") else {
+        return Ok(());
+    };
+
+    if embeds.len() > EMBED_MAX_COUNT {
+        return Err(SinkError::EmbedAmount);
+    }
+
+    let text_len = |v: Option<&Value>| v.and_then(Value::as_str).map_or(0, |s| s.chars().count());
+
+    for embed in embeds {
+        let fields = embed.get("fields").and_then(Value::as_array);
+        let total = text_len(embed.pointer("/author/name"))
+            + text_len(embed.get("description"))
+            + text_len(embed.pointer("/footer/text"))
+            + text_len(embed.get("title"))
+            + fields.map_or(0, |f| {
+                f.iter()
+                    .map(|f| text_len(f.get("name")) + text_len(f.get("value")))
+                    .sum()
+            });
+
+        if total > EMBED_MAX_LENGTH {
+            return Err(SinkError::EmbedTooLarge(total - EMBED_MAX_LENGTH));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_lengths(body: &Map<String, Value>) -> Result<(), SinkError> {
+    check_content_length(body)?;
+    check_embed_length(body)
+}
+
+impl<'a> TryFrom<CreateInteractionResponse<'a>> for Payload<'a> {
+    type Error = SinkError;
+
+    fn try_from(
+        CreateInteractionResponse(body, files): CreateInteractionResponse<'a>,
+    ) -> Result<Self, SinkError> {
+        Self::new(body, files)
+    }
+}
+
+impl<'a> TryFrom<CreateInteractionResponseFollowup<'a>> for Payload<'a> {
+    type Error = SinkError;
+
+    fn try_from(
+        CreateInteractionResponseFollowup(body, files): CreateInteractionResponseFollowup<'a>,
+    ) -> Result<Self, SinkError> {
+        Self::new(body, files)
+    }
+}
+
+impl TryFrom<EditInteractionResponse> for Payload<'static> {
+    type Error = SinkError;
+
+    fn try_from(EditInteractionResponse(body): EditInteractionResponse) -> Result<Self, SinkError> {
+        Self::new(body, vec![])
+    }
+}
+
+/// A transport capable of delivering interaction responses
+#[async_trait::async_trait]
+pub trait ResponseSink: fmt::Debug + Send + Sync {
+    /// Create the initial response to an interaction
+    async fn create_response(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<(), SinkError>;
+
+    /// Edit the original response message of an interaction
+    async fn edit_response(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError>;
+
+    /// Delete the original response message of an interaction
+    async fn delete_response(&self, src: Source<'_>) -> Result<(), SinkError>;
+
+    /// Fetch the original response message of an interaction as it currently
+    /// appears
+    async fn get_response(&self, src: Source<'_>) -> Result<Body, SinkError>;
+
+    /// Create a followup message for an interaction
+    async fn create_followup(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError>;
+
+    /// Edit a followup message for an interaction
+    async fn edit_followup(
+        &self,
+        src: Source<'_>,
+        id: MessageId,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError>;
+
+    /// Delete a followup message for an interaction
+    async fn delete_followup(&self, src: Source<'_>, id: MessageId) -> Result<(), SinkError>;
+}
+
+fn split(payload: Payload<'_>) -> (Value, Vec<AttachmentType<'_>>) {
+    let Payload { body, files } = payload;
+    (Value::from(body.0), files.into_iter().map(Into::into).collect())
+}
+
+#[async_trait::async_trait]
+impl ResponseSink for Http {
+    async fn create_response(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<(), SinkError> {
+        let (body, files) = split(payload);
+
+        if files.is_empty() {
+            self.create_interaction_response(src.id.0, src.token, &body)
+                .await
+        } else {
+            self.create_interaction_response_with_files(src.id.0, src.token, &body, files)
+                .await
+        }
+        .map_err(transport)
+    }
+
+    async fn edit_response(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError> {
+        // The edit endpoint used by serenity 0.11 does not support uploads
+        if !payload.files.is_empty() {
+            return Err(SinkError::UnsupportedUpload);
+        }
+
+        self.edit_original_interaction_response(src.token, &Value::from(payload.body.0))
+            .await
+            .map(|m| sent(&m))
+            .map_err(transport)
+    }
+
+    async fn delete_response(&self, src: Source<'_>) -> Result<(), SinkError> {
+        self.delete_original_interaction_response(src.token)
+            .await
+            .map_err(transport)
+    }
+
+    async fn get_response(&self, src: Source<'_>) -> Result<Body, SinkError> {
+        let msg = self
+            .get_original_interaction_response(src.token)
+            .await
+            .map_err(transport)?;
+
+        match serde_json::to_value(msg).map_err(|e| SinkError::Transport(e.into()))? {
+            Value::Object(map) => Ok(Body(map)),
+            _ => unreachable!("Message did not serialize to an object"),
+        }
+    }
+
+    async fn create_followup(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError> {
+        let (body, files) = split(payload);
+
+        if files.is_empty() {
+            self.create_followup_message(src.token, &body).await
+        } else {
+            self.create_followup_message_with_files(src.token, &body, files)
+                .await
+        }
+        .map(|m| sent(&m))
+        .map_err(transport)
+    }
+
+    async fn edit_followup(
+        &self,
+        src: Source<'_>,
+        id: MessageId,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError> {
+        let (body, files) = split(payload);
+
+        if files.is_empty() {
+            self.edit_followup_message(src.token, id.0, &body).await
+        } else {
+            self.edit_followup_message_and_attachments(src.token, id.0, &body, files)
+                .await
+        }
+        .map(|m| sent(&m))
+        .map_err(transport)
+    }
+
+    async fn delete_followup(&self, src: Source<'_>, id: MessageId) -> Result<(), SinkError> {
+        self.delete_followup_message(src.token, id.0)
+            .await
+            .map_err(transport)
+    }
+}
+
+/// A single request recorded by a [`MemorySink`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call {
+    /// An initial response was created
+    CreateResponse {
+        /// The ID of the interaction responded to
+        id: InteractionId,
+        /// The JSON body of the request
+        body: Body,
+        /// The number of attached files
+        files: usize,
+    },
+    /// The original response message was edited
+    EditResponse {
+        /// The JSON body of the request
+        body: Body,
+    },
+    /// The original response message was deleted
+    DeleteResponse,
+    /// The original response message was fetched
+    GetResponse,
+    /// A followup message was created
+    CreateFollowup {
+        /// The JSON body of the request
+        body: Body,
+        /// The number of attached files
+        files: usize,
+    },
+    /// A followup message was edited
+    EditFollowup {
+        /// The ID of the edited message
+        id: MessageId,
+        /// The JSON body of the request
+        body: Body,
+        /// The number of attached files
+        files: usize,
+    },
+    /// A followup message was deleted
+    DeleteFollowup {
+        /// The ID of the deleted message
+        id: MessageId,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    calls: Vec<Call>,
+    next_id: u64,
+    /// The message data of the original response, with any edits applied
+    response: Option<Map<String, Value>>,
+}
+
+/// A sink which records every request instead of sending it, for testing
+/// response logic without a connection to Discord
+///
+/// Requests returning a message are answered with a fresh message ID, and the
+/// original response is answered with the message data it was created with,
+/// with any edits applied.
+#[derive(Debug, Default)]
+pub struct MemorySink(Mutex<Recording>);
+
+impl MemorySink {
+    fn lock(&self) -> MutexGuard<Recording> { self.0.lock().unwrap_or_else(PoisonError::into_inner) }
+
+    fn record(&self, call: Call) -> SentMessage {
+        let mut rec = self.lock();
+        let id = if let Call::EditFollowup { id, .. } = call {
+            id
+        } else {
+            rec.next_id += 1;
+            MessageId(rec.next_id)
+        };
+        rec.calls.push(call);
+
+        SentMessage {
+            id,
+            channel_id: ChannelId(1),
+        }
+    }
+
+    /// Get every request recorded so far, oldest first
+    #[must_use]
+    pub fn calls(&self) -> Vec<Call> { self.lock().calls.clone() }
+}
+
+#[async_trait::async_trait]
+impl ResponseSink for MemorySink {
+    async fn create_response(
+        &self,
+        src: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<(), SinkError> {
+        let data = payload.body.get("data").and_then(Value::as_object).cloned();
+        self.lock().response = data;
+
+        self.record(Call::CreateResponse {
+            id: src.id,
+            body: payload.body,
+            files: payload.files.len(),
+        });
+        Ok(())
+    }
+
+    async fn edit_response(
+        &self,
+        _: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError> {
+        if !payload.files.is_empty() {
+            return Err(SinkError::UnsupportedUpload);
+        }
+
+        if let Some(ref mut res) = self.lock().response {
+            res.extend(payload.body.as_map().clone());
+        }
+
+        Ok(self.record(Call::EditResponse { body: payload.body }))
+    }
+
+    async fn delete_response(&self, _: Source<'_>) -> Result<(), SinkError> {
+        self.lock().response = None;
+        self.record(Call::DeleteResponse);
+        Ok(())
+    }
+
+    async fn get_response(&self, _: Source<'_>) -> Result<Body, SinkError> {
+        self.record(Call::GetResponse);
+
+        self.lock()
+            .response
+            .clone()
+            .map(Body)
+            .ok_or_else(|| SinkError::Transport("Unknown interaction response".into()))
+    }
+
+    async fn create_followup(
+        &self,
+        _: Source<'_>,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError> {
+        Ok(self.record(Call::CreateFollowup {
+            body: payload.body,
+            files: payload.files.len(),
+        }))
+    }
+
+    async fn edit_followup(
+        &self,
+        _: Source<'_>,
+        id: MessageId,
+        payload: Payload<'_>,
+    ) -> Result<SentMessage, SinkError> {
+        Ok(self.record(Call::EditFollowup {
+            id,
+            body: payload.body,
+            files: payload.files.len(),
+        }))
+    }
+
+    async fn delete_followup(&self, _: Source<'_>, id: MessageId) -> Result<(), SinkError> {
+        self.record(Call::DeleteFollowup { id });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::{
+        builder::{
+            CreateEmbed, CreateInteractionResponse, CreateInteractionResponseFollowup,
+            EditInteractionResponse,
+        },
+        model::channel::AttachmentType,
+    };
+
+    use super::{
+        Call, InteractionId, MemorySink, MessageId, Payload, ResponseSink, SinkError, Source,
+    };
+
+    const SRC: Source<'static> = Source {
+        id: InteractionId(2),
+        token: "token",
+    };
+
+    #[tokio::test]
+    async fn test_memory_sink() {
+        let sink = MemorySink::default();
+
+        let mut res = CreateInteractionResponse::default();
+        res.interaction_response_data(|d| d.content("hi"));
+        sink.create_response(SRC, res.try_into().unwrap())
+            .await
+            .unwrap();
+
+        let mut fup = CreateInteractionResponseFollowup::default();
+        fup.content("bye");
+        let msg = sink
+            .create_followup(SRC, fup.try_into().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(msg.id, MessageId(2));
+
+        sink.delete_followup(SRC, msg.id).await.unwrap();
+
+        let calls = sink.calls();
+        assert!(matches!(
+            &calls[..],
+            [
+                Call::CreateResponse {
+                    id: InteractionId(2),
+                    files: 0,
+                    ..
+                },
+                Call::CreateFollowup { files: 0, .. },
+                Call::DeleteFollowup { id: MessageId(2) },
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_check_lengths() {
+        let mut res = CreateInteractionResponse::default();
+        res.interaction_response_data(|d| d.content("a".repeat(2001)));
+        assert!(matches!(
+            Payload::try_from(res),
+            Err(SinkError::ContentTooLong(1))
+        ));
+
+        let mut fup = CreateInteractionResponseFollowup::default();
+        fup.embed(|e| e.title("a".repeat(4000)).description("b".repeat(2001)));
+        assert!(matches!(
+            Payload::try_from(fup),
+            Err(SinkError::EmbedTooLarge(1))
+        ));
+
+        let mut fup = CreateInteractionResponseFollowup::default();
+        fup.add_embeds(vec![CreateEmbed::default(); 11]);
+        assert!(matches!(Payload::try_from(fup), Err(SinkError::EmbedAmount)));
+
+        let mut edit = EditInteractionResponse::default();
+        edit.content("a".repeat(2000));
+        assert!(Payload::try_from(edit).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_edit_upload() {
+        let mut fup = CreateInteractionResponseFollowup::default();
+        fup.add_file(AttachmentType::Bytes {
+            data: b"hi".as_slice().into(),
+            filename: "hi.txt".into(),
+        });
+        let payload = Payload::try_from(fup).unwrap();
+        assert_eq!(payload.files[0].filename(), Some("hi.txt"));
+
+        let sink = MemorySink::default();
+        assert!(matches!(
+            sink.edit_response(SRC, payload).await,
+            Err(SinkError::UnsupportedUpload)
+        ));
+        assert!(sink.calls().is_empty());
+    }
+}
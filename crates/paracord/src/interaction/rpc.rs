@@ -4,7 +4,7 @@
 //! [`Id`]: super::response::id::Id
 //! [`Components`]: super::response::Components
 
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
 use serenity::model::application::interaction::{
     message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
@@ -23,6 +23,65 @@ where for<'a> Self: From<&'a Self::Payload>
     type Payload: fmt::Debug;
     /// The interaction event type for which this key is valid
     type Interaction;
+
+    /// Get a stable, semantic name for a payload, used to label interactions
+    /// in logs and analytics
+    ///
+    /// The default implementation labels every payload with the [`Debug`]
+    /// representation of its key.  Implementors should override this to
+    /// distinguish between payloads that trigger different actions, and
+    /// should avoid renaming labels once they have been used.
+    ///
+    /// [`Debug`]: fmt::Debug
+    #[inline]
+    fn label(payload: &Self::Payload) -> Label {
+        Label::new(format!("{:?}", Self::from(payload)))
+    }
+}
+
+/// A stable name identifying the action an RPC payload triggers, formatted as
+/// `handler.variant`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Label {
+    /// The name of the handler receiving the payload
+    pub handler: Cow<'static, str>,
+    /// The action within the handler, if it handles more than one
+    pub variant: Option<Cow<'static, str>>,
+}
+
+impl Label {
+    /// Construct a label for a handler with a single action
+    #[inline]
+    #[must_use]
+    pub fn new(handler: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            handler: handler.into(),
+            variant: None,
+        }
+    }
+
+    /// Add the name of the action within the handler to this label
+    #[inline]
+    #[must_use]
+    pub fn variant(self, variant: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            variant: Some(variant.into()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { handler, variant } = self;
+
+        f.write_str(handler)?;
+        if let Some(variant) = variant {
+            write!(f, ".{variant}")?;
+        }
+
+        Ok(())
+    }
 }
 
 /// A helper trait intended to be implemented on a marker unit struct for
@@ -0,0 +1,203 @@
+//! Fixtures for exercising handlers without a connection to Discord
+
+use std::sync::Arc;
+
+use serenity::{
+    client::{bridge::gateway::ShardMessenger, Context},
+    http::Http,
+    json::{json, Value},
+    model::application::interaction::{
+        application_command::ApplicationCommandInteraction,
+        message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+    },
+};
+
+use super::{
+    response::ModalSource,
+    rpc::{self, ComponentId, ModalId},
+};
+
+/// A minimal RPC schema with two component payloads and one modal payload
+#[derive(Debug)]
+pub struct Schema;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Component {
+    #[prost(oneof = "ComponentPayload", tags = "1, 2")]
+    pub payload: Option<ComponentPayload>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum ComponentPayload {
+    #[prost(uint32, tag = "1")]
+    A(u32),
+    #[prost(uint32, tag = "2")]
+    B(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComponentKey {
+    A,
+    B,
+}
+
+impl From<&ComponentPayload> for ComponentKey {
+    fn from(payload: &ComponentPayload) -> Self {
+        match payload {
+            ComponentPayload::A(_) => Self::A,
+            ComponentPayload::B(_) => Self::B,
+        }
+    }
+}
+
+impl rpc::Key for ComponentKey {
+    type Interaction = MessageComponentInteraction;
+    type Payload = ComponentPayload;
+}
+
+impl ComponentId for Component {
+    type Key = ComponentKey;
+    type Payload = ComponentPayload;
+
+    fn from_parts(payload: ComponentPayload) -> Self {
+        Self {
+            payload: Some(payload),
+        }
+    }
+
+    fn try_into_parts(self) -> Option<ComponentPayload> { self.payload }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Modal {
+    #[prost(oneof = "ModalPayload", tags = "1")]
+    pub payload: Option<ModalPayload>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum ModalPayload {
+    #[prost(uint32, tag = "1")]
+    A(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ModalKey {
+    A,
+}
+
+impl From<&ModalPayload> for ModalKey {
+    fn from(payload: &ModalPayload) -> Self {
+        match payload {
+            ModalPayload::A(_) => Self::A,
+        }
+    }
+}
+
+impl rpc::Key for ModalKey {
+    type Interaction = ModalSubmitInteraction;
+    type Payload = ModalPayload;
+}
+
+impl ModalId for Modal {
+    type Key = ModalKey;
+    type Payload = ModalPayload;
+
+    fn from_parts(_: ModalSource, payload: ModalPayload) -> Self {
+        Self {
+            payload: Some(payload),
+        }
+    }
+
+    fn try_into_parts(self) -> Option<(ModalSource, ModalPayload)> {
+        self.payload.map(|p| (ModalSource::Component, p))
+    }
+}
+
+impl rpc::Schema for Schema {
+    type Component = Component;
+    type ComponentKey = ComponentKey;
+    type ComponentPayload = ComponentPayload;
+    type Modal = Modal;
+    type ModalKey = ModalKey;
+    type ModalPayload = ModalPayload;
+}
+
+/// Build a context with an empty cache, a disconnected shard and an HTTP
+/// client with no token
+pub fn context() -> Context {
+    let (tx, _) = serenity::futures::channel::mpsc::unbounded();
+
+    Context {
+        data: Arc::default(),
+        shard: ShardMessenger::new(tx),
+        shard_id: 0,
+        http: Arc::new(Http::new("")),
+        cache: Arc::default(),
+    }
+}
+
+fn user() -> Value {
+    json!({
+        "id": "3",
+        "username": "user",
+        "discriminator": "0000",
+        "avatar": null,
+    })
+}
+
+/// Build a slash command interaction for the command `name` with the given
+/// options
+pub fn command(name: &str, options: &Value) -> ApplicationCommandInteraction {
+    serde_json::from_value(json!({
+        "id": "1",
+        "application_id": "2",
+        "type": 2,
+        "data": {
+            "id": "4",
+            "name": name,
+            "type": 1,
+            "options": options,
+        },
+        "channel_id": "5",
+        "user": user(),
+        "token": "token",
+        "version": 1,
+        "locale": "en-US",
+    }))
+    .unwrap()
+}
+
+/// Build a button interaction with the given custom ID
+pub fn component(custom_id: &str) -> MessageComponentInteraction {
+    serde_json::from_value(json!({
+        "id": "1",
+        "application_id": "2",
+        "type": 3,
+        "data": {
+            "custom_id": custom_id,
+            "component_type": 2,
+        },
+        "message": {
+            "id": "6",
+            "channel_id": "5",
+            "author": user(),
+            "content": "",
+            "timestamp": "2023-01-01T00:00:00Z",
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "embeds": [],
+            "pinned": false,
+            "type": 0,
+        },
+        "channel_id": "5",
+        "user": user(),
+        "token": "token",
+        "version": 1,
+        "locale": "en-US",
+    }))
+    .unwrap()
+}
@@ -58,6 +58,7 @@ enum VisitorState<'a> {
 #[derive(Debug)]
 pub struct CommandVisitor<'a, I> {
     base: BasicVisitor<'a, I>,
+    namespaced: bool,
     state: VisitorState<'a>,
 }
 
@@ -65,10 +66,23 @@ impl<'a, I> CommandVisitor<'a, I> {
     /// Wrap a reference to an interaction in a new visitor
     pub fn new(int: &'a I) -> Self {
         Self {
-            base: BasicVisitor { int },
+            base: BasicVisitor::new(int),
+            namespaced: false,
             state: VisitorState::Init,
         }
     }
+
+    /// Wrap a reference to an interaction for a command registered under a
+    /// namespace, hiding the namespace subcommand from extractors
+    pub(in super::super) fn new_namespaced(int: &'a I) -> Self {
+        Self {
+            namespaced: true,
+            ..Self::new(int)
+        }
+    }
+
+    /// Returns true if this visitor hides a namespace subcommand
+    pub(in super::super) fn is_namespaced(&self) -> bool { self.namespaced }
 }
 
 impl<'a, I> std::ops::Deref for CommandVisitor<'a, I> {
@@ -170,6 +184,14 @@ impl<'a, I: super::private::Interaction<Data = CommandData>> CommandVisitor<'a,
             opts = opt.options.iter().enumerate().peekable();
         }
 
+        if self.namespaced {
+            if subcmd.is_empty() {
+                return Err(Error::Malformed("Missing namespace subcommand"));
+            }
+
+            subcmd.remove(0);
+        }
+
         let map = opts
             .map(|(_, o)| {
                 if matches!(
@@ -221,21 +243,22 @@ impl<'a, I: super::private::Interaction<Data = CommandData>> CommandVisitor<'a,
     }
 
     pub(in super::super) fn finish(self) -> Result<()> {
-        let Self { base, state } = self;
+        let Self {
+            base,
+            namespaced,
+            state,
+        } = self;
 
         match state {
             VisitorState::Init => {
-                if base.int.data().kind == CommandType::ChatInput
-                    && !base.int.data().options.is_empty()
-                {
-                    return Err(Error::Trailing(
-                        base.int
-                            .data()
-                            .options
-                            .iter()
-                            .map(|o| o.name.clone())
-                            .collect(),
-                    ));
+                let opts = if namespaced {
+                    base.int.data().options.first().map_or(&[][..], |o| &o.options)
+                } else {
+                    &base.int.data().options
+                };
+
+                if base.int.data().kind == CommandType::ChatInput && !opts.is_empty() {
+                    return Err(Error::Trailing(opts.iter().map(|o| o.name.clone()).collect()));
                 }
             },
             VisitorState::SlashCommand(m) => {
@@ -1,6 +1,7 @@
 //! Types for extracting data from interaction invocations in a type-safe manner
 
 mod command;
+mod resolve;
 
 mod private {
     use serenity::model::{
@@ -18,6 +19,8 @@ mod private {
         fn member(&self) -> &Option<guild::Member>;
 
         fn user(&self) -> &user::User;
+
+        fn channel_id(&self) -> id::ChannelId;
     }
 
     impl Interaction for application_command::ApplicationCommandInteraction {
@@ -34,6 +37,9 @@ mod private {
 
         #[inline]
         fn user(&self) -> &user::User { &self.user }
+
+        #[inline]
+        fn channel_id(&self) -> id::ChannelId { self.channel_id }
     }
 
     impl Interaction for message_component::MessageComponentInteraction {
@@ -50,6 +56,9 @@ mod private {
 
         #[inline]
         fn user(&self) -> &user::User { &self.user }
+
+        #[inline]
+        fn channel_id(&self) -> id::ChannelId { self.channel_id }
     }
 
     impl Interaction for autocomplete::AutocompleteInteraction {
@@ -66,6 +75,9 @@ mod private {
 
         #[inline]
         fn user(&self) -> &user::User { &self.user }
+
+        #[inline]
+        fn channel_id(&self) -> id::ChannelId { self.channel_id }
     }
 
     impl Interaction for modal::ModalSubmitInteraction {
@@ -82,14 +94,24 @@ mod private {
 
         #[inline]
         fn user(&self) -> &user::User { &self.user }
+
+        #[inline]
+        fn channel_id(&self) -> id::ChannelId { self.channel_id }
     }
 }
 
 use std::fmt;
 
 pub use command::CommandVisitor;
+pub use resolve::Resolver;
 use serenity::model::{
-    application::command::CommandOptionType, guild::Member, id::GuildId, user::User,
+    application::{
+        command::CommandOptionType, interaction::message_component::MessageComponentInteraction,
+    },
+    channel::Message,
+    guild::Member,
+    id::{ChannelId, GuildId},
+    user::User,
 };
 
 /// An error caused by performing an invalid extraction
@@ -159,6 +181,22 @@ type Result<T> = std::result::Result<T, Error>;
 pub struct BasicVisitor<'a, I> {
     // TODO: make this private once dedicated interaction visitors are done
     pub(crate) int: &'a I,
+    resolver: Resolver,
+}
+
+impl<'a, I> BasicVisitor<'a, I> {
+    /// Wrap a reference to an interaction in a new visitor
+    pub(crate) fn new(int: &'a I) -> Self {
+        Self {
+            int,
+            resolver: Resolver::default(),
+        }
+    }
+
+    /// Get the lookup cache for guild entities referenced by this interaction
+    #[inline]
+    #[must_use]
+    pub fn resolver(&self) -> &Resolver { &self.resolver }
 }
 
 impl<'a, I: private::Interaction> BasicVisitor<'a, I> {
@@ -167,7 +205,7 @@ impl<'a, I: private::Interaction> BasicVisitor<'a, I> {
     /// # Errors
     /// This method returns an error if the input data is non-conformant.
     #[inline]
-    pub fn guild(&self) -> Result<GuildVisitor> {
+    pub fn guild(&self) -> Result<GuildVisitor<'a>> {
         if self.int.guild_id().is_some() != self.int.member().is_some() {
             return Err(Error::Malformed(
                 "Guild ID and member info presence desynced",
@@ -179,10 +217,70 @@ impl<'a, I: private::Interaction> BasicVisitor<'a, I> {
         ))
     }
 
+    /// Visit the source guild ID and invoking member information for this
+    /// interaction, which must have been invoked inside a guild
+    ///
+    /// Shorthand for [`guild`](Self::guild) followed by
+    /// [`GuildVisitor::required`].
+    ///
+    /// # Errors
+    /// This method returns an error if the input data is non-conformant or
+    /// the interaction was invoked outside a guild.
+    #[inline]
+    pub fn member(&self) -> Result<(GuildId, &'a Member)> { self.guild()?.required() }
+
     /// Visit the invoking user information for this interaction
     #[inline]
     #[must_use]
     pub fn user(&self) -> &'a User { self.int.user() }
+
+    /// Visit the invoking user and, if the interaction was invoked inside a
+    /// guild, their member information
+    ///
+    /// # Errors
+    /// This method returns an error if the input data is non-conformant.
+    #[inline]
+    pub fn invoker(&self) -> Result<Invoker<'a>> {
+        Ok(Invoker {
+            user: self.user(),
+            guild: self.guild()?.optional(),
+        })
+    }
+
+    /// Visit the ID of the channel this interaction was invoked from
+    #[inline]
+    #[must_use]
+    pub fn channel(&self) -> ChannelId { self.int.channel_id() }
+}
+
+impl<'a> BasicVisitor<'a, MessageComponentInteraction> {
+    /// Visit the message the interacted component is attached to
+    #[inline]
+    #[must_use]
+    pub fn message(&self) -> &'a Message { &self.int.message }
+}
+
+/// The user who invoked an interaction
+#[derive(Debug, Clone, Copy)]
+pub struct Invoker<'a> {
+    /// The invoking user
+    pub user: &'a User,
+    /// The source guild ID and the user's member information, if the
+    /// interaction was invoked inside a guild
+    pub guild: Option<(GuildId, &'a Member)>,
+}
+
+impl<'a> Invoker<'a> {
+    /// Get the ID of the guild the interaction was invoked in, if any
+    #[inline]
+    #[must_use]
+    pub fn guild_id(&self) -> Option<GuildId> { self.guild.map(|(g, _)| g) }
+
+    /// Get the invoking user's member information, if the interaction was
+    /// invoked inside a guild
+    #[inline]
+    #[must_use]
+    pub fn member(&self) -> Option<&'a Member> { self.guild.map(|(_, m)| m) }
 }
 
 /// Visitor for the source guild of an interaction
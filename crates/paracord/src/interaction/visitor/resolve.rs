@@ -0,0 +1,115 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serenity::{
+    client::Context,
+    model::{
+        channel::GuildChannel,
+        guild::{Member, Role},
+        id::{ChannelId, GuildId, RoleId, UserId},
+    },
+};
+use tokio::sync::Mutex;
+
+/// Memoized lookups of the guild entities referenced while handling a single
+/// interaction
+///
+/// Each lookup checks serenity's cache before falling back to the HTTP API,
+/// and successful results are remembered for the rest of the dispatch, so
+/// handlers can resolve the same member, channel or role from several code
+/// paths without repeating requests.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    members: Mutex<HashMap<(GuildId, UserId), Member>>,
+    channels: Mutex<HashMap<GuildId, Arc<HashMap<ChannelId, GuildChannel>>>>,
+    roles: Mutex<HashMap<GuildId, Arc<HashMap<RoleId, Role>>>>,
+}
+
+impl Resolver {
+    /// Look up a member of a guild
+    ///
+    /// # Errors
+    /// This method returns an error if the member is not cached and an API
+    /// error is received.
+    pub async fn member(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        user: UserId,
+    ) -> Result<Member, serenity::Error> {
+        let mut members = self.members.lock().await;
+        if let Some(memb) = members.get(&(gid, user)) {
+            return Ok(memb.clone());
+        }
+
+        let memb = match ctx.cache.member(gid, user) {
+            Some(m) => m,
+            None => gid.member(ctx, user).await?,
+        };
+        members.insert((gid, user), memb.clone());
+
+        Ok(memb)
+    }
+
+    /// Look up every channel in a guild
+    ///
+    /// # Errors
+    /// This method returns an error if the guild is not cached and an API
+    /// error is received.
+    pub async fn channels(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+    ) -> Result<Arc<HashMap<ChannelId, GuildChannel>>, serenity::Error> {
+        let mut channels = self.channels.lock().await;
+        if let Some(chans) = channels.get(&gid) {
+            return Ok(Arc::clone(chans));
+        }
+
+        let chans = Arc::new(match ctx.cache.guild_channels(gid) {
+            Some(c) => c.into_iter().collect(),
+            None => gid.channels(&ctx.http).await?,
+        });
+        channels.insert(gid, Arc::clone(&chans));
+
+        Ok(chans)
+    }
+
+    /// Look up a single channel in a guild, returning `None` if it does not
+    /// exist
+    ///
+    /// # Errors
+    /// This method returns an error if the guild is not cached and an API
+    /// error is received.
+    pub async fn channel(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        id: ChannelId,
+    ) -> Result<Option<GuildChannel>, serenity::Error> {
+        Ok(self.channels(ctx, gid).await?.get(&id).cloned())
+    }
+
+    /// Look up every role in a guild
+    ///
+    /// # Errors
+    /// This method returns an error if the guild is not cached and an API
+    /// error is received.
+    pub async fn roles(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+    ) -> Result<Arc<HashMap<RoleId, Role>>, serenity::Error> {
+        let mut roles = self.roles.lock().await;
+        if let Some(r) = roles.get(&gid) {
+            return Ok(Arc::clone(r));
+        }
+
+        let r = Arc::new(match ctx.cache.guild_roles(gid) {
+            Some(r) => r,
+            None => gid.roles(&ctx.http).await?,
+        });
+        roles.insert(gid, Arc::clone(&r));
+
+        Ok(r)
+    }
+}
@@ -11,4 +11,9 @@
 #![warn(clippy::pedantic, missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 
+// Allow CommandArgs derives to be tested from within this crate
+#[cfg(test)]
+extern crate self as paracord;
+
 pub mod interaction;
+pub mod markup;
@@ -0,0 +1,41 @@
+//! Extensions to [`MessageBuilder`] for Discord-specific markup
+
+use qcore::markup::{MaskedLink, Timestamp, TimestampStyle};
+use serenity::{
+    model::{channel::GuildChannel, guild::Role, user::User},
+    utils::MessageBuilder,
+};
+
+/// Extension methods for [`MessageBuilder`]
+pub trait MessageBuilderExt {
+    /// Push a Unix timestamp, displayed in the reader's time zone
+    fn timestamp(&mut self, secs: i64, style: TimestampStyle) -> &mut Self;
+
+    /// Push a user's name, without mentioning them
+    fn user_name(&mut self, user: &User) -> &mut Self;
+
+    /// Push a role's name, without mentioning it
+    fn role_name(&mut self, role: &Role) -> &mut Self;
+
+    /// Push a channel's name, without linking to it
+    fn channel_name(&mut self, chan: &GuildChannel) -> &mut Self;
+
+    /// Push a link displaying text in place of its URL
+    fn masked_link(&mut self, link: MaskedLink) -> &mut Self;
+}
+
+impl MessageBuilderExt for MessageBuilder {
+    fn timestamp(&mut self, secs: i64, style: TimestampStyle) -> &mut Self {
+        self.push(Timestamp { secs, style }.to_string())
+    }
+
+    fn user_name(&mut self, user: &User) -> &mut Self { self.push_safe(format!("@{}", user.tag())) }
+
+    fn role_name(&mut self, role: &Role) -> &mut Self { self.push_safe(format!("@{}", role.name)) }
+
+    fn channel_name(&mut self, chan: &GuildChannel) -> &mut Self {
+        self.push_safe(format!("#{}", chan.name))
+    }
+
+    fn masked_link(&mut self, link: MaskedLink) -> &mut Self { self.push(link.to_string()) }
+}
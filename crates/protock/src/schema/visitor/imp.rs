@@ -93,7 +93,7 @@ impl<'a> Visitor<'a> {
         assert!(source_code_info.is_none());
         assert_eq!(syntax.as_deref(), Some("proto3"));
 
-        let (optimize, deprecated) = if let Some(opts) = options {
+        let (_optimize, _deprecated) = if let Some(opts) = options {
             #[allow(deprecated)] // explicitly ignoring java_generate_equals_and_hash
             let FileOptions {
                 java_package: _,
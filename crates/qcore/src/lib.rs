@@ -12,5 +12,7 @@
 #![allow(clippy::module_name_repetitions)]
 
 pub mod build_range;
+pub mod markup;
+pub mod rng;
 
 pub use qmacros::*;
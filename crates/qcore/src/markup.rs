@@ -0,0 +1,156 @@
+//! Helpers for formatting Discord message markup
+
+use std::fmt;
+
+/// The display style of a Discord timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TimestampStyle {
+    /// A time of day, e.g. `16:20`
+    ShortTime,
+    /// A time of day with seconds, e.g. `16:20:30`
+    LongTime,
+    /// A numeric date, e.g. `20/04/2021`
+    ShortDate,
+    /// A date with the month spelled out, e.g. `20 April 2021`
+    LongDate,
+    /// A date and time, e.g. `20 April 2021 16:20`
+    #[default]
+    ShortDateTime,
+    /// A date and time with the weekday, e.g. `Tuesday, 20 April 2021 16:20`
+    LongDateTime,
+    /// A duration relative to now, e.g. `2 months ago`
+    Relative,
+}
+
+impl TimestampStyle {
+    /// Get the format flag Discord uses for this style
+    #[must_use]
+    pub fn flag(self) -> char {
+        match self {
+            Self::ShortTime => 't',
+            Self::LongTime => 'T',
+            Self::ShortDate => 'd',
+            Self::LongDate => 'D',
+            Self::ShortDateTime => 'f',
+            Self::LongDateTime => 'F',
+            Self::Relative => 'R',
+        }
+    }
+}
+
+/// A Unix timestamp, displayed by Discord in the reader's time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    /// Seconds since the Unix epoch
+    pub secs: i64,
+    /// How the timestamp should be displayed
+    pub style: TimestampStyle,
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<t:{}:{}>", self.secs, self.style.flag())
+    }
+}
+
+/// An error arising from constructing an invalid [`MaskedLink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkError {
+    /// The link text was empty
+    EmptyText,
+    /// The link text contained brackets or line breaks
+    TextMarkup,
+    /// The link text looked like a URL, which Discord refuses to mask
+    TextUrl,
+    /// The URL did not use the HTTP or HTTPS scheme
+    Scheme,
+    /// The URL contained whitespace, parentheses or angle brackets
+    UrlMarkup,
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::EmptyText => "Link text is empty",
+            Self::TextMarkup => "Link text contains brackets or line breaks",
+            Self::TextUrl => "Link text looks like a URL",
+            Self::Scheme => "Link URL is not HTTP or HTTPS",
+            Self::UrlMarkup => "Link URL contains whitespace, parentheses or angle brackets",
+        })
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+/// A Markdown link displaying text in place of its URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaskedLink<'a> {
+    text: &'a str,
+    url: &'a str,
+}
+
+impl<'a> MaskedLink<'a> {
+    /// Construct a new masked link, checking that Discord will render it
+    ///
+    /// # Errors
+    /// This method returns an error if the text or URL would break the link
+    /// markup or be rejected by Discord.
+    pub fn new(text: &'a str, url: &'a str) -> Result<Self, LinkError> {
+        if text.trim().is_empty() {
+            return Err(LinkError::EmptyText);
+        }
+
+        if text.contains(['[', ']', '\n', '\r']) {
+            return Err(LinkError::TextMarkup);
+        }
+
+        if text.contains("://") {
+            return Err(LinkError::TextUrl);
+        }
+
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(LinkError::Scheme);
+        }
+
+        if url.contains(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '<' | '>')) {
+            return Err(LinkError::UrlMarkup);
+        }
+
+        Ok(Self { text, url })
+    }
+}
+
+impl<'a> fmt::Display for MaskedLink<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]({})", self.text, self.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LinkError, MaskedLink, Timestamp, TimestampStyle};
+
+    #[test]
+    fn test_markup() {
+        let ts = Timestamp {
+            secs: 1_618_953_630,
+            style: TimestampStyle::Relative,
+        };
+        assert_eq!(ts.to_string(), "<t:1618953630:R>");
+
+        assert_eq!(
+            MaskedLink::new("docs", "https://example.com/a_(b)"),
+            Err(LinkError::UrlMarkup)
+        );
+        assert_eq!(
+            MaskedLink::new("https://evil.com", "https://example.com"),
+            Err(LinkError::TextUrl)
+        );
+        assert_eq!(
+            MaskedLink::new("docs", "https://example.com")
+                .unwrap()
+                .to_string(),
+            "[docs](https://example.com)"
+        );
+    }
+}
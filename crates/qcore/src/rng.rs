@@ -0,0 +1,206 @@
+//! Seedable random number generation with auditable results
+//!
+//! Before a draw, a random [`Seed`] is generated and its [`Commitment`] is
+//! published.  Once the draw is made the seed is revealed, and anyone can
+//! check it against the commitment and replay the draw with [`Rng`] to confirm
+//! the result wasn't tampered with.  Draws are reproducible across platforms
+//! and releases, as they rely only on the `ChaCha20` cipher and the sampling
+//! algorithms in this module.
+
+use std::{fmt, str::FromStr};
+
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha20Rng,
+};
+use sha2::{Digest, Sha256};
+
+const SEED_LEN: usize = 32;
+const COMMITMENT_LEN: usize = 32;
+
+/// An error arising from parsing a hexadecimal [`Seed`] or [`Commitment`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseError {
+    /// The input was not the expected number of digits
+    Length(usize),
+    /// The input contained a non-hexadecimal character
+    Digit(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Length(n) => write!(f, "Expected {n} hexadecimal digits"),
+            Self::Digit(c) => write!(f, "Invalid hexadecimal digit {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|b| write!(f, "{b:02x}"))
+}
+
+fn parse_hex<const N: usize>(s: &str) -> Result<[u8; N], ParseError> {
+    let s = s.trim();
+    if s.len() != N * 2 {
+        return Err(ParseError::Length(N * 2));
+    }
+
+    let mut digits = s.chars().map(|c| {
+        c.to_digit(16)
+            .and_then(|d| u8::try_from(d).ok())
+            .ok_or(ParseError::Digit(c))
+    });
+    let mut bytes = [0; N];
+    for b in &mut bytes {
+        let hi = digits.next().unwrap_or_else(|| unreachable!())?;
+        let lo = digits.next().unwrap_or_else(|| unreachable!())?;
+        *b = hi << 4 | lo;
+    }
+
+    Ok(bytes)
+}
+
+/// The secret input to a random draw
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed([u8; SEED_LEN]);
+
+impl Seed {
+    /// Generate a new seed from the operating system's random source
+    ///
+    /// # Errors
+    /// This method returns an error if the operating system's random source is
+    /// unavailable.
+    pub fn generate() -> Result<Self, getrandom::Error> {
+        let mut bytes = [0; SEED_LEN];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+
+    /// Compute the commitment to publish for this seed before it is used
+    #[must_use]
+    pub fn commitment(&self) -> Commitment { Commitment(Sha256::digest(self.0).into()) }
+}
+
+impl fmt::Debug for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't leak unrevealed seeds into logs
+        f.debug_tuple("Seed").field(&self.commitment()).finish()
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write_hex(f, &self.0) }
+}
+
+impl FromStr for Seed {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> { parse_hex(s).map(Self) }
+}
+
+/// A published SHA-256 hash of a [`Seed`], which can be checked once the seed
+/// is revealed
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commitment([u8; COMMITMENT_LEN]);
+
+impl Commitment {
+    /// Check whether the given seed matches this commitment
+    #[must_use]
+    pub fn verify(&self, seed: &Seed) -> bool { seed.commitment() == *self }
+}
+
+impl fmt::Debug for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Commitment(")?;
+        write_hex(f, &self.0)?;
+        f.write_str(")")
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write_hex(f, &self.0) }
+}
+
+impl FromStr for Commitment {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> { parse_hex(s).map(Self) }
+}
+
+/// A deterministic random number generator for replayable draws
+///
+/// Each seed provides 2<sup>64</sup> independent streams, so the same seed can
+/// be used for separate draws (e.g. one per guild) without correlating them.
+#[derive(Debug, Clone)]
+pub struct Rng(ChaCha20Rng);
+
+impl Rng {
+    /// Construct a generator for the given seed and stream
+    #[must_use]
+    pub fn new(seed: &Seed, stream: u64) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(seed.0);
+        rng.set_stream(stream);
+        Self(rng)
+    }
+
+    /// Get a uniformly distributed integer in the range `0..n`
+    ///
+    /// # Panics
+    /// This method panics if `n` is zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "Cannot sample from an empty range");
+
+        // Reject values from the final partial multiple of n to avoid bias
+        let zone = u64::MAX - (u64::MAX - n + 1) % n;
+        loop {
+            let x = self.0.next_u64();
+            if x <= zone {
+                break x % n;
+            }
+        }
+    }
+
+    /// Choose `amount` distinct indices into a list of length `len`, in the
+    /// order they were drawn
+    ///
+    /// If `amount` is greater than `len`, every index is returned.
+    pub fn sample(&mut self, len: usize, amount: usize) -> Vec<usize> {
+        let mut idxs: Vec<_> = (0..len).collect();
+        let amount = amount.min(len);
+
+        for i in 0..amount {
+            let rest = (len - i) as u64;
+            #[allow(clippy::cast_possible_truncation)] // Less than len
+            let j = i + self.below(rest) as usize;
+            idxs.swap(i, j);
+        }
+
+        idxs.truncate(amount);
+        idxs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Commitment, Rng, Seed};
+
+    #[test]
+    fn test_rng() {
+        let seed = Seed::generate().unwrap();
+        let commit = seed.commitment();
+
+        assert_eq!(seed.to_string().parse::<Seed>(), Ok(seed));
+        assert_eq!(commit.to_string().parse::<Commitment>(), Ok(commit));
+        assert!(commit.verify(&seed));
+        assert!(!commit.verify(&Seed::generate().unwrap()));
+
+        let draw = Rng::new(&seed, 1).sample(10, 3);
+        assert_eq!(draw.len(), 3);
+        assert!(draw.iter().all(|&i| i < 10));
+        assert_eq!(Rng::new(&seed, 1).sample(10, 3), draw);
+        assert_eq!(Rng::new(&seed, 2).sample(3, 5).len(), 3);
+    }
+}
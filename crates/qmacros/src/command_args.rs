@@ -0,0 +1,424 @@
+use crate::prelude::*;
+
+pub(super) fn run(input: &syn::DeriveInput) -> TokenStream {
+    match try_run(input) {
+        Ok(t) => t,
+        Err(e) => e.into_compile_error(),
+    }
+}
+
+#[derive(Default)]
+struct Attrs {
+    rename: Option<syn::LitStr>,
+    desc: Option<syn::LitStr>,
+    min: Option<syn::Expr>,
+    max: Option<syn::Expr>,
+    choices: Option<syn::Expr>,
+    autocomplete: bool,
+    examples: Vec<syn::LitStr>,
+}
+
+impl Attrs {
+    fn parse(attrs: &[syn::Attribute], field: bool) -> syn::Result<Self> {
+        let mut ret = Self::default();
+        let mut doc = vec![];
+
+        for attr in attrs {
+            if attr.path().is_ident("doc") {
+                if let syn::Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(ref s),
+                            ..
+                        }),
+                    ..
+                }) = attr.meta
+                {
+                    doc.push((s.value().trim().to_owned(), s.span()));
+                }
+                continue;
+            }
+
+            if !attr.path().is_ident("arg") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    ret.rename = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("desc") {
+                    ret.desc = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+
+                if !field && meta.path.is_ident("example") {
+                    ret.examples.push(meta.value()?.parse()?);
+                    return Ok(());
+                }
+
+                if field {
+                    if meta.path.is_ident("min") {
+                        ret.min = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("max") {
+                        ret.max = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("choices") {
+                        ret.choices = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("autocomplete") {
+                        ret.autocomplete = true;
+                        return Ok(());
+                    }
+                }
+
+                Err(meta.error("Invalid #[arg] attribute"))
+            })?;
+        }
+
+        if ret.desc.is_none() && !doc.is_empty() {
+            let span = doc[0].1;
+            let desc: Vec<_> = doc
+                .into_iter()
+                .map(|(s, _)| s)
+                .filter(|s| !s.is_empty())
+                .collect();
+            ret.desc = Some(syn::LitStr::new(&desc.join(" "), span));
+        }
+
+        Ok(ret)
+    }
+
+    fn name(&self, ident: &syn::Ident, snake: bool) -> syn::LitStr {
+        self.rename.clone().unwrap_or_else(|| {
+            let name = ident.to_string();
+            let name = name.strip_prefix("r#").unwrap_or(&name);
+            let name = if snake {
+                snake_case(name)
+            } else {
+                name.to_owned()
+            };
+            syn::LitStr::new(&name, ident.span())
+        })
+    }
+
+    fn examples(&self) -> TokenStream {
+        self.examples
+            .iter()
+            .map(|e| {
+                quote_spanned! { e.span() =>
+                    let builder =
+                        ::paracord::interaction::command::ArgBuilderExt::example(builder, #e);
+                }
+            })
+            .collect()
+    }
+
+    fn desc(&self, span: Span) -> syn::Result<&syn::LitStr> {
+        self.desc.as_ref().ok_or_else(|| {
+            span.error("Missing description; add a doc comment or #[arg(desc = \"...\")]")
+        })
+    }
+}
+
+fn snake_case(s: &str) -> String {
+    let mut ret = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                ret.push('_');
+            }
+            ret.extend(c.to_lowercase());
+        } else {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
+fn opt(e: Option<&syn::Expr>, f: impl FnOnce(&syn::Expr) -> TokenStream) -> TokenStream {
+    e.map_or_else(
+        || quote_spanned! { Span::call_site() => ::std::option::Option::None },
+        |e| {
+            let e = f(e);
+            quote_spanned! { e.span() => ::std::option::Option::Some(#e) }
+        },
+    )
+}
+
+struct Fields {
+    register: TokenStream,
+    construct: TokenStream,
+    visited: TokenStream,
+}
+
+fn named_fields(fields: &syn::FieldsNamed, lt: &syn::Lifetime) -> syn::Result<Fields> {
+    let mut register = TokenStream::new();
+    let mut construct = TokenStream::new();
+    let mut visited = TokenStream::new();
+
+    for field in &fields.named {
+        let span = field.span();
+        let ident = field.ident.as_ref().unwrap_or_else(|| unreachable!());
+        let ty = &field.ty;
+        let attrs = Attrs::parse(&field.attrs, true)?;
+        let name = attrs.name(ident, false);
+        let desc = attrs.desc(span)?;
+        let min = opt(attrs.min.as_ref(), ToTokens::to_token_stream);
+        let max = opt(attrs.max.as_ref(), ToTokens::to_token_stream);
+        let choices = opt(attrs.choices.as_ref(), |c| {
+            quote_spanned! { c.span() =>
+                ::std::iter::Iterator::collect(::std::iter::Iterator::map(
+                    ::std::iter::IntoIterator::into_iter(#c),
+                    ::std::convert::Into::into,
+                ))
+            }
+        });
+        let autocomplete = attrs.autocomplete;
+
+        register.extend(quote_spanned! { span =>
+            let builder = <#ty as ::paracord::interaction::args::ArgValue<#lt>>::register(
+                builder,
+                ::paracord::interaction::args::ArgSpec {
+                    name: #name,
+                    desc: #desc,
+                    required: true,
+                    min: #min,
+                    max: #max,
+                    choices: #choices,
+                    autocomplete: #autocomplete,
+                },
+            );
+        });
+
+        construct.extend(quote_spanned! { span =>
+            #ident: ::paracord::interaction::args::visit_arg::<#ty>(visitor, #name)?,
+        });
+
+        visited.extend(quote_spanned! { span =>
+            ::paracord::interaction::args::VisitedArg {
+                name: #name,
+                kind: <#ty as ::paracord::interaction::args::ArgValue<#lt>>::KIND,
+                required: <#ty as ::paracord::interaction::args::ArgValue<#lt>>::REQUIRED,
+            },
+        });
+    }
+
+    Ok(Fields {
+        register,
+        construct,
+        visited,
+    })
+}
+
+fn leaf(span: Span, visited: &TokenStream) -> TokenStream {
+    quote_spanned! { span =>
+        ::paracord::interaction::args::Visited::Leaf(::std::vec![#visited])
+    }
+}
+
+type Impl = (TokenStream, TokenStream, TokenStream, TokenStream);
+
+fn struct_impl(
+    span: Span,
+    attrs: &[syn::Attribute],
+    s: &syn::DataStruct,
+    lt: &syn::Lifetime,
+) -> syn::Result<Impl> {
+    let examples = Attrs::parse(attrs, false)?.examples();
+    let Fields {
+        register,
+        construct,
+        visited,
+    } = match s.fields {
+        syn::Fields::Named(ref f) => named_fields(f, lt)?,
+        syn::Fields::Unit => Fields {
+            register: TokenStream::new(),
+            construct: TokenStream::new(),
+            visited: TokenStream::new(),
+        },
+        syn::Fields::Unnamed(_) => {
+            return Err(span.error("Cannot derive CommandArgs on a tuple struct"));
+        },
+    };
+
+    Ok((
+        quote_spanned! { span => #register #examples },
+        quote_spanned! { span => Self::from_subcmd(visitor, &[]) },
+        quote_spanned! { span => ::std::result::Result::Ok(Self { #construct }) },
+        leaf(span, &visited),
+    ))
+}
+
+fn enum_impl(span: Span, e: &syn::DataEnum, lt: &syn::Lifetime) -> syn::Result<Impl> {
+    let mut register = TokenStream::new();
+    let mut arms = TokenStream::new();
+    let mut visited = TokenStream::new();
+
+    for var in &e.variants {
+        let var_span = var.span();
+        let var_ident = &var.ident;
+        let attrs = Attrs::parse(&var.attrs, false)?;
+        let name = attrs.name(var_ident, true);
+        let desc = attrs.desc(var_span)?;
+        let examples = attrs.examples();
+
+        let (f, construct, sub) = match var.fields {
+            syn::Fields::Unit => (
+                quote_spanned! { var_span => |builder| { #examples builder } },
+                quote_spanned! { var_span => Self::#var_ident },
+                leaf(var_span, &TokenStream::new()),
+            ),
+            syn::Fields::Named(ref f) => {
+                let Fields {
+                    register,
+                    construct,
+                    visited,
+                } = named_fields(f, lt)?;
+                (
+                    quote_spanned! { var_span => |builder| { #register #examples builder } },
+                    quote_spanned! { var_span => Self::#var_ident { #construct } },
+                    leaf(var_span, &visited),
+                )
+            },
+            syn::Fields::Unnamed(ref f) if f.unnamed.len() == 1 => {
+                let ty = &f.unnamed[0].ty;
+                (
+                    quote_spanned! { var_span =>
+                        |builder| {
+                            let builder =
+                                <#ty as ::paracord::interaction::args::CommandArgs<#lt>>
+                                    ::register(builder);
+                            #examples
+                            builder
+                        }
+                    },
+                    quote_spanned! { var_span =>
+                        Self::#var_ident(
+                            <#ty as ::paracord::interaction::args::CommandArgs<#lt>>
+                                ::from_subcmd(visitor, rest)?,
+                        )
+                    },
+                    quote_spanned! { var_span =>
+                        <#ty as ::paracord::interaction::args::CommandArgs<#lt>>::visited()
+                    },
+                )
+            },
+            syn::Fields::Unnamed(_) => {
+                return Err(var_span.error("Subcommand variants may wrap at most one type"));
+            },
+        };
+
+        register.extend(quote_spanned! { var_span =>
+            let builder = ::paracord::interaction::command::ArgBuilderExt::build_subcmd(
+                builder,
+                #name,
+                #desc,
+                #f,
+            );
+        });
+
+        visited.extend(quote_spanned! { var_span => (#name, #sub), });
+
+        arms.extend(quote_spanned! { var_span =>
+            ::std::option::Option::Some((&#name, rest)) => {
+                let _ = rest;
+                ::std::result::Result::Ok(#construct)
+            },
+        });
+    }
+
+    Ok((
+        register,
+        quote_spanned! { span =>
+            let path = visitor.visit_subcmd()?;
+            Self::from_subcmd(visitor, &path)
+        },
+        quote_spanned! { span =>
+            match path.split_first() {
+                #arms
+                _ => ::std::result::Result::Err(
+                    ::paracord::interaction::visitor::Error::UnhandledSubcommand(
+                        path.iter().map(|s| (*s).to_owned()).collect(),
+                    ),
+                ),
+            }
+        },
+        quote_spanned! { span =>
+            ::paracord::interaction::args::Visited::Branch(::std::vec![#visited])
+        },
+    ))
+}
+
+fn try_run(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let span = input.span();
+    let ident = &input.ident;
+
+    let mut lifetimes = input.generics.lifetimes();
+    let (lt, impl_generics) = match (lifetimes.next(), lifetimes.next()) {
+        (None, _) => {
+            let lt = syn::Lifetime::new("'__args", Span::call_site());
+            let mut generics = input.generics.clone();
+            generics.params.insert(
+                0,
+                syn::GenericParam::Lifetime(syn::LifetimeParam::new(lt.clone())),
+            );
+            (lt, generics)
+        },
+        (Some(l), None) => (l.lifetime.clone(), input.generics.clone()),
+        (Some(_), Some(l)) => {
+            return Err(l
+                .span()
+                .error("CommandArgs types may have at most one lifetime parameter"));
+        },
+    };
+    let (impl_gen, _, _) = impl_generics.split_for_impl();
+    let (_, ty_gen, where_toks) = input.generics.split_for_impl();
+
+    let (register, from_visitor, from_subcmd, visited) = match input.data {
+        syn::Data::Struct(ref s) => struct_impl(span, &input.attrs, s, &lt)?,
+        syn::Data::Enum(ref e) => enum_impl(span, e, &lt)?,
+        syn::Data::Union(_) => return Err(span.error("Cannot derive CommandArgs on a union")),
+    };
+
+    Ok(quote_spanned! { span =>
+        impl #impl_gen ::paracord::interaction::args::CommandArgs<#lt> for #ident #ty_gen
+        #where_toks
+        {
+            #[allow(clippy::let_and_return)]
+            fn register(
+                builder: ::paracord::interaction::command::ArgBuilder,
+            ) -> ::paracord::interaction::command::ArgBuilder {
+                #register
+                builder
+            }
+
+            fn from_visitor(
+                visitor: &mut ::paracord::interaction::handler::CommandVisitor<#lt>,
+            ) -> ::std::result::Result<Self, ::paracord::interaction::visitor::Error> {
+                #from_visitor
+            }
+
+            #[allow(unused_variables)]
+            fn from_subcmd(
+                visitor: &mut ::paracord::interaction::handler::CommandVisitor<#lt>,
+                path: &[&#lt str],
+            ) -> ::std::result::Result<Self, ::paracord::interaction::visitor::Error> {
+                #from_subcmd
+            }
+
+            fn visited() -> ::paracord::interaction::args::Visited {
+                #visited
+            }
+        }
+    })
+}
@@ -15,6 +15,7 @@ use proc_macro::TokenStream as TokenStream1;
 
 mod borrow;
 mod builder;
+mod command_args;
 
 pub(crate) mod prelude {
     pub use proc_macro2::{Span, TokenStream};
@@ -40,6 +41,13 @@ pub fn borrow(input: TokenStream1) -> TokenStream1 {
     borrow::run(syn::parse_macro_input!(input)).into()
 }
 
+/// Implement `paracord`'s `CommandArgs` trait, generating both parameter
+/// registration and typed extraction from a struct or enum definition
+#[proc_macro_derive(CommandArgs, attributes(arg))]
+pub fn command_args(input: TokenStream1) -> TokenStream1 {
+    command_args::run(&syn::parse_macro_input!(input)).into()
+}
+
 /// Lift an impl block for a builder struct into a helper trait
 #[proc_macro_attribute]
 pub fn builder(arg_stream: TokenStream1, body: TokenStream1) -> TokenStream1 {
@@ -15,7 +15,7 @@ use std::io::{self, Read};
 
 use shrec::{
     dfa::Scanner,
-    re::{syntax::token_dfa, Regex, RegexBag},
+    re::{Regex, RegexBag},
 };
 
 fn main() {
@@ -50,7 +50,7 @@ fn main() {
     // let dfa = token_dfa();
     let non_dfa = re.compile();
     let dfa = non_dfa.compile().copied();
-    let (dfa, states) = dfa.atomize_nodes::<u64>();
+    let (dfa, _states) = dfa.atomize_nodes::<u64>();
 
     let mut s = String::new();
     io::stdin().read_to_string(&mut s).unwrap();
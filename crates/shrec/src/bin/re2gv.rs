@@ -11,6 +11,7 @@
 #![warn(clippy::pedantic, missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 
+#[allow(unused_imports)] // used by the commented-out example below
 use shrec::re::Regex;
 
 fn main() {
@@ -0,0 +1,156 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use super::prelude::*;
+
+const TEMP_EXT: &str = "part";
+
+static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+/// A directory of cached files, trimmed back to a size limit by removing the
+/// least recently written files whenever a new one is added
+#[derive(Debug)]
+pub struct CacheDir {
+    path: PathBuf,
+    max_size: u64,
+}
+
+impl CacheDir {
+    pub fn new(path: PathBuf, max_size: u64) -> Self { Self { path, max_size } }
+
+    /// Get the path a cached file is stored under
+    #[inline]
+    pub fn entry(&self, name: &str) -> PathBuf { self.path.join(name) }
+
+    /// Create the cache directory if it doesn't exist yet
+    pub async fn create(&self) -> io::Result<()> { tokio::fs::create_dir_all(&self.path).await }
+
+    /// Get a path to write a new cache entry to before it is [inserted]
+    ///
+    /// Each call returns a different path, so concurrent writers never share a
+    /// temporary file.
+    ///
+    /// [inserted]: Self::insert
+    pub fn temp(&self) -> PathBuf {
+        self.path.join(format!(
+            "{}.{}.{TEMP_EXT}",
+            std::process::id(),
+            NEXT_TEMP.fetch_add(1, Ordering::Relaxed),
+        ))
+    }
+
+    /// Move a file written to a [temporary path] into the cache and evict old
+    /// entries if the cache is over its size limit
+    ///
+    /// [temporary path]: Self::temp
+    pub async fn insert(&self, temp: &Path, name: &str) -> io::Result<PathBuf> {
+        let path = self.entry(name);
+        tokio::fs::rename(temp, &path).await?;
+
+        self.prune(&path)
+            .await
+            .map_err(|e| warn!(dir = ?self.path, %e, "Error pruning cache"))
+            .ok();
+
+        Ok(path)
+    }
+
+    async fn prune(&self, keep: &Path) -> io::Result<()> {
+        let mut entries = vec![];
+        let mut total = 0_u64;
+        let mut dir = tokio::fs::read_dir(&self.path).await?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            // Skip in-progress writes and the entry that was just added
+            if path == keep || path.extension().map_or(false, |e| e == TEMP_EXT) {
+                continue;
+            }
+
+            let meta = entry.metadata().await?;
+            if !meta.is_file() {
+                continue;
+            }
+
+            total = total.saturating_add(meta.len());
+            entries.push((meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len(), path));
+        }
+
+        if let Ok(len) = tokio::fs::metadata(keep).await.map(|m| m.len()) {
+            total = total.saturating_add(len);
+        }
+
+        if total <= self.max_size {
+            return Ok(());
+        }
+
+        entries.sort_unstable();
+        for (_, len, path) in entries {
+            if total <= self.max_size {
+                break;
+            }
+
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => trace!(?path, "Evicted cache entry"),
+                // Another prune got to it first
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(e),
+            }
+            total = total.saturating_sub(len);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheDir, PathBuf};
+
+    fn cache_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("the-q-test-{}-{name}", std::process::id()))
+    }
+
+    async fn insert(cache: &CacheDir, name: &str, len: usize) {
+        let temp = cache.temp();
+        tokio::fs::write(&temp, vec![0; len]).await.unwrap();
+        let path = cache.insert(&temp, name).await.unwrap();
+        assert_eq!(path, cache.entry(name));
+    }
+
+    fn exists(cache: &CacheDir, name: &str) -> bool { cache.entry(name).exists() }
+
+    #[tokio::test]
+    async fn test_prune() {
+        let dir = cache_dir("cache");
+        let cache = CacheDir::new(dir.clone(), 10);
+        cache.create().await.unwrap();
+
+        // An unfinished write is neither counted nor evicted
+        let pending = cache.temp();
+        tokio::fs::write(&pending, [0; 8]).await.unwrap();
+
+        // Names sort in insertion order, in case the entries share a
+        // modification time
+        insert(&cache, "a", 4).await;
+        insert(&cache, "b", 4).await;
+        assert!(exists(&cache, "a") && exists(&cache, "b"));
+
+        insert(&cache, "c", 4).await;
+        assert!(!exists(&cache, "a"));
+        assert!(exists(&cache, "b") && exists(&cache, "c"));
+
+        // An entry over the limit by itself evicts everything else, but is
+        // kept
+        insert(&cache, "d", 12).await;
+        assert!(!exists(&cache, "b") && !exists(&cache, "c"));
+        assert!(exists(&cache, "d"));
+        assert!(pending.exists());
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+}
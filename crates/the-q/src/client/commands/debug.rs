@@ -0,0 +1,294 @@
+use std::{fmt::Write, time::Duration};
+
+use paracord::interaction::{decode_log::DecodeLog, legacy::LegacyIds};
+use serenity::{client::bridge::gateway::ShardManager, prelude::TypeMapKey};
+use tokio::sync::{Mutex, OnceCell};
+
+use super::{prelude::*, VoiceSessions};
+
+/// Discord's limit on the length of message content
+const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Gives the health view access to the client's shard manager
+pub struct ShardManagerKey;
+
+impl TypeMapKey for ShardManagerKey {
+    type Value = Arc<Mutex<ShardManager>>;
+}
+
+/// How long each phase of startup took
+///
+/// The phases run concurrently, so startup is complete once every phase in
+/// [`Startup::PHASES`] has been recorded.
+#[derive(Debug, Default)]
+pub struct Startup(std::sync::Mutex<Vec<(&'static str, Duration)>>);
+
+impl Startup {
+    /// Command sync, the gateway connection and command warmup
+    pub const PHASES: [&'static str; 3] = ["commands", "gateway", "warmup"];
+
+    /// Record how long a phase took, replacing any earlier timing for it
+    pub fn record(&self, phase: &'static str, elapsed: Duration) {
+        let mut phases = self.0.lock().unwrap();
+        phases.retain(|(p, _)| *p != phase);
+        phases.push((phase, elapsed));
+        info!(phase, ?elapsed, "Startup phase completed");
+
+        if Self::PHASES.iter().all(|p| phases.iter().any(|(q, _)| q == p)) {
+            info!(?phases, "Startup completed");
+        }
+    }
+
+    fn phases(&self) -> Vec<(&'static str, Duration)> { self.0.lock().unwrap().clone() }
+}
+
+#[derive(Debug)]
+pub struct DebugCommand {
+    name: String,
+    namespace: String,
+    decode_log: Arc<DecodeLog>,
+    legacy: Arc<LegacyIds<Schema>>,
+    outbox: Arc<Outbox>,
+    voice: Arc<VoiceSessions>,
+    startup: Arc<Startup>,
+    owner: OnceCell<UserId>,
+}
+
+impl DebugCommand {
+    pub fn new(
+        opts: &CommandOpts,
+        decode_log: Arc<DecodeLog>,
+        legacy: Arc<LegacyIds<Schema>>,
+        outbox: Arc<Outbox>,
+        voice: Arc<VoiceSessions>,
+        startup: Arc<Startup>,
+    ) -> Self {
+        Self {
+            name: "debug".into(),
+            namespace: opts.command_base.clone(),
+            decode_log,
+            legacy,
+            outbox,
+            voice,
+            startup,
+            owner: OnceCell::new(),
+        }
+    }
+
+    async fn is_owner(&self, ctx: &Context, user: &User) -> Result<bool> {
+        let owner = self
+            .owner
+            .get_or_try_init(|| async {
+                let info = ctx
+                    .http
+                    .get_current_application_info()
+                    .await
+                    .context("Error getting application info")?;
+
+                Result::<_>::Ok(info.owner.id)
+            })
+            .await?;
+
+        Ok(user.id == *owner)
+    }
+
+    fn render(text: &str) -> MessageBody {
+        // Leave room for the code block fences
+        let text: String = text.chars().take(MAX_MESSAGE_LEN - 16).collect();
+
+        MessageBody::rich(|b| b.push_codeblock_safe(text, None))
+    }
+
+    async fn health(&self, ctx: &Context) -> MessageBody {
+        let mut s = String::new();
+
+        let phases = self.startup.phases();
+        if phases.len() < Startup::PHASES.len() {
+            s.push_str("Startup: in progress");
+        } else {
+            s.push_str("Startup:");
+        }
+        for (phase, elapsed) in phases {
+            write!(s, " {phase} {elapsed:.2?}").unwrap();
+        }
+        s.push('\n');
+
+        writeln!(s, "Gateway: shard {} of {}", ctx.shard_id, ctx.cache.shard_count()).unwrap();
+        let manager = ctx.data.read().await.get::<ShardManagerKey>().map(Arc::clone);
+        if let Some(manager) = manager {
+            let manager = manager.lock().await;
+            for (id, info) in &*manager.runners.lock().await {
+                let latency = info
+                    .latency
+                    .map_or_else(|| "unknown".into(), |l| format!("{} ms", l.as_millis()));
+                writeln!(s, "  Shard {}: {}, {latency} heartbeat latency", id.0, info.stage)
+                    .unwrap();
+            }
+        }
+
+        let queued: usize = self.outbox.depth().into_iter().map(|(_, n)| n).sum();
+        writeln!(s, "Outbox: {queued} queued").unwrap();
+
+        let failures: u64 = self.decode_log.counts().into_values().sum();
+        writeln!(s, "Payloads: {failures} decode failure(s)").unwrap();
+
+        let (sessions, sounds) = self.voice.stats().await;
+        writeln!(s, "Voice: {sessions} session(s), {sounds} sound(s) queued").unwrap();
+        for (gid, link) in self.voice.links().await {
+            let guild = gid.name(&ctx.cache).unwrap_or_else(|| gid.to_string());
+            match link {
+                Some((loss, jitter)) => {
+                    writeln!(s, "  {guild}: {loss}% packet loss, {jitter} ms jitter").unwrap();
+                },
+                None => writeln!(s, "  {guild}: no link reports yet").unwrap(),
+            }
+        }
+
+        writeln!(
+            s,
+            "Cache: {} guild(s), {} channel(s), {} user(s), {} unknown member(s)",
+            ctx.cache.guild_count(),
+            ctx.cache.guild_channel_count(),
+            ctx.cache.user_count(),
+            ctx.cache.unknown_members(),
+        )
+        .unwrap();
+
+        Self::render(&s).build_row(|r| {
+            r.button(
+                ComponentPayload::Health(component::Health {}),
+                ButtonStyle::Secondary,
+                "Refresh",
+                false,
+            )
+        })
+    }
+
+    fn outbox(&self) -> String {
+        let mut s = String::new();
+
+        for (priority, n) in self.outbox.depth() {
+            writeln!(s, "{priority:?}: {n} queued").unwrap();
+        }
+
+        s
+    }
+
+    fn payloads(&self) -> MessageBody {
+        let mut s = String::new();
+        let counts = self.decode_log.counts();
+
+        for (prefix, n) in self.legacy.hits() {
+            writeln!(s, "Legacy {prefix:?}: {n}").unwrap();
+        }
+
+        if counts.is_empty() {
+            s.push_str("No payload failures recorded.");
+        } else {
+            for (bucket, n) in counts {
+                writeln!(s, "{bucket}: {n}").unwrap();
+            }
+
+            s.push('\n');
+            // Newest first, so the most relevant samples survive truncation
+            for sample in self.decode_log.samples().into_iter().rev() {
+                writeln!(s, "{sample}").unwrap();
+            }
+        }
+
+        Self::render(&s).build_row(|r| {
+            r.button(
+                ComponentPayload::Payloads(component::Payloads {}),
+                ButtonStyle::Secondary,
+                "Refresh",
+                false,
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl CommandHandler<Schema> for DebugCommand {
+    fn register_global(&self) -> CommandInfo {
+        CommandInfo::build_slash(&self.name, "Bot diagnostics", |a| {
+            a.build_subcmd(
+                "payloads",
+                "Show legacy ID usage and component and modal payload failures by handler",
+                id,
+            )
+                .build_subcmd("outbox", "Show the number of queued outgoing messages", id)
+                .build_subcmd("health", "Show a summary of the bot's internal state", id)
+        })
+        .unwrap()
+        .in_namespace(&self.namespace)
+    }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+    ) -> CommandResult<'a> {
+        if !self.is_owner(ctx, visitor.user()).await? {
+            return Err(responder
+                .create_message(Message::plain("Only the bot owner can do that.").ephemeral(true))
+                .await
+                .context("Error sending owner error")?
+                .into_err("Non-owner attempted to use debug command"));
+        }
+
+        let path = visitor.visit_subcmd()?;
+        let body = match *path {
+            ["payloads"] => self.payloads(),
+            ["outbox"] => Self::render(&self.outbox()),
+            ["health"] => self.health(ctx).await,
+            _ => {
+                return Err(visitor::Error::UnhandledSubcommand(
+                    path.iter().map(|s| (*s).to_owned()).collect(),
+                )
+                .into());
+            },
+        };
+
+        Ok(responder
+            .create_message(Message::from(body).ephemeral(true))
+            .await
+            .context("Error sending debug output")?
+            .into())
+    }
+}
+
+#[async_trait]
+impl RpcHandler<Schema, ComponentKey> for DebugCommand {
+    fn register_keys(&self) -> &'static [ComponentKey] {
+        &[ComponentKey::Health, ComponentKey::Payloads]
+    }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        payload: ComponentPayload,
+        visitor: &mut ComponentVisitor<'_>,
+        responder: ComponentResponder<'_, 'a>,
+    ) -> ComponentResult<'a> {
+        if !self.is_owner(ctx, visitor.user()).await? {
+            return Err(responder
+                .create_message(Message::plain("Only the bot owner can do that.").ephemeral(true))
+                .await
+                .context("Error sending owner error")?
+                .into_err("Non-owner attempted to refresh diagnostics"));
+        }
+
+        let body = match payload {
+            ComponentPayload::Health(component::Health {}) => self.health(ctx).await,
+            ComponentPayload::Payloads(component::Payloads {}) => self.payloads(),
+            _ => unreachable!(),
+        };
+
+        Ok(responder
+            .update_message(body.into())
+            .await
+            .context("Error refreshing diagnostics")?
+            .into())
+    }
+}
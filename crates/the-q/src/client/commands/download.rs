@@ -0,0 +1,317 @@
+use std::{path::PathBuf, time::Duration};
+
+use super::prelude::*;
+
+#[derive(Debug, clap::Args)]
+pub struct DownloadOpts {
+    /// Directory to cache downloaded attachments in
+    #[arg(long, env)]
+    download_cache_dir: Option<PathBuf>,
+
+    /// Maximum total size, in bytes, of the download cache
+    #[arg(long, env, default_value_t = 512 * 1024 * 1024)]
+    download_cache_size: u64,
+
+    /// Maximum size, in bytes, of a single downloaded file
+    #[arg(long, env, default_value_t = 25 * 1024 * 1024)]
+    download_max_size: u64,
+
+    /// Number of times to retry a failed download
+    #[arg(long, env, default_value_t = 2)]
+    download_retries: u32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadError {
+    #[error("File is too large (over {0} bytes)")]
+    TooLarge(u64),
+    #[error("Unexpected content type {0:?}")]
+    ContentType(Option<String>),
+    #[error("Invalid URL")]
+    Url(#[from] url::ParseError),
+    #[error("HTTP request failed")]
+    Http(#[from] reqwest::Error),
+    #[error("Error accessing download cache")]
+    Cache(#[from] std::io::Error),
+}
+
+impl DownloadError {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Http(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.is_body()
+                    || e.status().map_or(false, |s| s.is_server_error())
+            },
+            Self::TooLarge(_) | Self::ContentType(_) | Self::Url(_) | Self::Cache(_) => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Download {
+    pub data: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+/// Constraints on a single download
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Accept<'a> {
+    /// MIME type prefixes to accept, or empty to accept any content type
+    pub mime_prefixes: &'a [&'a str],
+    /// Size limit overriding the downloader's default
+    pub max_size: Option<u64>,
+}
+
+impl<'a> Accept<'a> {
+    pub const IMAGE: Self = Self {
+        mime_prefixes: &["image/"],
+        max_size: None,
+    };
+
+    fn check_type(&self, ty: Option<&str>) -> Result<(), DownloadError> {
+        if self.mime_prefixes.is_empty() {
+            return Ok(());
+        }
+
+        // Missing content types are let through for the caller to sniff
+        match ty {
+            Some(t) if !self.mime_prefixes.iter().any(|p| t.starts_with(p)) => {
+                Err(DownloadError::ContentType(Some(t.into())))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Downloader {
+    client: reqwest::Client,
+    cache: CacheDir,
+    max_size: u64,
+    retries: u32,
+}
+
+impl From<&DownloadOpts> for Downloader {
+    fn from(opts: &DownloadOpts) -> Self {
+        let DownloadOpts {
+            download_cache_dir,
+            download_cache_size,
+            download_max_size,
+            download_retries,
+        } = opts;
+
+        Self {
+            client: http_client(Some(Duration::from_secs(30))),
+            cache: CacheDir::new(
+                download_cache_dir
+                    .clone()
+                    .unwrap_or_else(|| std::env::temp_dir().join("the-q-downloads")),
+                *download_cache_size,
+            ),
+            max_size: *download_max_size,
+            retries: *download_retries,
+        }
+    }
+}
+
+impl Downloader {
+    /// Download a Discord attachment, reusing a cached copy if one exists
+    ///
+    /// `progress` is called with the number of bytes received so far and the
+    /// expected total size, if known.
+    pub async fn attachment(
+        &self,
+        attachment: &Attachment,
+        accept: Accept<'_>,
+        progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> Result<Download, DownloadError> {
+        let max_size = accept.max_size.unwrap_or(self.max_size);
+        if attachment.size > max_size {
+            return Err(DownloadError::TooLarge(max_size));
+        }
+        accept.check_type(attachment.content_type.as_deref())?;
+
+        let name = attachment.id.to_string();
+        let path = self.cache.entry(&name);
+        match tokio::fs::read(&path).await {
+            Ok(data) => {
+                trace!(?path, "Attachment download cache hit");
+                return Ok(Download {
+                    data,
+                    content_type: attachment.content_type.clone(),
+                });
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => warn!(?path, %e, "Error reading download cache"),
+        }
+
+        let dl = self.url(attachment.url.parse()?, accept, progress).await?;
+
+        self.cache.create().await?;
+        // Write to a temporary name first so concurrent readers never observe
+        // a partial file
+        let tmp = self.cache.temp();
+        tokio::fs::write(&tmp, &dl.data).await?;
+        self.cache.insert(&tmp, &name).await?;
+
+        Ok(dl)
+    }
+
+    /// Download an arbitrary URL, retrying on transient errors
+    pub async fn url(
+        &self,
+        url: Url,
+        accept: Accept<'_>,
+        mut progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> Result<Download, DownloadError> {
+        let max_size = accept.max_size.unwrap_or(self.max_size);
+        let mut attempt = 0;
+
+        loop {
+            match self.try_url(url.clone(), accept, max_size, &mut progress).await {
+                Err(e) if attempt < self.retries && e.is_transient() => {
+                    attempt += 1;
+                    warn!(%url, %e, attempt, "Retrying failed download");
+                    tokio::time::sleep(Duration::from_millis(500) * 2_u32.pow(attempt)).await;
+                },
+                r => break r,
+            }
+        }
+    }
+
+    async fn try_url(
+        &self,
+        url: Url,
+        accept: Accept<'_>,
+        max_size: u64,
+        progress: &mut (impl FnMut(u64, Option<u64>) + Send),
+    ) -> Result<Download, DownloadError> {
+        let mut res = self.client.get(url).send().await?.error_for_status()?;
+
+        let content_type = res
+            .headers()
+            .get("Content-Type")
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+        accept.check_type(content_type.as_deref())?;
+
+        let len = res.content_length();
+        if len.map_or(false, |l| l > max_size) {
+            return Err(DownloadError::TooLarge(max_size));
+        }
+
+        let mut data = Vec::new();
+        progress(0, len);
+        while let Some(chunk) = res.chunk().await? {
+            data.extend_from_slice(&chunk);
+
+            let recvd = u64::try_from(data.len()).unwrap_or(u64::MAX);
+            if recvd > max_size {
+                return Err(DownloadError::TooLarge(max_size));
+            }
+            progress(recvd, len);
+        }
+
+        Ok(Download { data, content_type })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::{json::json, model::channel::Attachment};
+
+    use super::{Accept, DownloadError, DownloadOpts, Downloader, PathBuf};
+
+    fn cache_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("the-q-test-{}-{name}", std::process::id()))
+    }
+
+    fn downloader(name: &str) -> Downloader {
+        Downloader::from(&DownloadOpts {
+            download_cache_dir: Some(cache_dir(name)),
+            download_cache_size: 1024,
+            download_max_size: 64,
+            download_retries: 0,
+        })
+    }
+
+    fn attachment(size: u64, content_type: &str, url: &str) -> Attachment {
+        serde_json::from_value(json!({
+            "id": "1",
+            "filename": "file",
+            "size": size,
+            "url": url,
+            "proxy_url": url,
+            "content_type": content_type,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_type() {
+        assert!(Accept::IMAGE.check_type(Some("image/png")).is_ok());
+        assert!(Accept::IMAGE.check_type(None).is_ok());
+        assert!(matches!(
+            Accept::IMAGE.check_type(Some("text/html")),
+            Err(DownloadError::ContentType(Some(t))) if t == "text/html"
+        ));
+        assert!(Accept::default().check_type(Some("text/html")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_attachment() {
+        let dl = downloader("rejected");
+        let url = "http://127.0.0.1:0/file";
+
+        assert!(matches!(
+            dl.attachment(&attachment(65, "image/png", url), Accept::IMAGE, |_, _| ())
+                .await,
+            Err(DownloadError::TooLarge(64))
+        ));
+        assert!(matches!(
+            dl.attachment(
+                &attachment(65, "image/png", url),
+                Accept {
+                    max_size: Some(128),
+                    ..Accept::IMAGE
+                },
+                |_, _| (),
+            )
+            .await,
+            Err(DownloadError::Http(_))
+        ));
+        assert!(matches!(
+            dl.attachment(&attachment(1, "text/html", url), Accept::IMAGE, |_, _| ())
+                .await,
+            Err(DownloadError::ContentType(_))
+        ));
+        assert!(matches!(
+            dl.attachment(&attachment(1, "image/png", "not a url"), Accept::IMAGE, |_, _| ())
+                .await,
+            Err(DownloadError::Url(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit() {
+        let dl = downloader("hit");
+        dl.cache.create().await.unwrap();
+        tokio::fs::write(dl.cache.entry("1"), b"cached").await.unwrap();
+
+        // The URL is unreachable, so this only succeeds if the cache is used
+        let hit = dl
+            .attachment(
+                &attachment(6, "image/png", "http://127.0.0.1:0/file"),
+                Accept::IMAGE,
+                |_, _| (),
+            )
+            .await
+            .unwrap();
+        assert_eq!(hit.data, b"cached");
+        assert_eq!(hit.content_type.as_deref(), Some("image/png"));
+
+        tokio::fs::remove_dir_all(cache_dir("hit")).await.unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::{Duration, SystemTime};
+
+use paracord::markup::MessageBuilderExt;
+use qcore::markup::TimestampStyle;
+use serenity::{
+    http::UserPagination,
+    model::{
+        guild::{ScheduledEvent, ScheduledEventStatus},
+        id::ScheduledEventId,
+    },
+    utils::MessageBuilder,
+};
+use tokio::{sync::Mutex, task::JoinHandle};
+
+use super::prelude::*;
+
+/// Discord's limit on the number of event users returned per request
+const USER_PAGE: usize = 100;
+/// The maximum number of interested members to name in an event summary
+const SUMMARY_NAMES: usize = 50;
+
+#[derive(Debug, clap::Args)]
+pub struct EventOpts {
+    /// Number of minutes before a scheduled event starts to remind the members
+    /// interested in it
+    #[arg(long, env, default_value_t = 15)]
+    event_reminder_lead: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    tasks: HashMap<ScheduledEventId, JoinHandle<()>>,
+    reminded: HashSet<ScheduledEventId>,
+}
+
+/// Reminds the members interested in a guild's scheduled events before they
+/// start, and sends the creator the list of interested members once they end
+///
+/// Discord doesn't report who actually attended an event, so the list is of
+/// members who marked themselves interested.  Messages are sent through the
+/// [`Outbox`], so a large event can't starve interaction responses of
+/// requests.
+///
+/// **NOTE:** Pending reminders are only held in memory, but they are
+/// rescheduled from Discord's copy of the events whenever a guild becomes
+/// available, so only reminders which fall due while the bot is offline are
+/// lost.  Reminders already sent during the lead time before a restart will be
+/// sent again.
+#[derive(Debug)]
+pub struct EventReminders {
+    lead: Duration,
+    outbox: Arc<Outbox>,
+    state: Arc<Mutex<State>>,
+}
+
+fn until(ts: i64) -> Option<Duration> {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    u64::try_from(ts)
+        .ok()
+        .and_then(|t| Duration::from_secs(t).checked_sub(now))
+}
+
+impl EventReminders {
+    pub fn new(opts: &CommandOpts, outbox: Arc<Outbox>) -> Self {
+        let EventOpts {
+            event_reminder_lead,
+        } = opts.event;
+
+        Self {
+            lead: Duration::from_secs(event_reminder_lead * 60),
+            outbox,
+            state: Arc::default(),
+        }
+    }
+
+    /// Schedule reminders for every upcoming event in a guild
+    pub async fn load(&self, ctx: &Context, gid: GuildId) {
+        match gid.scheduled_events(&ctx.http, false).await {
+            Ok(events) => {
+                for event in events {
+                    self.update(ctx, event).await;
+                }
+            },
+            Err(err) => warn!(?err, ?gid, "Error listing scheduled events"),
+        }
+    }
+
+    /// Reschedule the reminder for an event which was created or changed, or
+    /// send its summary if it has just ended
+    pub async fn update(&self, ctx: &Context, event: ScheduledEvent) {
+        let mut state = self.state.lock().await;
+        if let Some(task) = state.tasks.remove(&event.id) {
+            task.abort();
+        }
+        state.tasks.retain(|_, t| !t.is_finished());
+
+        let id = event.id;
+        let gid = event.guild_id;
+        let ctx = ctx.clone();
+        let outbox = Arc::clone(&self.outbox);
+        let task = match event.status {
+            ScheduledEventStatus::Scheduled if !state.reminded.contains(&id) => {
+                let Some(start) = until(event.start_time.unix_timestamp()) else {
+                    return;
+                };
+                let delay = start.saturating_sub(self.lead);
+                let state = Arc::clone(&self.state);
+
+                async move {
+                    tokio::time::sleep(delay).await;
+                    state.lock().await.reminded.insert(event.id);
+
+                    remind(&ctx, &outbox, &event)
+                        .await
+                        .map_err(|err| error!(?err, "Error sending event reminders"))
+                        .ok();
+                }
+                .boxed()
+            },
+            ScheduledEventStatus::Completed => {
+                state.reminded.remove(&id);
+
+                async move {
+                    summarize(&ctx, &outbox, &event)
+                        .await
+                        .map_err(|err| error!(?err, "Error sending event summary"))
+                        .ok();
+                }
+                .boxed()
+            },
+            ScheduledEventStatus::Canceled => {
+                state.reminded.remove(&id);
+                return;
+            },
+            _ => return,
+        };
+
+        state.tasks.insert(
+            id,
+            tokio::task::spawn(task.instrument(info_span!(parent: None, "event", ?gid, ?id))),
+        );
+    }
+
+    /// Cancel the reminder for a deleted event
+    pub async fn remove(&self, id: ScheduledEventId) {
+        let mut state = self.state.lock().await;
+        if let Some(task) = state.tasks.remove(&id) {
+            task.abort();
+        }
+        state.reminded.remove(&id);
+    }
+}
+
+async fn interested(ctx: &Context, event: &ScheduledEvent) -> Result<Vec<User>> {
+    let mut users: Vec<User> = vec![];
+    // Paginate on the raw page, since bots are filtered out of `users`
+    let mut after = None;
+
+    loop {
+        let page = ctx
+            .http
+            .get_scheduled_event_users(
+                event.guild_id.0,
+                event.id.0,
+                Some(USER_PAGE as u64),
+                after.map(UserPagination::After),
+                Some(false),
+            )
+            .await
+            .context("Error listing interested members")?;
+        let done = page.len() < USER_PAGE;
+        after = page.last().map(|u| u.user.id);
+
+        users.extend(page.into_iter().map(|u| u.user).filter(|u| !u.bot));
+
+        if done {
+            break Ok(users);
+        }
+    }
+}
+
+async fn send_dm(ctx: &Context, outbox: &Outbox, user: UserId, content: &str) -> Result {
+    let dm = user
+        .create_dm_channel(&ctx.http)
+        .await
+        .context("Error opening DM")?;
+
+    outbox
+        .send(ctx, dm.id, Priority::Normal, |m| m.content(content))
+        .await
+        .context("Error sending DM")?;
+
+    Ok(())
+}
+
+async fn remind(ctx: &Context, outbox: &Outbox, event: &ScheduledEvent) -> Result {
+    let guild = event
+        .guild_id
+        .name(&ctx.cache)
+        .unwrap_or_else(|| event.guild_id.to_string());
+    let content = MessageBuilder::new()
+        .push("Reminder: ")
+        .push_bold_safe(&event.name)
+        .push(" in ")
+        .push_safe(guild)
+        .push(" starts ")
+        .timestamp(event.start_time.unix_timestamp(), TimestampStyle::Relative)
+        .push(".")
+        .build();
+
+    for user in interested(ctx, event).await? {
+        // Members with DMs closed shouldn't stop the rest being reminded
+        send_dm(ctx, outbox, user.id, &content)
+            .await
+            .map_err(|err| debug!(?err, user = ?user.id, "Error sending event reminder"))
+            .ok();
+    }
+
+    Ok(())
+}
+
+async fn summarize(ctx: &Context, outbox: &Outbox, event: &ScheduledEvent) -> Result {
+    let Some(creator) = event.creator_id else {
+        return Ok(());
+    };
+    let users = interested(ctx, event).await?;
+
+    let mut content = MessageBuilder::new();
+    content
+        .push_bold_safe(&event.name)
+        .push(format!(" has ended.  Interested members ({})", users.len()));
+
+    if !users.is_empty() {
+        content.push(": ");
+        for (i, user) in users.iter().take(SUMMARY_NAMES).enumerate() {
+            if i > 0 {
+                content.push(", ");
+            }
+            content.push_safe(&user.name);
+        }
+        if users.len() > SUMMARY_NAMES {
+            content.push(format!(" and {} more", users.len() - SUMMARY_NAMES));
+        }
+    }
+
+    send_dm(ctx, outbox, creator, &content.build())
+        .await
+        .context("Error sending event summary")
+}
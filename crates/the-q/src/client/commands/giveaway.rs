@@ -0,0 +1,622 @@
+use std::time::{Duration, SystemTime};
+
+use paracord::markup::MessageBuilderExt;
+use qcore::{
+    markup::TimestampStyle,
+    rng::{Commitment, Rng, Seed},
+};
+use serenity::{
+    model::{
+        guild::Role,
+        id::{ChannelId, MessageId, RoleId, UserId},
+        Permissions,
+    },
+    utils::MessageBuilder,
+};
+use tokio::sync::Mutex;
+
+use super::prelude::*;
+
+/// Maximum number of winners that can be drawn at once
+const MAX_WINNERS: i64 = 25;
+
+/// Maximum number of minutes a giveaway can stay open
+const MAX_DURATION: i64 = 4 * 7 * 24 * 60;
+
+/// Number of seconds a closed giveaway can still be rerolled for
+const REROLL_WINDOW: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, CommandArgs)]
+enum GiveawayArgs<'a> {
+    /// Post a giveaway that members enter with a button
+    #[arg(example = "prize:a hug duration:60 winners:2")]
+    Start {
+        /// What the winners receive
+        #[arg(min = 1, max = 200)]
+        prize: &'a str,
+        /// How long entries stay open, in minutes
+        #[arg(min = 1, max = MAX_DURATION)]
+        duration: i64,
+        /// Number of winners to draw (default 1)
+        #[arg(min = 1, max = MAX_WINNERS)]
+        winners: Option<i64>,
+        /// A role members must have to enter
+        role: Option<&'a Role>,
+    },
+    /// Publish a commitment to the seed for this server's next draw
+    Commit,
+    /// Draw winners from a list of entrants, revealing the committed seed
+    #[arg(example = "entrants:alice, bob, carol winners:1")]
+    Draw {
+        /// Comma-separated list of entrants, in the order to draw from
+        entrants: &'a str,
+        /// Number of winners to draw (default 1)
+        #[arg(min = 1, max = MAX_WINNERS)]
+        winners: Option<i64>,
+    },
+    /// Check a revealed seed against the commitment published for it
+    Verify {
+        /// The seed revealed after the draw
+        seed: &'a str,
+        /// The commitment published before the draw
+        commitment: &'a str,
+    },
+}
+
+/// Draw `winners` entrants with the given seed, using the guild ID as the
+/// stream so a seed can't be replayed across guilds
+fn draw<'a, T>(seed: &Seed, gid: GuildId, entrants: &'a [T], winners: usize) -> Vec<&'a T> {
+    Rng::new(seed, gid.0)
+        .sample(entrants.len(), winners)
+        .into_iter()
+        .map(|i| &entrants[i])
+        .collect()
+}
+
+/// Split a comma-separated list of entrants, dropping blanks and repeats
+fn parse_entrants(entrants: &str) -> Vec<&str> {
+    let mut unique = HashSet::new();
+    entrants
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty() && unique.insert(*e))
+        .collect()
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs().try_into().unwrap_or(i64::MAX))
+}
+
+fn winner_count(winners: Option<i64>) -> usize {
+    let winners = winners.unwrap_or(1).clamp(1, MAX_WINNERS);
+    winners.try_into().unwrap_or_else(|_| unreachable!())
+}
+
+#[derive(Debug)]
+struct Giveaway {
+    gid: GuildId,
+    chan: ChannelId,
+    prize: String,
+    winners: usize,
+    role: Option<RoleId>,
+    ends: i64,
+    seed: Seed,
+    entrants: BTreeSet<UserId>,
+    /// Entrants in draw order once the giveaway has closed, along with the
+    /// number drawn so far
+    order: Option<(Vec<UserId>, usize)>,
+}
+
+impl Giveaway {
+    fn message(&self) -> MessageBody {
+        let body = MessageBody::rich(|b| {
+            b.push("Giveaway: ").push_bold_safe(&self.prize);
+
+            let Some((ref order, _)) = self.order else {
+                b.push(format!("\n{} winner(s), ends ", self.winners))
+                    .timestamp(self.ends, TimestampStyle::Relative);
+                if let Some(role) = self.role {
+                    b.push(".  Requires ").mention(&role);
+                }
+
+                return b
+                    .push("\nSeed commitment: ")
+                    .push_mono(self.seed.commitment().to_string());
+            };
+
+            b.push("\nEnded ")
+                .timestamp(self.ends, TimestampStyle::Relative)
+                .push(format!(" with {} entrant(s).  Winners: ", order.len()));
+            Self::mentions(b, &order[..self.winners.min(order.len())]);
+
+            b.push("\nSeed ")
+                .push_mono(self.seed.to_string())
+                .push(format!(
+                    ", stream {}.  Entrants are sorted by user ID before drawing.",
+                    self.gid
+                ))
+        });
+
+        if self.order.is_some() {
+            body.build_row(|r| {
+                r.button(
+                    ComponentPayload::Giveaway(component::Giveaway {
+                        action: component::GiveawayAction::Reroll.into(),
+                    }),
+                    ButtonStyle::Secondary,
+                    "Reroll",
+                    false,
+                )
+            })
+        } else {
+            body.build_row(|r| {
+                r.button(
+                    ComponentPayload::Giveaway(component::Giveaway {
+                        action: component::GiveawayAction::Enter.into(),
+                    }),
+                    ButtonStyle::Primary,
+                    "Enter",
+                    false,
+                )
+            })
+        }
+    }
+
+    fn mentions<'a>(b: &'a mut MessageBuilder, users: &[UserId]) -> &'a mut MessageBuilder {
+        if users.is_empty() {
+            return b.push("nobody");
+        }
+
+        for (i, user) in users.iter().enumerate() {
+            if i > 0 {
+                b.push(", ");
+            }
+            b.mention(user);
+        }
+        b
+    }
+
+    /// Draw the next `winners` entrants, returning an announcement pinging
+    /// them or `None` if there were none left
+    fn draw_next(&mut self) -> Option<MessageBody> {
+        let (order, drawn) = self.order.get_or_insert_with(|| {
+            let entrants: Vec<_> = self.entrants.iter().copied().collect();
+            let order = draw(&self.seed, self.gid, &entrants, entrants.len())
+                .into_iter()
+                .copied()
+                .collect();
+            (order, 0)
+        });
+
+        let start = *drawn;
+        let winners = &order[start.min(order.len())..(start + self.winners).min(order.len())];
+        *drawn = start + self.winners;
+
+        if winners.is_empty() {
+            return None;
+        }
+
+        let pings = winners.to_vec();
+        let prize = &self.prize;
+        Some(
+            MessageBody::rich(|b| {
+                b.push("Congratulations ");
+                Self::mentions(b, &pings).push("!  You won ").push_bold_safe(prize)
+            })
+            .ping_users(pings),
+        )
+    }
+}
+
+/// Giveaways which are open or can still be rerolled, keyed by the ID of the
+/// message they were posted in
+///
+/// Giveaways are only held in memory, so they are lost if the bot restarts.
+type Giveaways = Arc<Mutex<HashMap<MessageId, Giveaway>>>;
+
+#[derive(Debug)]
+pub struct GiveawayCommand {
+    name: String,
+    namespace: String,
+    seeds: Mutex<HashMap<GuildId, Seed>>,
+    giveaways: Giveaways,
+    outbox: Arc<Outbox>,
+}
+
+impl GiveawayCommand {
+    pub fn new(opts: &CommandOpts, outbox: Arc<Outbox>) -> Self {
+        Self {
+            name: "giveaway".into(),
+            namespace: opts.command_base.clone(),
+            seeds: Mutex::default(),
+            giveaways: Giveaways::default(),
+            outbox,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn start(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        chan: ChannelId,
+        prize: &str,
+        duration: i64,
+        winners: usize,
+        role: Option<RoleId>,
+    ) -> Result<MessageBody> {
+        let now = unix_now();
+        let duration = duration.clamp(1, MAX_DURATION) * 60;
+        let giveaway = Giveaway {
+            gid,
+            chan,
+            prize: prize.into(),
+            winners,
+            role,
+            ends: now + duration,
+            seed: Seed::generate().context("Error generating seed")?,
+            entrants: BTreeSet::new(),
+            order: None,
+        };
+
+        let body = giveaway
+            .message()
+            .prepare()
+            .context("Error building giveaway message")?;
+        let msg = self
+            .outbox
+            .send(ctx, chan, Priority::Normal, |m| body.build_create_message(m))
+            .await
+            .context("Error sending giveaway message")?;
+
+        {
+            let mut giveaways = self.giveaways.lock().await;
+            giveaways.retain(|_, g| g.order.is_none() || g.ends + REROLL_WINDOW > now);
+            giveaways.insert(msg.id, giveaway);
+        }
+
+        let ctx = ctx.clone();
+        let giveaways = Arc::clone(&self.giveaways);
+        let outbox = Arc::clone(&self.outbox);
+        tokio::task::spawn(
+            async move {
+                tokio::time::sleep(Duration::from_secs(duration.unsigned_abs())).await;
+
+                close(&ctx, &outbox, &giveaways, msg.id)
+                    .await
+                    .map_err(|err| error!(?err, "Error closing giveaway"))
+                    .ok();
+            }
+            .instrument(info_span!(parent: None, "giveaway", ?gid, msg = ?msg.id)),
+        );
+
+        Ok(MessageBody::plain("Giveaway started!"))
+    }
+
+    async fn commit(&self, gid: GuildId) -> Result<MessageBody> {
+        let seed = Seed::generate().context("Error generating seed")?;
+        let commitment = seed.commitment();
+        self.seeds.lock().await.insert(gid, seed);
+
+        Ok(MessageBody::rich(|b| {
+            b.push("The next draw will use the seed with commitment ")
+                .push_mono(commitment.to_string())
+                .push(".  It will be revealed along with the winners.")
+        }))
+    }
+
+    async fn draw(&self, gid: GuildId, entrants: &str, winners: usize) -> Result<MessageBody> {
+        let entrants = parse_entrants(entrants);
+
+        if entrants.is_empty() {
+            return Ok(MessageBody::plain("There's nobody to draw from."));
+        }
+
+        let committed = self.seeds.lock().await.remove(&gid);
+        let seed = match committed {
+            Some(s) => s,
+            None => Seed::generate().context("Error generating seed")?,
+        };
+        let drawn = draw(&seed, gid, &entrants, winners);
+
+        Ok(MessageBody::rich(|b| {
+            b.push_bold("Winners:");
+            for (i, winner) in drawn.iter().enumerate() {
+                b.push(format!("\n{}. ", i + 1)).push_safe(winner);
+            }
+
+            b.push(format!("\n\nDrawn from {} entrant(s) with seed ", entrants.len()))
+                .push_mono(seed.to_string())
+                .push(" (commitment ")
+                .push_mono(seed.commitment().to_string())
+                .push(format!(", stream {gid})."));
+
+            if committed.is_none() {
+                b.push("\nNo commitment was published before this draw.");
+            }
+
+            b
+        }))
+    }
+
+    fn verify(seed: &str, commitment: &str) -> MessageBody {
+        let (seed, commitment) = match (seed.parse::<Seed>(), commitment.parse::<Commitment>()) {
+            (Ok(s), Ok(c)) => (s, c),
+            (Err(e), _) => return MessageBody::plain(format!("That isn't a valid seed: {e}")),
+            (_, Err(e)) => return MessageBody::plain(format!("That isn't a valid commitment: {e}")),
+        };
+
+        if commitment.verify(&seed) {
+            MessageBody::plain("The seed matches the commitment.")
+        } else {
+            MessageBody::plain("The seed does not match the commitment!")
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<Schema> for GiveawayCommand {
+    fn register_global(&self) -> CommandInfo {
+        CommandInfo::build_slash(&self.name, "Run a verifiably fair draw", GiveawayArgs::register)
+            .unwrap()
+            .in_namespace(&self.namespace)
+    }
+
+    fn visited_args(&self) -> Option<Visited> { Some(GiveawayArgs::visited()) }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+    ) -> CommandResult<'a> {
+        let args = GiveawayArgs::from_visitor(visitor)?;
+        let (gid, memb) = visitor.member()?;
+
+        // Giveaways ping their winners, so starting one is as privileged as
+        // running a draw
+        if !matches!(args, GiveawayArgs::Verify { .. })
+            && !memb.permissions.map_or(false, Permissions::manage_guild)
+        {
+            return Err(responder
+                .create_message(
+                    Message::plain("Only moderators can start giveaways or run draws.")
+                        .ephemeral(true),
+                )
+                .await
+                .context("Error sending permission error")?
+                .into_err("User cannot run draws"));
+        }
+
+        let (msg, ephemeral) = match args {
+            GiveawayArgs::Start {
+                prize,
+                duration,
+                winners,
+                role,
+            } => {
+                let winners = winner_count(winners);
+                let role = role.map(|r| r.id);
+                let chan = visitor.channel();
+                let msg = self
+                    .start(ctx, gid, chan, prize, duration, winners, role)
+                    .await?;
+                (msg, true)
+            },
+            GiveawayArgs::Commit => (self.commit(gid).await?, false),
+            GiveawayArgs::Draw { entrants, winners } => {
+                (self.draw(gid, entrants, winner_count(winners)).await?, false)
+            },
+            GiveawayArgs::Verify { seed, commitment } => (Self::verify(seed, commitment), true),
+        };
+
+        Ok(responder
+            .create_message(Message::from(msg).ephemeral(ephemeral))
+            .await
+            .context("Error sending giveaway response")?
+            .into())
+    }
+}
+
+/// Close a giveaway, drawing its winners and announcing them
+async fn close(
+    ctx: &Context,
+    outbox: &Outbox,
+    giveaways: &Mutex<HashMap<MessageId, Giveaway>>,
+    id: MessageId,
+) -> Result {
+    let (chan, body, announce) = {
+        let mut giveaways = giveaways.lock().await;
+        let giveaway = giveaways.get_mut(&id).context("Missing giveaway")?;
+        let announce = giveaway.draw_next();
+
+        (giveaway.chan, giveaway.message(), announce)
+    };
+
+    let body = body.prepare().context("Error building giveaway message")?;
+    outbox
+        .edit(ctx, chan, id, Priority::Normal, |m| body.build_edit_message(m))
+        .await
+        .context("Error editing giveaway message")?;
+
+    if let Some(announce) = announce {
+        let announce = announce
+            .prepare()
+            .context("Error building giveaway announcement")?;
+        outbox
+            .send(ctx, chan, Priority::Normal, |m| announce.build_create_message(m))
+            .await
+            .context("Error announcing giveaway winners")?;
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl RpcHandler<Schema, ComponentKey> for GiveawayCommand {
+    fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::Giveaway] }
+
+    async fn respond<'a>(
+        &self,
+        _: &Context,
+        payload: ComponentPayload,
+        visitor: &mut ComponentVisitor<'_>,
+        responder: ComponentResponder<'_, 'a>,
+    ) -> ComponentResult<'a> {
+        let ComponentPayload::Giveaway(component::Giveaway { action }) = payload else {
+            unreachable!();
+        };
+        let (_gid, memb) = visitor.member()?;
+        let user = visitor.user();
+        let id = visitor.message().id;
+
+        let mut giveaways = self.giveaways.lock().await;
+        let (msg, err) = match (
+            giveaways.get_mut(&id),
+            component::GiveawayAction::from_i32(action),
+        ) {
+            (None, _) => (
+                MessageBody::plain("That giveaway is no longer running."),
+                Some("Giveaway not found"),
+            ),
+            (Some(g), Some(component::GiveawayAction::Enter)) => {
+                if g.order.is_some() {
+                    (MessageBody::plain("That giveaway has ended."), Some("Giveaway ended"))
+                } else if g.role.map_or(false, |r| !memb.roles.contains(&r)) {
+                    (
+                        MessageBody::plain("You don't have the role needed to enter."),
+                        Some("User missing giveaway role"),
+                    )
+                } else if g.entrants.insert(user.id) {
+                    (
+                        MessageBody::plain(format!(
+                            "You're in!  There are {} entrant(s) so far.",
+                            g.entrants.len()
+                        )),
+                        None,
+                    )
+                } else {
+                    (MessageBody::plain("You've already entered."), None)
+                }
+            },
+            (Some(g), Some(component::GiveawayAction::Reroll)) => {
+                if !memb.permissions.map_or(false, Permissions::manage_guild) {
+                    (
+                        MessageBody::plain("Only moderators can reroll a giveaway."),
+                        Some("User cannot reroll giveaway"),
+                    )
+                } else if g.order.is_none() {
+                    (MessageBody::plain("That giveaway hasn't ended yet."), Some("Giveaway open"))
+                } else if let Some(announce) = g.draw_next() {
+                    mem::drop(giveaways);
+
+                    return Ok(responder
+                        .create_message(announce.into())
+                        .await
+                        .context("Error announcing rerolled winners")?
+                        .into());
+                } else {
+                    (
+                        MessageBody::plain("There's nobody left to draw."),
+                        Some("Giveaway entrants exhausted"),
+                    )
+                }
+            },
+            (Some(_), Some(component::GiveawayAction::Unknown) | None) => {
+                return Err(anyhow!("Invalid giveaway action {action:?}").into());
+            },
+        };
+        mem::drop(giveaways);
+
+        let responder = responder
+            .create_message(Message::from(msg).ephemeral(true))
+            .await
+            .context("Error sending giveaway response")?;
+
+        match err {
+            Some(err) => Err(responder.into_err(err)),
+            None => Ok(responder.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw, parse_entrants, BTreeSet, ChannelId, Giveaway, GuildId, Seed, UserId};
+
+    fn seed(byte: &str) -> Seed { byte.repeat(32).parse().unwrap() }
+
+    fn giveaway(seed: Seed, entrants: u64, winners: usize) -> Giveaway {
+        Giveaway {
+            gid: GuildId(1),
+            chan: ChannelId(2),
+            prize: "a hug".into(),
+            winners,
+            role: None,
+            ends: 0,
+            seed,
+            entrants: (1..=entrants).map(UserId).collect(),
+            order: None,
+        }
+    }
+
+    fn drawn(giveaway: &Giveaway) -> &[UserId] {
+        let (order, drawn) = giveaway.order.as_ref().unwrap();
+        &order[..(*drawn).min(order.len())]
+    }
+
+    #[test]
+    fn test_parse_entrants() {
+        assert_eq!(parse_entrants(" alice, bob,,alice ,carol, "), ["alice", "bob", "carol"]);
+        assert!(parse_entrants(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_draw_deterministic() {
+        let entrants: Vec<_> = (0..20).collect();
+        let a = draw(&seed("ab"), GuildId(1), &entrants, 5);
+
+        assert_eq!(a.len(), 5);
+        assert_eq!(a, draw(&seed("ab"), GuildId(1), &entrants, 5));
+        assert_ne!(a, draw(&seed("cd"), GuildId(1), &entrants, 5));
+        assert_ne!(a, draw(&seed("ab"), GuildId(2), &entrants, 5));
+
+        let all: BTreeSet<_> = draw(&seed("ab"), GuildId(1), &entrants, 30)
+            .into_iter()
+            .collect();
+        assert_eq!(all, entrants.iter().collect());
+    }
+
+    #[test]
+    fn test_draw_next() {
+        let mut g = giveaway(seed("ab"), 5, 2);
+
+        assert!(g.draw_next().is_some());
+        let first = drawn(&g).to_vec();
+        assert_eq!(first.len(), 2);
+
+        assert!(g.draw_next().is_some());
+        assert!(g.draw_next().is_some());
+        let all = drawn(&g).to_vec();
+        assert_eq!(all[..2], first);
+        assert_eq!(all.iter().collect::<BTreeSet<_>>(), g.entrants.iter().collect());
+
+        assert!(g.draw_next().is_none());
+        assert!(g.draw_next().is_none());
+        assert_eq!(drawn(&g), all);
+
+        let mut again = giveaway(seed("ab"), 5, 2);
+        again.draw_next();
+        assert_eq!(drawn(&again), first);
+    }
+
+    #[test]
+    fn test_draw_next_empty() {
+        let mut g = giveaway(seed("ab"), 0, 1);
+
+        assert!(g.draw_next().is_none());
+        assert!(drawn(&g).is_empty());
+    }
+}
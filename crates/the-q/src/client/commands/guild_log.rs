@@ -0,0 +1,266 @@
+use std::time::Duration;
+
+use serenity::model::{channel::ChannelType, id::ChannelId};
+use tokio::sync::Mutex;
+
+use super::prelude::*;
+
+/// Discord's limit on the length of message content
+const MAX_MESSAGE_LEN: usize = 2000;
+
+#[derive(Debug, clap::Args)]
+pub struct GuildLogOpts {
+    /// Name of the channel to write guild log entries to
+    #[arg(long, env, default_value = "bot-log")]
+    log_channel: String,
+
+    /// Number of seconds to batch guild log entries for before posting them
+    #[arg(long, env, default_value_t = 5)]
+    log_interval: u64,
+}
+
+/// Log entries waiting to be posted to a single guild
+#[derive(Debug, Default)]
+struct Batch(Vec<(String, usize)>);
+
+impl Batch {
+    fn push(&mut self, entry: String) {
+        match self.0.last_mut() {
+            Some((last, n)) if *last == entry => *n += 1,
+            _ => self.0.push((entry, 1)),
+        }
+    }
+
+    /// Render the batch into as few messages as possible
+    fn render(self) -> Vec<String> {
+        let mut msgs = vec![];
+        let mut msg = String::new();
+
+        for (entry, n) in self.0 {
+            let mut line = if n > 1 {
+                format!("{entry} ({} similar entries suppressed)", n - 1)
+            } else {
+                entry
+            };
+
+            if line.chars().count() > MAX_MESSAGE_LEN {
+                line = line.chars().take(MAX_MESSAGE_LEN - 1).collect();
+                line.push('…');
+            }
+
+            if !msg.is_empty() && msg.chars().count() + line.chars().count() + 1 > MAX_MESSAGE_LEN
+            {
+                msgs.push(mem::take(&mut msg));
+            }
+
+            if !msg.is_empty() {
+                msg.push('\n');
+            }
+            msg.push_str(&line);
+        }
+
+        if !msg.is_empty() {
+            msgs.push(msg);
+        }
+
+        msgs
+    }
+}
+
+/// A shared writer for per-guild log channels
+///
+/// Entries are batched for a short interval before being posted, so a burst
+/// of entries costs a single message (or a few, if they exceed the message
+/// length limit) rather than one request each.  Identical consecutive entries
+/// are merged.  If a guild has no log channel or it can't be written to, the
+/// entries are sent to the bot owner instead.
+#[derive(Debug)]
+pub struct GuildLog {
+    channel: String,
+    interval: Duration,
+    batches: Arc<Mutex<HashMap<GuildId, Batch>>>,
+    outbox: Arc<Outbox>,
+}
+
+impl GuildLog {
+    pub fn new(opts: &CommandOpts, outbox: Arc<Outbox>) -> Self {
+        let GuildLogOpts {
+            log_channel,
+            log_interval,
+        } = &opts.guild_log;
+
+        Self {
+            channel: log_channel.clone(),
+            interval: Duration::from_secs(*log_interval),
+            batches: Arc::default(),
+            outbox,
+        }
+    }
+
+    /// Queue an entry to be written to a guild's log channel
+    pub async fn log(&self, ctx: &Context, gid: GuildId, entry: impl Into<String>) {
+        let mut batches = self.batches.lock().await;
+        let scheduled = batches.contains_key(&gid);
+        batches.entry(gid).or_default().push(entry.into());
+
+        if scheduled {
+            return;
+        }
+
+        let ctx = ctx.clone();
+        let channel = self.channel.clone();
+        let interval = self.interval;
+        let batches = Arc::clone(&self.batches);
+        let outbox = Arc::clone(&self.outbox);
+        tokio::task::spawn(
+            async move {
+                tokio::time::sleep(interval).await;
+
+                let Some(batch) = batches.lock().await.remove(&gid) else {
+                    return;
+                };
+
+                flush(&ctx, &outbox, gid, &channel, batch.render())
+                    .await
+                    .map_err(|err| error!(?err, "Error writing guild log"))
+                    .ok();
+            }
+            .instrument(info_span!(parent: None, "guild_log", ?gid)),
+        );
+    }
+}
+
+async fn find_channel(ctx: &Context, gid: GuildId, name: &str) -> Result<Option<ChannelId>> {
+    let chans = match ctx.cache.guild_channels(gid) {
+        Some(c) => c.into_iter().collect(),
+        None => gid
+            .channels(&ctx.http)
+            .await
+            .context("Error listing guild channels")?,
+    };
+    let chan = chans
+        .into_iter()
+        .find_map(|(id, c)| (c.kind == ChannelType::Text && c.name == name).then_some(id));
+
+    Ok(chan)
+}
+
+async fn send(
+    ctx: &Context,
+    outbox: &Outbox,
+    chan: ChannelId,
+    priority: Priority,
+    content: &str,
+) -> Result {
+    outbox
+        .send(ctx, chan, priority, |m| {
+            m.content(content).allowed_mentions(|a| a.empty_parse())
+        })
+        .await
+        .context("Error sending log message")?;
+
+    Ok(())
+}
+
+async fn flush(
+    ctx: &Context,
+    outbox: &Outbox,
+    gid: GuildId,
+    channel: &str,
+    msgs: Vec<String>,
+) -> Result {
+    let mut msgs = msgs.into_iter().peekable();
+
+    match find_channel(ctx, gid, channel).await {
+        Ok(Some(chan)) => {
+            while let Some(msg) = msgs.peek() {
+                if let Err(err) = send(ctx, outbox, chan, Priority::Low, msg).await {
+                    warn!(?err, "Error writing to guild log channel, falling back to owner");
+                    break;
+                }
+                msgs.next();
+            }
+        },
+        Ok(None) => debug!(channel, "Guild has no log channel, falling back to owner"),
+        Err(err) => warn!(?err, "Error finding guild log channel, falling back to owner"),
+    }
+
+    if msgs.peek().is_none() {
+        return Ok(());
+    }
+
+    let owner = ctx
+        .http
+        .get_current_application_info()
+        .await
+        .context("Error getting application info")?
+        .owner;
+    let dm = owner
+        .create_dm_channel(&ctx.http)
+        .await
+        .context("Error opening owner DM")?;
+
+    let guild = gid.name(&ctx.cache).unwrap_or_else(|| gid.to_string());
+    send(
+        ctx,
+        outbox,
+        dm.id,
+        Priority::High,
+        &format!("Couldn't write to #{channel} in {guild}, the entries were:"),
+    )
+    .await?;
+
+    for msg in msgs {
+        send(ctx, outbox, dm.id, Priority::High, &msg).await?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Batch, MAX_MESSAGE_LEN};
+
+    #[test]
+    fn test_merge() {
+        let mut batch = Batch::default();
+        for entry in ["a", "a", "b", "a", "a", "a"] {
+            batch.push(entry.into());
+        }
+
+        assert_eq!(batch.render(), [
+            "a (1 similar entries suppressed)\nb\na (2 similar entries suppressed)"
+        ]);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut batch = Batch::default();
+        batch.push("x".repeat(MAX_MESSAGE_LEN + 10));
+        let msgs = batch.render();
+
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].chars().count(), MAX_MESSAGE_LEN);
+        assert!(msgs[0].ends_with('…'));
+    }
+
+    #[test]
+    fn test_split() {
+        let line = "x".repeat(MAX_MESSAGE_LEN / 2);
+        let mut batch = Batch::default();
+        for c in ['a', 'b', 'c'] {
+            batch.push(format!("{c}{line}"));
+        }
+        let msgs = batch.render();
+
+        assert_eq!(msgs.len(), 3);
+        assert!(msgs.iter().all(|m| m.chars().count() <= MAX_MESSAGE_LEN));
+        assert!(msgs[0].starts_with('a') && msgs[2].starts_with('c'));
+
+        let mut batch = Batch::default();
+        batch.push("a".into());
+        batch.push("b".into());
+        assert_eq!(batch.render(), ["a\nb"]);
+    }
+}
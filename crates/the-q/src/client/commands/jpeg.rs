@@ -10,43 +10,36 @@ enum JpegInput<'a> {
     Url(Url),
 }
 
-async fn jpeg(input: JpegInput<'_>, quality: Option<i64>) -> Result<Vec<u8>> {
+async fn jpeg(
+    downloader: &Downloader,
+    input: JpegInput<'_>,
+    quality: Option<i64>,
+) -> Result<Vec<u8>> {
     let quality @ 0..=100 = quality.unwrap_or(1) else {
         unreachable!()
     };
     let quality = u8::try_from(quality).unwrap_or_else(|_| unreachable!());
 
-    let image_data;
-    let content_type;
+    let download;
     let filename;
     match input {
         JpegInput::Attachment(a) => {
-            image_data = a
-                .download()
+            download = downloader
+                .attachment(a, Accept::IMAGE, |_, _| ())
                 .await
                 .context("Error downloading attachment from discord")?;
-            content_type = a.content_type.clone();
             filename = Some(a.filename.clone());
         },
         JpegInput::Url(u) => {
-            let res = http_client(None)
-                .get(u)
-                .send()
+            download = downloader
+                .url(u, Accept::IMAGE, |_, _| ())
                 .await
-                .context("Error fetching input URL")?;
-            content_type = res
-                .headers()
-                .get("Content-Type")
-                .and_then(|h| h.to_str().ok())
-                .map(ToOwned::to_owned);
-            image_data = res
-                .bytes()
-                .await
-                .context("Error downloading image response")?
-                .to_vec();
+                .context("Error downloading input URL")?;
             filename = None;
         },
     }
+    let image_data = download.data;
+    let content_type = download.content_type;
 
     tokio::task::spawn_blocking(move || {
         let format = content_type
@@ -78,12 +71,16 @@ async fn jpeg(input: JpegInput<'_>, quality: Option<i64>) -> Result<Vec<u8>> {
 #[derive(Debug)]
 pub struct JpegCommand {
     name: String,
+    namespace: String,
+    downloader: Arc<Downloader>,
 }
 
-impl From<&CommandOpts> for JpegCommand {
-    fn from(opts: &CommandOpts) -> Self {
+impl JpegCommand {
+    pub fn new(opts: &CommandOpts, downloader: Arc<Downloader>) -> Self {
         Self {
-            name: format!("{}jpeg", opts.command_base),
+            name: "jpeg".into(),
+            namespace: opts.command_base.clone(),
+            downloader,
         }
     }
 }
@@ -100,6 +97,7 @@ impl CommandHandler<Schema> for JpegCommand {
             )
         })
         .unwrap()
+        .in_namespace(&self.namespace)
     }
 
     async fn respond<'a>(
@@ -116,7 +114,7 @@ impl CommandHandler<Schema> for JpegCommand {
             .await
             .context("Error sending deferred message")?;
 
-        let bytes = jpeg(JpegInput::Attachment(attachment), quality).await?;
+        let bytes = jpeg(&self.downloader, JpegInput::Attachment(attachment), quality).await?;
 
         let attachment = AttachmentType::Bytes {
             data: bytes.into(),
@@ -137,12 +135,14 @@ impl CommandHandler<Schema> for JpegCommand {
 #[derive(Debug)]
 pub struct JpegMessageCommand {
     name: String,
+    downloader: Arc<Downloader>,
 }
 
-impl From<&CommandOpts> for JpegMessageCommand {
-    fn from(opts: &CommandOpts) -> Self {
+impl JpegMessageCommand {
+    pub fn new(opts: &CommandOpts, downloader: Arc<Downloader>) -> Self {
         Self {
             name: format!("{}JPEG This", opts.context_menu_base),
+            downloader,
         }
     }
 }
@@ -215,7 +215,7 @@ impl CommandHandler<Schema> for JpegMessageCommand {
             .await
             .context("Error sending deferred message")?;
 
-        let bytes = jpeg(input, None).await?;
+        let bytes = jpeg(&self.downloader, input, None).await?;
 
         // TODO: post file size difference
         let attachment = AttachmentType::Bytes {
@@ -1,15 +1,28 @@
+mod cache;
+mod debug;
+mod download;
+mod event;
 mod explode;
+mod giveaway;
+mod guild_log;
 mod jpeg;
+mod outbox;
+mod pin;
 mod point;
 mod re;
 mod rpc;
 mod say;
+mod share;
 mod sound;
 mod test;
+mod tts;
+mod undo;
+mod voice;
 
 pub(self) mod prelude {
     pub use paracord::interaction::{
-        command::{prelude::*, Args, CommandInfo},
+        args::{CommandArgs, Visited},
+        command::{prelude::*, Args, CommandInfo, NamespaceInfo},
         completion::Completion,
         handler,
         handler::{
@@ -25,10 +38,22 @@ pub(self) mod prelude {
     };
     pub(super) use serenity::{
         client::Context,
-        model::{channel::Attachment, id::GuildId, user::User},
+        model::{
+            channel::Attachment,
+            id::{GuildId, UserId},
+            user::User,
+        },
     };
 
-    pub use super::{CommandOpts, ComponentKey, ModalKey, Schema};
+    pub use super::{
+        cache::CacheDir,
+        download::{Accept, Downloader},
+        guild_log::GuildLog,
+        outbox::{Outbox, Priority},
+        share::shareable,
+        undo::{Undo, UndoLog},
+        CommandOpts, ComponentKey, ModalKey, Schema,
+    };
     pub use crate::{
         prelude::*,
         proto::{
@@ -64,45 +89,170 @@ pub(self) mod prelude {
     }
 }
 
+pub use debug::{ShardManagerKey, Startup};
+pub use event::EventReminders;
 pub use rpc::*;
+pub use voice::VoiceSessions;
 
 pub type Handlers = prelude::handler::Handlers<Schema>;
 
+/// State shared between the interaction handlers and the gateway event
+/// handler
+#[derive(Debug)]
+pub struct Shared {
+    outbox: prelude::Arc<outbox::Outbox>,
+    guild_log: prelude::Arc<guild_log::GuildLog>,
+    pub voice: prelude::Arc<VoiceSessions>,
+    pub events: EventReminders,
+    pub startup: prelude::Arc<Startup>,
+}
+
+impl From<&CommandOpts> for Shared {
+    fn from(opts: &CommandOpts) -> Self {
+        use prelude::Arc;
+
+        let outbox = Arc::new(outbox::Outbox::default());
+        let guild_log = Arc::new(guild_log::GuildLog::new(opts, Arc::clone(&outbox)));
+        let voice = Arc::new(VoiceSessions::new(opts, Arc::clone(&guild_log)));
+        let events = EventReminders::new(opts, Arc::clone(&outbox));
+
+        Self {
+            outbox,
+            guild_log,
+            voice,
+            events,
+            startup: Arc::default(),
+        }
+    }
+}
+
 // TODO: set up command names
 #[derive(Debug, clap::Args)]
 pub struct CommandOpts {
+    /// Name of the top-level command to group slash commands under
     #[arg(long, env, default_value = "q")]
     command_base: String,
 
     #[arg(long, env, default_value = "")]
     context_menu_base: String,
+
+    /// Name of the channel to archive old pins into when a channel is out of
+    /// pins
+    #[arg(long, env, default_value = "pin-archive")]
+    pin_archive_channel: String,
+
+    /// Directory to load sounds from, which is watched for changes.  A
+    /// sound is named after its path relative to this directory, and hidden
+    /// files are skipped.  Sounds under `guilds/<guild ID>/` are only
+    /// available in that guild, named relative to that directory, and take
+    /// precedence over global sounds with the same name
+    #[arg(long, env, default_value = "etc/samples")]
+    sound_dir: std::path::PathBuf,
+
+    /// Name of the channel to post soundboard buttons into from the now
+    /// playing message
+    #[arg(long, env, default_value = "soundboard")]
+    soundboard_channel: String,
+
+    /// Maximum number of sounds that can be posted to the soundboard channel
+    #[arg(long, env, default_value_t = 100)]
+    soundboard_max_sounds: usize,
+
+    #[command(flatten)]
+    download: download::DownloadOpts,
+
+    #[command(flatten)]
+    event: event::EventOpts,
+
+    #[command(flatten)]
+    guild_log: guild_log::GuildLogOpts,
+
+    #[command(flatten)]
+    tts: tts::TtsOpts,
+
+    #[command(flatten)]
+    undo: undo::UndoOpts,
+
+    #[command(flatten)]
+    voice: voice::VoiceOpts,
 }
 
 // TODO: can this be attribute-macro-ified?
-pub fn handlers(opts: &CommandOpts) -> Handlers {
-    use prelude::Arc;
+pub fn handlers(opts: &CommandOpts, shared: &Shared) -> Handlers {
+    use paracord::interaction::{decode_log::DecodeLog, legacy::LegacyIds};
+    use prelude::{Arc, NamespaceInfo};
+
+    let decode_log = Arc::new(DecodeLog::default());
+    // Register shims here before changing the format of any existing component
+    // payload, and remove them once the debug command shows they are unused
+    let legacy = Arc::new(LegacyIds::default());
+    let outbox = Arc::clone(&shared.outbox);
+    let guild_log = Arc::clone(&shared.guild_log);
+    let undo_log = Arc::new(undo::UndoLog::from(&opts.undo));
+
+    let debug = Arc::new(debug::DebugCommand::new(
+        opts,
+        Arc::clone(&decode_log),
+        Arc::clone(&legacy),
+        outbox,
+        Arc::clone(&shared.voice),
+        Arc::clone(&shared.startup),
+    ));
 
     let explode = Arc::new(explode::ExplodeCommand::from(opts));
-    let jpeg = Arc::new(jpeg::JpegCommand::from(opts));
-    let jpeg_message = Arc::new(jpeg::JpegMessageCommand::from(opts));
+    let giveaway = Arc::new(giveaway::GiveawayCommand::new(opts, Arc::clone(&shared.outbox)));
+    let downloader = Arc::new(download::Downloader::from(&opts.download));
+    let jpeg = Arc::new(jpeg::JpegCommand::new(opts, Arc::clone(&downloader)));
+    let jpeg_message = Arc::new(jpeg::JpegMessageCommand::new(opts, downloader));
+    let pin = Arc::new(pin::PinCommand::pin(opts, Arc::clone(&guild_log), Arc::clone(&undo_log)));
+    let unpin = Arc::new(pin::PinCommand::unpin(
+        opts,
+        Arc::clone(&guild_log),
+        Arc::clone(&undo_log),
+    ));
+    let pin_message = Arc::new(pin::PinMessageCommand::pin(
+        opts,
+        Arc::clone(&guild_log),
+        Arc::clone(&undo_log),
+    ));
+    let unpin_message = Arc::new(pin::PinMessageCommand::unpin(
+        opts,
+        Arc::clone(&guild_log),
+        Arc::clone(&undo_log),
+    ));
+    let pin_archive = Arc::new(pin::PinArchiveCommand::from(opts));
     let point = Arc::new(point::PointCommand::from(opts));
     let re = Arc::new(re::ReCommand::from(opts));
     let say = Arc::new(say::SayCommand::from(opts));
-    let sound = Arc::new(sound::SoundCommand::from(opts));
+    let share = Arc::new(share::ShareComponent);
+    let sound = Arc::new(sound::SoundCommand::new(opts, Arc::clone(&shared.voice)));
     let test = Arc::new(test::TestCommand::from(opts));
+    let tts = Arc::new(tts::TtsCommand::new(opts, Arc::clone(&sound)));
+    let undo = Arc::new(undo::UndoComponent::new(undo_log, guild_log));
 
     Handlers {
+        namespaces: vec![NamespaceInfo::new(&opts.command_base, "Is he...y’know,")],
         commands: vec![
+            Arc::clone(&debug) as Arc<dyn prelude::CommandHandler<Schema>>,
             explode,
+            Arc::clone(&giveaway) as Arc<dyn prelude::CommandHandler<Schema>>,
             jpeg,
             jpeg_message,
+            pin,
+            unpin,
+            pin_message,
+            unpin_message,
+            Arc::clone(&pin_archive) as Arc<dyn prelude::CommandHandler<Schema>>,
             point,
             re,
             say,
             test,
+            tts,
             Arc::clone(&sound) as Arc<dyn prelude::CommandHandler<Schema>>,
         ],
-        components: vec![sound],
+        components: vec![debug, giveaway, pin_archive, share, sound, undo],
         modals: vec![],
+        decode_log,
+        legacy,
     }
 }
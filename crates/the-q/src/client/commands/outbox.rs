@@ -0,0 +1,329 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serenity::{
+    builder::{CreateMessage, EditMessage},
+    http::{Http, HttpError},
+    json::{self, JsonMap, Value},
+    model::{
+        channel::Message as ChannelMessage,
+        id::{ChannelId, MessageId},
+    },
+};
+use tokio::sync::{oneshot, Notify};
+
+use super::prelude::*;
+
+/// Number of times to retry a message that failed with a transient error
+const MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry, doubled after each subsequent failure
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The urgency of an outgoing message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Informational messages that can wait, such as log entries
+    Low,
+    /// Messages a user is expecting, such as reminders
+    Normal,
+    /// Messages that need attention, such as alerts to the bot owner
+    High,
+}
+
+impl Priority {
+    const ALL: [Self; 3] = [Self::Low, Self::Normal, Self::High];
+}
+
+/// The request a queued message is sent with
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// Create a new message in a channel
+    Create(ChannelId),
+    /// Edit an existing message
+    Edit(ChannelId, MessageId),
+}
+
+impl Target {
+    async fn send(self, http: &Http, map: &Value) -> serenity::Result<ChannelMessage> {
+        match self {
+            Self::Create(chan) => http.send_message(chan.0, map).await,
+            Self::Edit(chan, msg) => http.edit_message(chan.0, msg.0, map).await,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Pending {
+    priority: Priority,
+    seq: u64,
+    target: Target,
+    map: JsonMap,
+    reply: oneshot::Sender<serenity::Result<ChannelMessage>>,
+}
+
+impl Pending {
+    /// Higher priorities first, then oldest first
+    fn key(&self) -> (Priority, Reverse<u64>) { (self.priority, Reverse(self.seq)) }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool { self.key() == other.key() }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.key().cmp(&other.key()) }
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    heap: std::sync::Mutex<BinaryHeap<Pending>>,
+    notify: Notify,
+    seq: AtomicU64,
+}
+
+/// A prioritized queue for messages sent or edited outside of an interaction
+/// response
+///
+/// Messages are sent one at a time by a single background task, so a burst
+/// from a background feature can't starve interaction responses of requests.
+/// Serenity's HTTP client already waits out global and per-route rate limits;
+/// this queue additionally retries server errors and dropped connections with
+/// exponential backoff.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    queue: Arc<Queue>,
+    started: AtomicBool,
+}
+
+impl Outbox {
+    /// Queue a message to be sent, waiting until it has been sent
+    ///
+    /// # Errors
+    /// This method returns an error if the message could not be sent after
+    /// retrying.
+    pub async fn send<'a>(
+        &self,
+        ctx: &Context,
+        chan: ChannelId,
+        priority: Priority,
+        f: impl for<'b> FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+    ) -> Result<ChannelMessage> {
+        let mut msg = CreateMessage::default();
+        f(&mut msg);
+        ensure!(msg.2.is_empty(), "Attachments cannot be sent through the outbox");
+
+        self.enqueue(ctx, Target::Create(chan), priority, json::hashmap_to_json_map(msg.0))
+            .await
+    }
+
+    /// Queue an edit to an existing message, waiting until it has been sent
+    ///
+    /// # Errors
+    /// This method returns an error if the edit could not be sent after
+    /// retrying.
+    pub async fn edit<'a>(
+        &self,
+        ctx: &Context,
+        chan: ChannelId,
+        id: MessageId,
+        priority: Priority,
+        f: impl for<'b> FnOnce(&'b mut EditMessage<'a>) -> &'b mut EditMessage<'a>,
+    ) -> Result<ChannelMessage> {
+        let mut msg = EditMessage::default();
+        f(&mut msg);
+        ensure!(msg.1.is_empty(), "Attachments cannot be sent through the outbox");
+
+        self.enqueue(ctx, Target::Edit(chan, id), priority, json::hashmap_to_json_map(msg.0))
+            .await
+    }
+
+    async fn enqueue(
+        &self,
+        ctx: &Context,
+        target: Target,
+        priority: Priority,
+        map: JsonMap,
+    ) -> Result<ChannelMessage> {
+        if !self.started.swap(true, Ordering::SeqCst) {
+            tokio::task::spawn(
+                run(Arc::clone(&ctx.http), Arc::clone(&self.queue))
+                    .instrument(info_span!(parent: None, "outbox")),
+            );
+        }
+
+        let (reply, rx) = oneshot::channel();
+        let depth = {
+            let mut heap = self.queue.heap.lock().unwrap();
+            heap.push(Pending {
+                priority,
+                seq: self.queue.seq.fetch_add(1, Ordering::Relaxed),
+                target,
+                map,
+                reply,
+            });
+            heap.len()
+        };
+        self.queue.notify.notify_one();
+        trace!(?priority, depth, "Message queued");
+
+        rx.await
+            .context("Outbox worker stopped")?
+            .context("Error sending queued message")
+    }
+
+    /// Get the number of messages waiting to be sent at each priority
+    #[must_use]
+    pub fn depth(&self) -> Vec<(Priority, usize)> {
+        let heap = self.queue.heap.lock().unwrap();
+
+        Priority::ALL
+            .into_iter()
+            .map(|p| (p, heap.iter().filter(|m| m.priority == p).count()))
+            .collect()
+    }
+}
+
+async fn run(http: Arc<Http>, queue: Arc<Queue>) {
+    loop {
+        let next = queue.heap.lock().unwrap().pop();
+        let Some(Pending {
+            priority,
+            target,
+            map,
+            reply,
+            ..
+        }) = next
+        else {
+            queue.notify.notified().await;
+            continue;
+        };
+
+        let res = send_retrying(&http, target, &Value::from(map)).await;
+        if let Err(ref err) = res {
+            warn!(?priority, ?target, %err, "Queued message failed");
+        }
+
+        // The sender may have given up waiting, which is fine
+        reply.send(res).ok();
+    }
+}
+
+async fn send_retrying(
+    http: &Http,
+    target: Target,
+    map: &Value,
+) -> serenity::Result<ChannelMessage> {
+    let mut delay = RETRY_DELAY;
+
+    for _ in 0..MAX_RETRIES {
+        match target.send(http, map).await {
+            Err(err) if is_transient(&err) => {
+                debug!(%err, ?delay, "Transient error sending message, retrying");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            },
+            res => return res,
+        }
+    }
+
+    target.send(http, map).await
+}
+
+fn is_transient(err: &serenity::Error) -> bool {
+    let serenity::Error::Http(err) = err else {
+        return false;
+    };
+
+    match **err {
+        HttpError::UnsuccessfulRequest(ref res) => {
+            res.status_code.is_server_error() || res.status_code.as_u16() == 429
+        },
+        HttpError::Request(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BinaryHeap;
+
+    use serenity::{
+        http::{
+            error::{DiscordJsonError, ErrorResponse},
+            HttpError, StatusCode,
+        },
+        model::id::ChannelId,
+    };
+    use tokio::sync::oneshot;
+
+    use super::{is_transient, Pending, Priority, Target};
+
+    fn pending(priority: Priority, seq: u64) -> Pending {
+        Pending {
+            priority,
+            seq,
+            target: Target::Create(ChannelId(1)),
+            map: serenity::json::JsonMap::new(),
+            reply: oneshot::channel().0,
+        }
+    }
+
+    fn status(code: u16) -> serenity::Error {
+        HttpError::UnsuccessfulRequest(ErrorResponse {
+            status_code: StatusCode::from_u16(code).unwrap(),
+            url: "https://discord.com/api/v10".parse().unwrap(),
+            error: serde_json::from_str::<DiscordJsonError>(r#"{"code":0,"message":""}"#)
+                .unwrap(),
+        })
+        .into()
+    }
+
+    #[test]
+    fn test_order() {
+        let mut heap: BinaryHeap<_> = [
+            (Priority::Low, 0),
+            (Priority::Normal, 1),
+            (Priority::High, 2),
+            (Priority::Normal, 3),
+            (Priority::Low, 4),
+            (Priority::High, 5),
+        ]
+        .into_iter()
+        .map(|(p, s)| pending(p, s))
+        .collect();
+
+        let order: Vec<_> = std::iter::from_fn(|| heap.pop())
+            .map(|p| (p.priority, p.seq))
+            .collect();
+        assert_eq!(order, [
+            (Priority::High, 2),
+            (Priority::High, 5),
+            (Priority::Normal, 1),
+            (Priority::Normal, 3),
+            (Priority::Low, 0),
+            (Priority::Low, 4),
+        ]);
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&status(500)));
+        assert!(is_transient(&status(503)));
+        assert!(is_transient(&status(429)));
+        assert!(!is_transient(&status(400)));
+        assert!(!is_transient(&status(403)));
+        assert!(!is_transient(&status(404)));
+        assert!(!is_transient(&serenity::Error::Other("oops")));
+    }
+}
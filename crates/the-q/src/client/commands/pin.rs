@@ -0,0 +1,629 @@
+use std::time::Duration;
+
+use paracord::markup::MessageBuilderExt;
+use qcore::markup::MaskedLink;
+use response::page::{self, Nav, NavButton, PageState, View};
+use serenity::{
+    builder::CreateEmbed,
+    model::{
+        channel::Message as ChannelMessage,
+        id::{ChannelId, MessageId},
+        Permissions,
+    },
+};
+
+use super::prelude::*;
+
+/// Discord's limit on the number of pinned messages in a single channel
+const MAX_PINS: usize = 50;
+
+/// Number of archived pins shown on each page of the archive browser
+const ARCHIVE_PAGE_LEN: usize = 10;
+
+/// Number of the most recent archived pins loaded by the archive browser
+const ARCHIVE_FETCH_LIMIT: u64 = 100;
+
+/// Maximum length of the excerpt shown for each archived pin
+const EXCERPT_LEN: usize = 80;
+
+/// How long the archive browser can be navigated for
+const ARCHIVE_NAV_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Parse a message link or ID, returning the channel ID if a link was given
+fn parse_target(s: &str) -> Option<(Option<ChannelId>, MessageId)> {
+    let mut segments = s.trim().rsplit('/');
+    let msg = segments.next()?.parse().ok().map(MessageId)?;
+    let chan = match segments.next() {
+        Some(c) => Some(c.parse().ok().map(ChannelId)?),
+        None => None,
+    };
+
+    Some((chan, msg))
+}
+
+fn quote_embed<'a>(e: &'a mut CreateEmbed, msg: &ChannelMessage) -> &'a mut CreateEmbed {
+    e.author(|a| a.name(&msg.author.name).icon_url(msg.author.face()))
+        .description(&msg.content)
+        .url(msg.link())
+        .timestamp(msg.timestamp);
+
+    if let Some(img) = msg.attachments.iter().find(|a| {
+        a.content_type
+            .as_ref()
+            .map_or(false, |t| t.starts_with("image/"))
+    }) {
+        e.image(&img.url);
+    }
+
+    e
+}
+
+async fn find_archive(
+    ctx: &Context,
+    res: &visitor::Resolver,
+    gid: GuildId,
+    name: &str,
+) -> Result<Option<ChannelId>> {
+    let chan = res
+        .channels(ctx, gid)
+        .await
+        .context("Error listing guild channels")?
+        .iter()
+        .find_map(|(&id, c)| (c.name == name).then_some(id));
+
+    Ok(chan)
+}
+
+/// Pin or unpin a message, archiving the oldest pin if the channel is full
+///
+/// Returns a message describing the outcome to the user, and whether the
+/// message was actually pinned or unpinned.
+async fn set_pinned(
+    ctx: &Context,
+    res: &visitor::Resolver,
+    gid: GuildId,
+    chan: ChannelId,
+    msg: MessageId,
+    archive: &str,
+    pin: bool,
+) -> Result<(MessageBody, bool)> {
+    let pins = chan.pins(&ctx.http).await.context("Error listing pins")?;
+    let pinned = pins.iter().any(|m| m.id == msg);
+
+    if !pin {
+        if !pinned {
+            return Ok((MessageBody::plain("That message isn't pinned."), false));
+        }
+
+        chan.unpin(&ctx.http, msg)
+            .await
+            .context("Error unpinning message")?;
+        return Ok((MessageBody::plain("Message unpinned."), true));
+    }
+
+    if pinned {
+        return Ok((MessageBody::plain("That message is already pinned."), false));
+    }
+
+    // Pins are listed newest-first
+    let mut archived = None;
+    if let Some(oldest) = pins.get(MAX_PINS - 1) {
+        let Some(archive_chan) = find_archive(ctx, res, gid, archive).await? else {
+            return Ok((
+                MessageBody::rich(|b| {
+                    b.push("This channel is out of pins, and there's no ")
+                        .push_mono_safe(format!("#{archive}"))
+                        .push(" channel to archive old pins to.")
+                }),
+                false,
+            ));
+        };
+
+        archive_chan
+            .send_message(&ctx.http, |m| {
+                m.content(format!("Pinned in <#{chan}>"))
+                    .embed(|e| quote_embed(e, oldest))
+            })
+            .await
+            .context("Error archiving oldest pin")?;
+        chan.unpin(&ctx.http, oldest.id)
+            .await
+            .context("Error unpinning oldest pin")?;
+        archived = Some(archive_chan);
+    }
+
+    chan.pin(&ctx.http, msg)
+        .await
+        .context("Error pinning message")?;
+
+    let body = match archived {
+        Some(a) => MessageBody::rich(|b| {
+            b.push("Message pinned. The oldest pin was moved to ")
+                .channel(a)
+                .push(".")
+        }),
+        None => MessageBody::plain("Message pinned."),
+    };
+
+    Ok((body, true))
+}
+
+/// Reverses a pin or unpin
+///
+/// Undoing a pin does not restore a pin that was archived to make room for it.
+#[derive(Debug)]
+struct PinUndo {
+    chan: ChannelId,
+    msg: MessageId,
+    archive: String,
+    pinned: bool,
+}
+
+#[async_trait]
+impl Undo for PinUndo {
+    fn describe(&self) -> String {
+        let Self { chan, msg, .. } = self;
+        let action = if self.pinned { "pinned" } else { "unpinned" };
+        format!("{action} <#{chan}> message {msg}")
+    }
+
+    fn permissions(&self) -> Permissions { Permissions::MANAGE_MESSAGES }
+
+    async fn undo(
+        &self,
+        ctx: &Context,
+        res: &visitor::Resolver,
+        gid: GuildId,
+    ) -> Result<MessageBody> {
+        let Self {
+            chan,
+            msg,
+            ref archive,
+            pinned,
+        } = *self;
+        let (body, _) = set_pinned(ctx, res, gid, chan, msg, archive, !pinned).await?;
+        Ok(body)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn respond_pin<'a>(
+    ctx: &Context,
+    visitor: &mut CommandVisitor<'_>,
+    responder: CommandResponder<'_, 'a>,
+    target: Option<MessageId>,
+    archive: &str,
+    pin: bool,
+    log: &GuildLog,
+    undo: &UndoLog,
+) -> CommandResult<'a> {
+    let (gid, memb) = visitor.member()?;
+    let chan = visitor.channel();
+    let user = visitor.user();
+
+    if !memb
+        .permissions
+        .map_or(false, Permissions::manage_messages)
+    {
+        return Err(responder
+            .create_message(
+                Message::plain("You need the Manage Messages permission to do that.")
+                    .ephemeral(true),
+            )
+            .await
+            .context("Error sending permission error")?
+            .into_err("User cannot manage messages"));
+    }
+
+    let Some(target) = target else {
+        return Err(responder
+            .create_message(Message::plain("That isn't a valid message link.").ephemeral(true))
+            .await
+            .context("Error sending message ID error")?
+            .into_err("Invalid message ID"));
+    };
+
+    let responder = responder
+        .defer_message(MessageOpts::default().ephemeral(true))
+        .await
+        .context("Error sending deferred message")?;
+
+    let (mut msg, changed) =
+        set_pinned(ctx, visitor.resolver(), gid, chan, target, archive, pin).await?;
+
+    if changed {
+        let action = if pin { "pinned" } else { "unpinned" };
+        log.log(
+            ctx,
+            gid,
+            format!("{} {action} https://discord.com/channels/{gid}/{chan}/{target}", user.tag()),
+        )
+        .await;
+
+        msg = msg.row(
+            undo.push(gid, user.id, PinUndo {
+                chan,
+                msg: target,
+                archive: archive.into(),
+                pinned: pin,
+            })
+            .await?,
+        );
+    }
+
+    responder
+        .edit(msg)
+        .await
+        .context("Error updating deferred response")?;
+
+    Ok(responder.into())
+}
+
+#[derive(Debug)]
+pub struct PinCommand {
+    name: String,
+    namespace: String,
+    archive: String,
+    pin: bool,
+    log: Arc<GuildLog>,
+    undo: Arc<UndoLog>,
+}
+
+impl PinCommand {
+    pub fn pin(opts: &CommandOpts, log: Arc<GuildLog>, undo: Arc<UndoLog>) -> Self {
+        Self {
+            name: "pin".into(),
+            namespace: opts.command_base.clone(),
+            archive: opts.pin_archive_channel.clone(),
+            pin: true,
+            log,
+            undo,
+        }
+    }
+
+    pub fn unpin(opts: &CommandOpts, log: Arc<GuildLog>, undo: Arc<UndoLog>) -> Self {
+        Self {
+            name: "unpin".into(),
+            pin: false,
+            ..Self::pin(opts, log, undo)
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<Schema> for PinCommand {
+    fn register_global(&self) -> CommandInfo {
+        let desc = if self.pin {
+            "Pin a message, archiving the oldest pin if needed"
+        } else {
+            "Unpin a message"
+        };
+
+        CommandInfo::build_slash(&self.name, desc, |a| {
+            a.string("message", "A link to or ID of a message in this channel", true, ..)
+        })
+        .unwrap()
+        .in_namespace(&self.namespace)
+    }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+    ) -> CommandResult<'a> {
+        let message = visitor.visit_string("message")?.required()?;
+        let target = parse_target(message);
+
+        if matches!(target, Some((Some(c), _)) if c != visitor.channel()) {
+            return Err(responder
+                .create_message(
+                    Message::plain("That message is in a different channel.").ephemeral(true),
+                )
+                .await
+                .context("Error sending channel mismatch error")?
+                .into_err("Message link points to another channel"));
+        }
+
+        respond_pin(
+            ctx,
+            visitor,
+            responder,
+            target.map(|(_, m)| m),
+            &self.archive,
+            self.pin,
+            &self.log,
+            &self.undo,
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct PinMessageCommand {
+    name: String,
+    archive: String,
+    pin: bool,
+    log: Arc<GuildLog>,
+    undo: Arc<UndoLog>,
+}
+
+impl PinMessageCommand {
+    pub fn pin(opts: &CommandOpts, log: Arc<GuildLog>, undo: Arc<UndoLog>) -> Self {
+        Self {
+            name: format!("{}Pin Message", opts.context_menu_base),
+            archive: opts.pin_archive_channel.clone(),
+            pin: true,
+            log,
+            undo,
+        }
+    }
+
+    pub fn unpin(opts: &CommandOpts, log: Arc<GuildLog>, undo: Arc<UndoLog>) -> Self {
+        Self {
+            name: format!("{}Unpin Message", opts.context_menu_base),
+            pin: false,
+            ..Self::pin(opts, log, undo)
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<Schema> for PinMessageCommand {
+    fn register_global(&self) -> CommandInfo { CommandInfo::message(&self.name) }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+    ) -> CommandResult<'a> {
+        let target = visitor.target().message()?.id;
+
+        respond_pin(
+            ctx,
+            visitor,
+            responder,
+            Some(target),
+            &self.archive,
+            self.pin,
+            &self.log,
+            &self.undo,
+        )
+        .await
+    }
+}
+
+/// A pin archived by [`set_pinned`], as shown in the archive browser
+#[derive(Debug)]
+struct ArchivedPin {
+    author: String,
+    source: Option<ChannelId>,
+    excerpt: String,
+    link: String,
+}
+
+impl ArchivedPin {
+    fn from_message(msg: &ChannelMessage) -> Option<Self> {
+        let embed = msg.embeds.first()?;
+        let source = msg
+            .content
+            .strip_prefix("Pinned in <#")
+            .and_then(|s| s.strip_suffix('>'))
+            .and_then(|s| s.parse().ok())
+            .map(ChannelId);
+        let description = embed.description.as_deref().unwrap_or_default();
+        let line = description.lines().next().unwrap_or_default();
+        let mut excerpt: String = line.chars().take(EXCERPT_LEN).collect();
+        if excerpt.len() < description.len() {
+            excerpt.push('…');
+        }
+
+        Some(Self {
+            author: embed
+                .author
+                .as_ref()
+                .map_or_else(|| "Unknown".into(), |a| a.name.clone()),
+            source,
+            excerpt,
+            link: msg.link(),
+        })
+    }
+}
+
+/// The most recent pins in a guild's pin archive, split into pages
+#[derive(Debug)]
+struct ArchiveView {
+    archive: ChannelId,
+    pins: Vec<ArchivedPin>,
+}
+
+impl ArchiveView {
+    async fn fetch(ctx: &Context, archive: ChannelId) -> Result<Self> {
+        let me = ctx.cache.current_user_id();
+        let pins = archive
+            .messages(&ctx.http, |m| m.limit(ARCHIVE_FETCH_LIMIT))
+            .await
+            .context("Error listing archived pins")?
+            .iter()
+            .filter(|m| m.author.id == me)
+            .filter_map(ArchivedPin::from_message)
+            .collect();
+
+        Ok(Self { archive, pins })
+    }
+}
+
+impl View<component::Component> for ArchiveView {
+    fn pages(&self) -> u32 {
+        let pages = (self.pins.len() + ARCHIVE_PAGE_LEN - 1) / ARCHIVE_PAGE_LEN;
+        pages.max(1).try_into().unwrap_or(u32::MAX)
+    }
+
+    fn render(&self, page: u32) -> MessageBody {
+        if self.pins.is_empty() {
+            return MessageBody::rich(|b| {
+                b.push("There are no archived pins in ")
+                    .channel(self.archive)
+                    .push(".")
+            });
+        }
+
+        let start = usize::try_from(page).unwrap_or(usize::MAX).saturating_mul(ARCHIVE_PAGE_LEN);
+        MessageBody::rich(|b| {
+            b.push("Pins archived in ")
+                .channel(self.archive)
+                .push(format!(" (page {} of {}):", page + 1, self.pages()));
+
+            for (i, pin) in self.pins.iter().enumerate().skip(start).take(ARCHIVE_PAGE_LEN) {
+                b.push(format!("\n{}. ", i + 1)).push_bold_safe(&pin.author);
+                if let Some(source) = pin.source {
+                    b.push(" in ").channel(source);
+                }
+                b.push(": ").push_safe(&pin.excerpt).push(" ");
+                match MaskedLink::new("jump", &pin.link) {
+                    Ok(l) => b.masked_link(l),
+                    Err(_) => b.push(&pin.link),
+                };
+            }
+
+            b
+        })
+    }
+
+    fn payload(&self, nav: Nav) -> ComponentPayload {
+        let button = match nav.button {
+            NavButton::First => component::PageButton::First,
+            NavButton::Prev => component::PageButton::Prev,
+            NavButton::Next => component::PageButton::Next,
+            NavButton::Last => component::PageButton::Last,
+        };
+
+        ComponentPayload::PinArchive(component::PinArchive {
+            page: nav.state.page,
+            expires: nav.state.expires.unwrap_or(0),
+            button: button.into(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PinArchiveCommand {
+    name: String,
+    namespace: String,
+    archive: String,
+}
+
+impl From<&CommandOpts> for PinArchiveCommand {
+    fn from(opts: &CommandOpts) -> Self {
+        Self {
+            name: "pins".into(),
+            namespace: opts.command_base.clone(),
+            archive: opts.pin_archive_channel.clone(),
+        }
+    }
+}
+
+impl PinArchiveCommand {
+    /// Load the archive view for a guild, or a message explaining why there
+    /// isn't one
+    async fn view(
+        &self,
+        ctx: &Context,
+        res: &visitor::Resolver,
+        gid: GuildId,
+    ) -> Result<Result<ArchiveView, MessageBody>> {
+        let Some(archive) = find_archive(ctx, res, gid, &self.archive).await? else {
+            let archive = &self.archive;
+            return Ok(Err(MessageBody::rich(|b| {
+                b.push("There's no ")
+                    .push_mono_safe(format!("#{archive}"))
+                    .push(" channel to browse.")
+            })));
+        };
+
+        ArchiveView::fetch(ctx, archive).await.map(Ok)
+    }
+}
+
+#[async_trait]
+impl CommandHandler<Schema> for PinArchiveCommand {
+    fn register_global(&self) -> CommandInfo {
+        CommandInfo::build_slash(&self.name, "Browse pins moved to the pin archive", |a| a)
+            .unwrap()
+            .in_namespace(&self.namespace)
+    }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+    ) -> CommandResult<'a> {
+        let (gid, _memb) = visitor.member()?;
+        let state = PageState::new(Some(ARCHIVE_NAV_TIMEOUT));
+        let body = match self.view(ctx, visitor.resolver(), gid).await? {
+            Ok(view) => page::render(&view, state),
+            Err(body) => body,
+        };
+
+        let responder = responder
+            .create_message(Message::from(body).ephemeral(true))
+            .await
+            .context("Error sending pin archive")?;
+        page::expire(Arc::clone(&ctx.http), &responder, state);
+
+        Ok(responder.into())
+    }
+}
+
+#[async_trait]
+impl RpcHandler<Schema, ComponentKey> for PinArchiveCommand {
+    fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::PinArchive] }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        payload: ComponentPayload,
+        visitor: &mut ComponentVisitor<'_>,
+        responder: ComponentResponder<'_, 'a>,
+    ) -> ComponentResult<'a> {
+        let ComponentPayload::PinArchive(component::PinArchive {
+            page,
+            expires,
+            button,
+        }) = payload
+        else {
+            unreachable!();
+        };
+        let button = match component::PageButton::from_i32(button) {
+            Some(component::PageButton::First) => NavButton::First,
+            Some(component::PageButton::Prev) => NavButton::Prev,
+            Some(component::PageButton::Next) => NavButton::Next,
+            Some(component::PageButton::Last) => NavButton::Last,
+            Some(component::PageButton::Unknown) | None => {
+                return Err(anyhow!("Invalid page button {button:?}").into());
+            },
+        };
+        let nav = Nav {
+            state: PageState {
+                page,
+                expires: (expires != 0).then_some(expires),
+            },
+            button,
+        };
+        let (gid, _memb) = visitor.member()?;
+
+        let responder = match self.view(ctx, visitor.resolver(), gid).await? {
+            Ok(view) => page::navigate(&view, nav, responder).await,
+            Err(body) => {
+                responder
+                    .create_message(Message::from(body).ephemeral(true))
+                    .await
+            },
+        }
+        .context("Error updating pin archive")?;
+
+        Ok(responder.into())
+    }
+}
@@ -25,7 +25,7 @@ impl CommandHandler<Schema> for ReCommand {
         visitor: &mut CommandVisitor<'_>,
         responder: CommandResponder<'_, 'a>,
     ) -> CommandResult<'a> {
-        let target = visitor.target().message()?;
+        let _target = visitor.target().message()?;
 
         let msg = {
             let re = Regex::Cat(vec![
@@ -56,7 +56,9 @@ impl CommandHandler<Schema> for ReCommand {
             let atomized_dfa = compiled_dfa.atomize_nodes::<u32>();
             debug!("{atomized_dfa:#?}");
 
-            Message::rich(|m| m.push_codeblock_safe(format!("{atomized_dfa:?}"), None))
+            shareable(Message::rich(|m| {
+                m.push_codeblock_safe(format!("{atomized_dfa:?}"), None)
+            }))
         };
 
         let responder = responder
@@ -36,6 +36,14 @@ impl rpc::ComponentId for component::Component {
 pub enum ComponentKey {
     Role,
     Soundboard,
+    Share,
+    SoundControl,
+    Undo,
+    SoundPost,
+    Giveaway,
+    PinArchive,
+    Health,
+    Payloads,
 }
 
 impl From<&ComponentPayload> for ComponentKey {
@@ -43,6 +51,14 @@ impl From<&ComponentPayload> for ComponentKey {
         match value {
             ComponentPayload::Role(_) => Self::Role,
             ComponentPayload::Soundboard(_) => Self::Soundboard,
+            ComponentPayload::Share(_) => Self::Share,
+            ComponentPayload::SoundControl(_) => Self::SoundControl,
+            ComponentPayload::Undo(_) => Self::Undo,
+            ComponentPayload::SoundPost(_) => Self::SoundPost,
+            ComponentPayload::Giveaway(_) => Self::Giveaway,
+            ComponentPayload::PinArchive(_) => Self::PinArchive,
+            ComponentPayload::Health(_) => Self::Health,
+            ComponentPayload::Payloads(_) => Self::Payloads,
         }
     }
 }
@@ -77,6 +93,35 @@ impl rpc::ModalId for modal::Modal {
 impl rpc::Key for ComponentKey {
     type Interaction = MessageComponentInteraction;
     type Payload = ComponentPayload;
+
+    fn label(payload: &ComponentPayload) -> rpc::Label {
+        match payload {
+            ComponentPayload::Role(_) => rpc::Label::new("role"),
+            ComponentPayload::Soundboard(_) => rpc::Label::new("soundboard").variant("play"),
+            ComponentPayload::Share(_) => rpc::Label::new("share"),
+            ComponentPayload::SoundControl(c) => {
+                let action = match component::SoundAction::from_i32(c.action) {
+                    Some(component::SoundAction::Skip) => "skip",
+                    Some(component::SoundAction::Stop) => "stop",
+                    Some(component::SoundAction::Unknown) | None => "unknown",
+                };
+                rpc::Label::new("sound_control").variant(action)
+            },
+            ComponentPayload::Undo(_) => rpc::Label::new("undo"),
+            ComponentPayload::SoundPost(_) => rpc::Label::new("soundboard").variant("post"),
+            ComponentPayload::Giveaway(g) => {
+                let action = match component::GiveawayAction::from_i32(g.action) {
+                    Some(component::GiveawayAction::Enter) => "enter",
+                    Some(component::GiveawayAction::Reroll) => "reroll",
+                    Some(component::GiveawayAction::Unknown) | None => "unknown",
+                };
+                rpc::Label::new("giveaway").variant(action)
+            },
+            ComponentPayload::PinArchive(_) => rpc::Label::new("pin_archive"),
+            ComponentPayload::Health(_) => rpc::Label::new("health"),
+            ComponentPayload::Payloads(_) => rpc::Label::new("payloads"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -3,12 +3,14 @@ use super::prelude::*;
 #[derive(Debug)]
 pub struct SayCommand {
     name: String,
+    namespace: String,
 }
 
 impl From<&CommandOpts> for SayCommand {
     fn from(opts: &CommandOpts) -> Self {
         Self {
-            name: format!("{}say", opts.command_base),
+            name: "say".into(),
+            namespace: opts.command_base.clone(),
         }
     }
 }
@@ -20,6 +22,7 @@ impl CommandHandler<Schema> for SayCommand {
             a.string("message", "The message to send", true, ..)
         })
         .unwrap()
+        .in_namespace(&self.namespace)
     }
 
     async fn respond<'a>(
@@ -29,9 +32,10 @@ impl CommandHandler<Schema> for SayCommand {
         responder: CommandResponder<'_, 'a>,
     ) -> CommandResult<'a> {
         let msg = visitor.visit_string("message")?.required()?;
-        let guild = visitor.guild()?.optional();
-
-        let color = guild.and_then(|(_, m)| m.colour(&ctx.cache));
+        let color = visitor
+            .invoker()?
+            .member()
+            .and_then(|m| m.colour(&ctx.cache));
 
         Ok(responder
             .create_message(Embed::default().desc_plain(msg).color_opt(color).into())
@@ -0,0 +1,122 @@
+use serenity::{builder::CreateEmbed, model::Permissions, utils::MessageBuilder};
+
+use super::prelude::*;
+
+/// Discord's limit on the length of message content
+const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Make a message ephemeral and attach a button allowing the invoking user to
+/// repost it publicly
+///
+/// Only the content and embeds of the message are reposted, under a line
+/// naming the user who shared it.
+pub fn shareable(
+    msg: Message<'_, component::Component, response::id::Error>,
+) -> Message<'_, component::Component, response::id::Error> {
+    msg.ephemeral(true).build_row(|r| {
+        r.button(
+            ComponentPayload::Share(component::Share {}),
+            ButtonStyle::Secondary,
+            "Share",
+            false,
+        )
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct ShareComponent;
+
+#[async_trait]
+impl RpcHandler<Schema, ComponentKey> for ShareComponent {
+    fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::Share] }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        payload: ComponentPayload,
+        visitor: &mut ComponentVisitor<'_>,
+        responder: ComponentResponder<'_, 'a>,
+    ) -> ComponentResult<'a> {
+        let ComponentPayload::Share(component::Share {}) = payload else {
+            unreachable!();
+        };
+        let (_gid, memb) = visitor.member()?;
+
+        if !memb
+            .permissions
+            .map_or(false, Permissions::send_messages)
+        {
+            return Err(responder
+                .create_message(
+                    Message::plain("You don't have permission to post in this channel.")
+                        .ephemeral(true),
+                )
+                .await
+                .context("Error sending permission error")?
+                .into_err("User cannot send messages"));
+        }
+
+        let msg = visitor.message();
+        let content = attribute(visitor.user(), &msg.content);
+        visitor
+            .channel()
+            .send_message(&ctx.http, |m| {
+                m.content(content)
+                    .set_embeds(msg.embeds.iter().cloned().map(CreateEmbed::from).collect())
+                    .allowed_mentions(|a| a.empty_parse())
+            })
+            .await
+            .context("Error reposting shared message")?;
+
+        let responder = responder
+            .update_message(Message::plain("Shared to the channel."))
+            .await
+            .context("Error updating shared message")?;
+
+        Ok(responder.into())
+    }
+}
+
+/// Prefix shared content with a mention of the user who shared it,
+/// truncating the content if the result would be too long
+fn attribute(user: &User, content: &str) -> String {
+    let mut out = MessageBuilder::new()
+        .push("Shared by ")
+        .mention(user)
+        .push(":")
+        .build();
+
+    if !content.is_empty() {
+        out.push('\n');
+        let room = MAX_MESSAGE_LEN - out.chars().count();
+        if content.chars().count() > room {
+            out.extend(content.chars().take(room - 1));
+            out.push('…');
+        } else {
+            out.push_str(content);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::{id::UserId, user::User};
+
+    use super::{attribute, MAX_MESSAGE_LEN};
+
+    #[test]
+    fn test_attribute() {
+        let mut user = User::default();
+        user.id = UserId(42);
+
+        assert_eq!(attribute(&user, ""), "Shared by <@42>:");
+        assert_eq!(attribute(&user, "hi"), "Shared by <@42>:\nhi");
+
+        let long = attribute(&user, &"x".repeat(MAX_MESSAGE_LEN));
+        assert_eq!(long.chars().count(), MAX_MESSAGE_LEN);
+        assert!(long.starts_with("Shared by <@42>:\nxx"));
+        assert!(long.ends_with('…'));
+    }
+}
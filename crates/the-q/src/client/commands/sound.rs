@@ -1,39 +1,169 @@
-use std::{collections::BinaryHeap, path::PathBuf};
+use std::{
+    collections::BinaryHeap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use ordered_float::OrderedFloat;
+use paracord::interaction::completion::{History, RankTarget, Ranking};
+use serenity::model::{
+    application::component::ActionRowComponent,
+    channel::{ChannelType, Message as ChannelMessage},
+    id::{ChannelId, MessageId},
+};
 use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
 
-use super::prelude::*;
+use super::{
+    prelude::*,
+    voice::{JoinError, VoiceSession, VoiceSessions},
+};
 
-// TODO: make this configurable
-const SAMPLE_DIR: &str = "etc/samples";
+/// Subdirectory of the sample directory holding per-guild sounds
+const GUILD_DIR: &str = "guilds";
+
+const PATH_ERR: &str = "That isn't a valid file.";
+
+/// Content of the bot's soundboard channel messages, used to tell them apart
+/// from anything else it has posted there
+const BOARD_HEADER: &str = "**Soundboard**";
+
+/// Number of recent messages to search for the soundboard channel's buttons
+const BOARD_HISTORY: u64 = 100;
+
+/// Maximum number of buttons Discord allows on a single message
+const BOARD_MSG_BUTTONS: usize = 25;
+
+/// Maximum length of a button label, which holds the file name
+const BOARD_LABEL_LEN: usize = 80;
+
+/// Time for a played sound to lose half its weight in completion rankings
+const HISTORY_HALF_LIFE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// Discord's limit on the number of choices in a completion list
+const MAX_COMPLETIONS: usize = 25;
 
 #[derive(Debug)]
 struct FileMap {
-    files: RwLock<HashMap<String, PathBuf>>,
+    files: RwLock<Library>,
     _task_handle: oneshot::Sender<Infallible>,
 }
 
+/// The sounds in the sample directory, split into global and per-guild sounds
+#[derive(Debug, Default)]
+struct Library {
+    global: HashMap<String, PathBuf>,
+    guilds: HashMap<GuildId, HashMap<String, PathBuf>>,
+}
+
+impl Library {
+    fn insert(&mut self, name: String, path: PathBuf) {
+        let guild = name
+            .strip_prefix(GUILD_DIR)
+            .and_then(|n| n.strip_prefix('/'))
+            .and_then(|n| n.split_once('/'))
+            .and_then(|(id, n)| Some((GuildId(id.parse().ok()?), n)));
+
+        match guild {
+            Some((gid, name)) => {
+                self.guilds.entry(gid).or_default().insert(name.into(), path);
+            },
+            None => {
+                self.global.insert(name, path);
+            },
+        }
+    }
+
+    fn guild(&self, gid: Option<GuildId>) -> Option<&HashMap<String, PathBuf>> {
+        gid.and_then(|g| self.guilds.get(&g))
+    }
+
+    /// Look up a sound by name, preferring the guild's own sounds
+    fn get(&self, gid: Option<GuildId>, name: &str) -> Option<&PathBuf> {
+        self.guild(gid)
+            .and_then(|g| g.get(name))
+            .or_else(|| self.global.get(name))
+    }
+
+    /// List the names of every sound available in a guild
+    fn names(&self, gid: Option<GuildId>) -> impl Iterator<Item = &String> {
+        let guild = self.guild(gid);
+
+        guild.into_iter().flat_map(HashMap::keys).chain(
+            self.global
+                .keys()
+                .filter(move |n| !guild.map_or(false, |g| g.contains_key(*n))),
+        )
+    }
+}
+
+#[derive(Debug, CommandArgs)]
+enum SoundArgs<'a> {
+    /// Play a single file
+    #[arg(example = "path:BUDDY.flac")]
+    Play {
+        /// Path to the file to play
+        #[arg(autocomplete)]
+        path: &'a str,
+    },
+    /// Create a soundboard message
+    Board,
+    /// List the sounds waiting to play
+    Queue,
+    /// Show the sound currently playing
+    #[arg(rename = "nowplaying")]
+    NowPlaying,
+    /// Skip the sound currently playing
+    Skip,
+    /// Stop playback and clear the queue
+    Stop,
+    /// Remove every sound waiting to play
+    Clear,
+    /// Set the playback volume
+    #[arg(example = "percent:50")]
+    Volume {
+        /// The volume to play at, as a percentage
+        #[arg(min = 0, max = 200)]
+        percent: i64,
+    },
+    /// Stop suggesting a sound you've played before
+    #[arg(example = "path:BUDDY.flac")]
+    Forget {
+        /// A sound from your play history
+        #[arg(autocomplete)]
+        path: &'a str,
+    },
+}
+
 #[derive(Debug)]
 pub struct SoundCommand {
     name: String,
+    namespace: String,
+    dir: PathBuf,
     files: Mutex<std::sync::Weak<FileMap>>,
-    songbird_handle: Mutex<HashMap<GuildId, std::sync::Weak<()>>>,
+    voice: Arc<VoiceSessions>,
+    board_channel: String,
+    board_max: usize,
+    board_lock: Mutex<()>,
+    history: History,
     _notify_handle: RwLock<Option<oneshot::Sender<()>>>,
 }
 
-impl From<&CommandOpts> for SoundCommand {
-    fn from(opts: &CommandOpts) -> Self {
+impl SoundCommand {
+    pub fn new(opts: &CommandOpts, voice: Arc<VoiceSessions>) -> Self {
         Self {
-            name: format!("{}sound", opts.command_base),
+            name: "sound".into(),
+            namespace: opts.command_base.clone(),
+            dir: opts.sound_dir.clone(),
             files: Mutex::default(),
-            songbird_handle: Mutex::default(),
+            voice,
+            board_channel: opts.soundboard_channel.clone(),
+            board_max: opts.soundboard_max_sounds,
+            board_lock: Mutex::default(),
+            history: History::new(HISTORY_HALF_LIFE),
             _notify_handle: RwLock::default(),
         }
     }
-}
 
-impl SoundCommand {
     async fn files(&self) -> Result<Arc<FileMap>> {
         let mut guard = self.files.lock().await;
         if let Some(files) = guard.upgrade() {
@@ -49,6 +179,7 @@ impl SoundCommand {
 
         let (ready_tx, ready_rx) = oneshot::channel();
         let map = Arc::clone(&files);
+        let dir = self.dir.clone();
         tokio::task::spawn(
             async move {
                 let mut ready_tx = Some(ready_tx);
@@ -58,6 +189,7 @@ impl SoundCommand {
                     .try_send(Ok(notify::Event::new(notify::EventKind::Any)))
                     .unwrap_or_else(|_| unreachable!());
 
+                let watch_dir = dir.clone();
                 let watcher = tokio::task::spawn_blocking(move || {
                     use notify::Watcher;
 
@@ -67,7 +199,7 @@ impl SoundCommand {
                     })
                     .context("Error creating filesystem watcher")?;
 
-                    w.watch(SAMPLE_DIR.as_ref(), notify::RecursiveMode::Recursive)?;
+                    w.watch(&watch_dir, notify::RecursiveMode::Recursive)?;
 
                     Result::<_>::Ok(w)
                 })
@@ -80,7 +212,7 @@ impl SoundCommand {
 
                         info!(?evt, "Scanning sample table...");
 
-                        let files = walkdir::WalkDir::new(SAMPLE_DIR)
+                        let files = walkdir::WalkDir::new(&dir)
                             .same_file_system(true)
                             .into_iter()
                             .filter_map(|f| {
@@ -91,15 +223,18 @@ impl SoundCommand {
                                         && f.metadata().map_or(false, |m| m.is_file()))
                                     .then(|| {
                                         let f = f.into_path();
-                                        let s = f.display().to_string();
-                                        let s = s.strip_prefix(SAMPLE_DIR).unwrap();
-                                        let s = s.strip_prefix('/').unwrap_or(s);
-                                        (s.into(), f)
+                                        let s = f.strip_prefix(&dir).unwrap().display().to_string();
+                                        (s, f)
                                     })
                                 })
                                 .transpose()
                             })
-                            .collect::<Result<_, _>>()
+                            .try_fold(Library::default(), |mut l, f| {
+                                f.map(|(s, f)| {
+                                    l.insert(s, f);
+                                    l
+                                })
+                            })
                             .context("Error enumerating files")?;
 
                         info!(?files, "Sample table scan completed");
@@ -140,33 +275,52 @@ impl SoundCommand {
         path: &str,
         extra: X,
         fail: impl FnOnce(X, MessageBody, &'static str) -> F,
-    ) -> Result<X, E> {
-        const PATH_ERR: &str = "That isn't a valid file.";
-
-        let guild = gid.to_guild_cached(&ctx.cache).context("Missing guild")?;
+    ) -> Result<(X, usize), E> {
+        let files = self.files().await.context("Error getting sample list")?;
+        let file = files.files.read().await.get(Some(gid), path).cloned();
 
-        let Some(voice_chan) = guild.voice_states.get(&user.id).and_then(|s| s.channel_id) else {
+        let Some(file) = file else {
             return Err(fail(
                 extra,
-                MessageBody::plain("Please connect to a voice channel first."),
-                "Error getting user voice state",
+                MessageBody::plain(PATH_ERR),
+                "File not in sample table",
             )
             .await);
         };
 
-        let sb = songbird::get(ctx)
-            .await
-            .context("Missing songbird context")?;
+        let ret = self
+            .play_file(ctx, gid, user, path, &file, extra, fail)
+            .await?;
 
-        let files = self.files().await.context("Error getting sample list")?;
-        let files = files.files.read().await;
-        let path = files.get(path);
+        let target = RankTarget {
+            user: user.id,
+            guild: Some(gid),
+        };
+        self.history.record(target, path);
+
+        Ok(ret)
+    }
+
+    /// Queue an arbitrary audio file in the voice channel `user` is connected
+    /// to, returning its position in the queue
+    #[allow(clippy::too_many_arguments)]
+    pub async fn play_file<'a, X, E: From<Error>, F: Future<Output = E>>(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        user: &User,
+        name: &str,
+        path: &Path,
+        extra: X,
+        fail: impl FnOnce(X, MessageBody, &'static str) -> F,
+    ) -> Result<(X, usize), E> {
+        let guild = gid.to_guild_cached(&ctx.cache).context("Missing guild")?;
 
-        let Some(path) = path else {
+        let Some(voice_chan) = guild.voice_states.get(&user.id).and_then(|s| s.channel_id) else {
             return Err(fail(
                 extra,
-                MessageBody::plain(PATH_ERR),
-                "File not in sample table",
+                MessageBody::plain("Please connect to a voice channel first."),
+                "Error getting user voice state",
             )
             .await);
         };
@@ -179,45 +333,28 @@ impl SoundCommand {
             .await
             .with_context(|| format!("Error opening sample {path:?}"))?;
 
-        let (call_lock, res) = sb.join(gid, voice_chan).await;
-
-        if let Err(err) = res {
-            warn!(?err, "Unable to join voice channel");
-            return Err(fail(
-                extra,
-                MessageBody::plain("Couldn't join that channel, sorry."),
-                "Error joining call (missing permissions?)",
-            )
-            .await);
-        }
-
-        let mut call = call_lock.lock().await;
-        let mut handles = self.songbird_handle.lock().await;
-
-        if handles
-            .get(&gid)
-            .and_then(std::sync::Weak::upgrade)
-            .is_some()
+        let (msg, err) = match self
+            .voice
+            .enqueue(ctx, gid, voice_chan, name, source)
+            .await
         {
-            return Err(fail(
-                extra,
-                MessageBody::plain("Calm down, buddy"),
-                "Sound already running",
-            )
-            .await);
-        }
-
-        let handle = Arc::new(());
-        handles.insert(gid, Arc::downgrade(&handle));
-
-        call.play_source(source)
-            .add_event(
-                songbird::Event::Track(songbird::TrackEvent::End),
-                SongbirdHandler(handle, Arc::clone(&call_lock)),
-            )
-            .context("Error hooking track stop")?;
+            Ok(pos) => return Ok((extra, pos)),
+            Err(JoinError::Busy) => (
+                "I'm already playing in another channel.",
+                "Sound running in another channel",
+            ),
+            Err(JoinError::Full) => ("Calm down, buddy", "Sound queue full"),
+            Err(JoinError::Join(err)) => {
+                warn!(?err, "Unable to join voice channel");
+                (
+                    "Couldn't join that channel, sorry.",
+                    "Error joining call (missing permissions?)",
+                )
+            },
+            Err(JoinError::Other(err)) => return Err(err.into()),
+        };
 
-        Ok(extra)
+        Err(fail(extra, MessageBody::plain(msg), err).await)
     }
 
     #[inline]
@@ -226,17 +363,17 @@ impl SoundCommand {
         ctx: &Context,
         visitor: &mut CommandVisitor<'_>,
         responder: CommandResponder<'_, 'a>,
+        path: &str,
     ) -> CommandResult<'a> {
-        let (gid, _memb) = visitor.guild()?.required()?;
+        let (gid, _memb) = visitor.member()?;
         let user = visitor.user();
-        let path = visitor.visit_string("path")?.required()?;
 
         let responder = responder
             .defer_message(MessageOpts::default().ephemeral(true))
             .await
             .context("Error sending deferred message")?;
 
-        let responder = self
+        let (responder, pos) = self
             .play_impl(ctx, gid, user, path, responder, |r, m, e| async move {
                 match r.edit(m).await.context("Error sending error message") {
                     Ok(_) => r.into_err(e),
@@ -245,14 +382,20 @@ impl SoundCommand {
             })
             .await?;
 
-        responder
-            .edit(MessageBody::plain(";)").build_row(|c| {
+        let msg = if pos == 0 {
+            MessageBody::plain(";)").build_row(|c| {
                 c.link_button(
                     Url::parse("https://youtu.be/dQw4w9WgXcQ").unwrap(),
                     "See More",
                     false,
                 )
-            }))
+            })
+        } else {
+            MessageBody::plain(format!("Queued at position {pos}."))
+        };
+
+        responder
+            .edit(msg)
             .await
             .context("Error updating deferred response")?;
 
@@ -281,21 +424,295 @@ impl SoundCommand {
 
         Ok(responder.into())
     }
+
+    /// Get the voice session for a guild, requiring that `user` be listening
+    /// to it
+    async fn session(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        user: &User,
+    ) -> Result<Arc<VoiceSession>, &'static str> {
+        let session = self
+            .voice
+            .get(gid)
+            .await
+            .ok_or("Nothing is playing right now.")?;
+
+        let user_chan = gid
+            .to_guild_cached(&ctx.cache)
+            .and_then(|g| g.voice_states.get(&user.id).and_then(|s| s.channel_id));
+
+        if user_chan.is_none() || user_chan != session.channel().await {
+            return Err("You need to be in my voice channel to do that.");
+        }
+
+        Ok(session)
+    }
+
+    async fn now_playing(&self, session: Option<&VoiceSession>) -> MessageBody {
+        let names = session.map(VoiceSession::names).unwrap_or_default();
+        let Some((current, queued)) = names.split_first() else {
+            return MessageBody::plain("Nothing is playing right now.");
+        };
+
+        // Only sounds from the sample table can be replayed from a button
+        let postable = match self.files().await {
+            Ok(f) => f
+                .files
+                .read()
+                .await
+                .get(session.map(VoiceSession::guild), current)
+                .is_some(),
+            Err(err) => {
+                warn!(?err, "Error getting sample list");
+                false
+            },
+        };
+
+        MessageBody::rich(|b| {
+            b.push("Now playing: ").push_bold_safe(current);
+            if !queued.is_empty() {
+                b.push(format!(" ({} queued)", queued.len()));
+            }
+            b
+        })
+        .build_row(|r| {
+            r.button(
+                ComponentPayload::SoundControl(component::SoundControl {
+                    action: component::SoundAction::Skip.into(),
+                }),
+                ButtonStyle::Primary,
+                "Skip",
+                false,
+            )
+            .button(
+                ComponentPayload::SoundControl(component::SoundControl {
+                    action: component::SoundAction::Stop.into(),
+                }),
+                ButtonStyle::Danger,
+                "Stop",
+                false,
+            )
+        })
+        .build_row(|r| {
+            if postable {
+                r.button(
+                    ComponentPayload::SoundPost(component::SoundPost {
+                        file: current.clone(),
+                    }),
+                    ButtonStyle::Secondary,
+                    format!("Post to #{}", self.board_channel),
+                    false,
+                )
+            } else {
+                r
+            }
+        })
+    }
+
+    /// Build a soundboard channel message with a button for each file
+    fn board_message(files: &[String]) -> MessageBody {
+        files
+            .chunks(5)
+            .fold(MessageBody::plain(BOARD_HEADER), |m, chunk| {
+                m.build_row(|r| {
+                    chunk.iter().fold(r, |r, f| {
+                        r.button(
+                            ComponentPayload::Soundboard(component::Soundboard { file: f.clone() }),
+                            ButtonStyle::Secondary,
+                            f.as_str(),
+                            false,
+                        )
+                    })
+                })
+            })
+    }
+
+    /// Read the files already posted to the soundboard channel, newest
+    /// message first
+    fn board_files(ctx: &Context, msgs: Vec<ChannelMessage>) -> Vec<(MessageId, Vec<String>)> {
+        let me = ctx.cache.current_user_id();
+
+        msgs.into_iter()
+            .filter(|m| m.author.id == me && m.content == BOARD_HEADER)
+            .map(|m| {
+                let files = m
+                    .components
+                    .into_iter()
+                    .flat_map(|r| r.components)
+                    .filter_map(|c| match c {
+                        ActionRowComponent::Button(b) => b.label,
+                        _ => None,
+                    })
+                    .collect();
+                (m.id, files)
+            })
+            .collect()
+    }
+
+    /// Add a button for a file to the soundboard channel, returning a message
+    /// describing the outcome
+    async fn post(
+        &self,
+        ctx: &Context,
+        res: &visitor::Resolver,
+        gid: GuildId,
+        file: String,
+    ) -> Result<MessageBody> {
+        let chan = res
+            .channels(ctx, gid)
+            .await
+            .context("Error listing guild channels")?
+            .iter()
+            .find_map(|(&id, c)| {
+                (c.kind == ChannelType::Text && c.name == self.board_channel).then_some(id)
+            });
+        let Some(chan) = chan else {
+            return Ok(MessageBody::plain(format!(
+                "There's no #{} channel to post to.",
+                self.board_channel
+            )));
+        };
+
+        if file.chars().count() > BOARD_LABEL_LEN {
+            return Ok(MessageBody::plain("That sound's name is too long for a button."));
+        }
+
+        // Hold the lock until the channel is updated so concurrent posts can't
+        // duplicate a file or race to edit the same message
+        let _guard = self.board_lock.lock().await;
+
+        let msgs = chan
+            .messages(&ctx.http, |m| m.limit(BOARD_HISTORY))
+            .await
+            .context("Error reading soundboard channel")?;
+        let mut board = Self::board_files(ctx, msgs);
+
+        if board.iter().any(|(_, f)| f.contains(&file)) {
+            return Ok(MessageBody::plain("That sound is already on the soundboard."));
+        }
+
+        if board.iter().map(|(_, f)| f.len()).sum::<usize>() >= self.board_max {
+            return Ok(MessageBody::plain("The soundboard is full."));
+        }
+
+        match board.first_mut() {
+            Some((id, files)) if files.len() < BOARD_MSG_BUTTONS => {
+                files.push(file);
+                Self::edit_board(ctx, chan, *id, files).await?;
+            },
+            _ => {
+                let body = Self::board_message(&[file])
+                    .prepare()
+                    .context("Error building soundboard message")?;
+                chan.send_message(&ctx.http, |m| body.build_create_message(m))
+                    .await
+                    .context("Error sending soundboard message")?;
+            },
+        }
+
+        Ok(MessageBody::plain(format!("Posted to <#{chan}>.")))
+    }
+
+    async fn edit_board(ctx: &Context, chan: ChannelId, id: MessageId, files: &[String]) -> Result {
+        let body = Self::board_message(files)
+            .prepare()
+            .context("Error building soundboard message")?;
+        chan.edit_message(&ctx.http, id, |m| body.build_edit_message(m))
+            .await
+            .context("Error editing soundboard message")?;
+
+        Ok(())
+    }
+
+    fn queue(session: Option<&VoiceSession>) -> MessageBody {
+        let names = session.map(VoiceSession::names).unwrap_or_default();
+        if names.is_empty() {
+            return MessageBody::plain("The queue is empty.");
+        }
+
+        MessageBody::rich(|b| {
+            for (i, name) in names.iter().enumerate() {
+                b.push(format!("{}. ", i + 1)).push_safe(name);
+                if i == 0 {
+                    b.push(" (playing)");
+                }
+                b.push("\n");
+            }
+            b
+        })
+    }
+
+    async fn control<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+        args: SoundArgs<'_>,
+    ) -> CommandResult<'a> {
+        let (gid, _memb) = visitor.member()?;
+        let user = visitor.user();
+
+        let msg = match args {
+            SoundArgs::Queue => Self::queue(self.voice.get(gid).await.as_deref()),
+            SoundArgs::NowPlaying => {
+                self.now_playing(self.voice.get(gid).await.as_deref())
+                    .await
+            },
+            args => {
+                let session = match self.session(ctx, gid, user).await {
+                    Ok(s) => s,
+                    Err(msg) => {
+                        return Err(responder
+                            .create_message(Message::plain(msg).ephemeral(true))
+                            .await
+                            .context("Error sending voice session error")?
+                            .into_err("User not listening to voice session"));
+                    },
+                };
+
+                match args {
+                    SoundArgs::Skip => {
+                        session.skip();
+                        MessageBody::plain("Skipped.")
+                    },
+                    SoundArgs::Stop => {
+                        session.stop();
+                        MessageBody::plain("Stopped and cleared the queue.")
+                    },
+                    SoundArgs::Clear => {
+                        let n = session.clear();
+                        MessageBody::plain(format!("Removed {n} queued sound(s)."))
+                    },
+                    SoundArgs::Volume { percent } => {
+                        let percent = percent.clamp(0, 200).try_into().unwrap_or(100);
+                        session.set_volume(percent);
+                        MessageBody::plain(format!("Volume set to {percent}%."))
+                    },
+                    _ => unreachable!(),
+                }
+            },
+        };
+
+        Ok(responder
+            .create_message(Message::from(msg))
+            .await
+            .context("Error sending sound control response")?
+            .into())
+    }
 }
 
 #[async_trait]
 impl CommandHandler<Schema> for SoundCommand {
     fn register_global(&self) -> CommandInfo {
-        CommandInfo::build_slash(&self.name, ";)", |a| {
-            a.build_subcmd("play", "Play a single file", |a| {
-                a.string("path", "Path to the file to play", true, ..)
-                    .autocomplete(true, ["path"])
-            })
-            .build_subcmd("board", "Create a soundboard message", id)
-        })
-        .unwrap()
+        CommandInfo::build_slash(&self.name, ";)", SoundArgs::register)
+            .unwrap()
+            .in_namespace(&self.namespace)
     }
 
+    fn visited_args(&self) -> Option<Visited> { Some(SoundArgs::visited()) }
+
     async fn complete(&self, _: &Context, visitor: &mut CompletionVisitor<'_>) -> CompletionResult {
         // TODO: CompletionVisitor should probably have a better API
         match *visitor.visit_subcmd()? {
@@ -306,14 +723,34 @@ impl CommandHandler<Schema> for SoundCommand {
                     .optional()
                     .map(|s| s.to_lowercase());
                 let path = path.as_deref().unwrap_or("");
+                let invoker = visitor.invoker()?;
+                let target = RankTarget {
+                    user: invoker.user.id,
+                    guild: invoker.guild_id(),
+                };
                 let files = self.files().await?;
                 let files = files.files.read().await;
 
+                if path.is_empty() {
+                    let mut list: Vec<_> = files
+                        .names(invoker.guild_id())
+                        .map(|s| Completion {
+                            name: s.into(),
+                            value: s.into(),
+                        })
+                        .collect();
+                    list.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+                    self.history.rank(target, &mut list);
+                    list.truncate(MAX_COMPLETIONS);
+
+                    return Ok(list);
+                }
+
                 #[allow(clippy::cast_precision_loss)]
                 let mut heap: BinaryHeap<_> = {
                     let all = once_cell::unsync::OnceCell::new();
                     files
-                        .keys()
+                        .names(invoker.guild_id())
                         .map(|s| {
                             (
                                 OrderedFloat(strsim::normalized_damerau_levenshtein(
@@ -332,11 +769,36 @@ impl CommandHandler<Schema> for SoundCommand {
 
                 debug!(?heap, "File completion list accumulated");
 
-                Ok(std::iter::from_fn(move || heap.pop())
+                // Only the closest matches are reordered by history, so a
+                // popular sound can't push out a better match
+                let mut list: Vec<_> = std::iter::from_fn(move || heap.pop())
+                    .take(MAX_COMPLETIONS)
                     .map(|(_, s)| Completion {
                         name: s.into(),
                         value: s.into(),
                     })
+                    .collect();
+                self.history.rank(target, &mut list);
+
+                Ok(list)
+            },
+            ["forget"] => {
+                let path = visitor
+                    .visit_string("path")?
+                    .optional()
+                    .map(|s| s.to_lowercase());
+                let path = path.as_deref().unwrap_or("");
+
+                Ok(self
+                    .history
+                    .user_entries(visitor.user().id)
+                    .into_iter()
+                    .filter(|s| s.to_lowercase().contains(path))
+                    .take(MAX_COMPLETIONS)
+                    .map(|s| Completion {
+                        name: s.clone(),
+                        value: s.into(),
+                    })
                     .collect())
             },
             ref s => Err(anyhow!("Unexpected subcommand {s:?}").into()),
@@ -349,17 +811,42 @@ impl CommandHandler<Schema> for SoundCommand {
         visitor: &mut CommandVisitor<'_>,
         responder: CommandResponder<'_, 'a>,
     ) -> CommandResult<'a> {
-        match *visitor.visit_subcmd()? {
-            ["play"] => self.play(ctx, visitor, responder).await,
-            ["board"] => self.board(ctx, visitor, responder).await,
-            [..] => unreachable!(), // TODO: visitor should handle this
+        match SoundArgs::from_visitor(visitor)? {
+            SoundArgs::Play { path } => self.play(ctx, visitor, responder, path).await,
+            SoundArgs::Board => self.board(ctx, visitor, responder).await,
+            SoundArgs::Forget { path } => {
+                let msg = if self.history.forget(visitor.user().id, path) {
+                    MessageBody::rich(|b| {
+                        b.push("Forgot ")
+                            .push_mono_safe(path)
+                            .push(".  It won't be suggested to you until you play it again.")
+                    })
+                } else {
+                    MessageBody::rich(|b| {
+                        b.push_mono_safe(path).push(" isn't in your play history.")
+                    })
+                };
+
+                Ok(responder
+                    .create_message(Message::from(msg).ephemeral(true))
+                    .await
+                    .context("Error sending forget response")?
+                    .into())
+            },
+            args => self.control(ctx, visitor, responder, args).await,
         }
     }
 }
 
 #[async_trait]
 impl RpcHandler<Schema, ComponentKey> for SoundCommand {
-    fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::Soundboard] }
+    fn register_keys(&self) -> &'static [ComponentKey] {
+        &[
+            ComponentKey::Soundboard,
+            ComponentKey::SoundControl,
+            ComponentKey::SoundPost,
+        ]
+    }
 
     async fn respond<'a>(
         &self,
@@ -371,7 +858,7 @@ impl RpcHandler<Schema, ComponentKey> for SoundCommand {
         match payload {
             ComponentPayload::Soundboard(s) => {
                 let component::Soundboard { file } = s;
-                let (gid, _memb) = visitor.guild()?.required()?;
+                let (gid, _memb) = visitor.member()?;
                 let user = visitor.user();
 
                 let responder = responder
@@ -379,7 +866,7 @@ impl RpcHandler<Schema, ComponentKey> for SoundCommand {
                     .await
                     .context("Error sending deferred update")?;
 
-                let responder = self
+                let (responder, _pos) = self
                     .play_impl(ctx, gid, user, &file, responder, |r, m, e| async move {
                         match r.create_followup(Message::from(m).ephemeral(true)).await {
                             Ok(_) => r.into_err(e),
@@ -390,31 +877,71 @@ impl RpcHandler<Schema, ComponentKey> for SoundCommand {
 
                 Ok(responder.into())
             },
-            _ => unreachable!(), // TODO: set up an error for this
-        }
-    }
-}
+            ComponentPayload::SoundControl(c) => {
+                let (gid, _memb) = visitor.member()?;
+                let user = visitor.user();
 
-struct SongbirdHandler(Arc<()>, Arc<Mutex<songbird::Call>>);
+                let session = match self.session(ctx, gid, user).await {
+                    Ok(s) => s,
+                    Err(msg) => {
+                        return Err(responder
+                            .create_message(Message::plain(msg).ephemeral(true))
+                            .await
+                            .context("Error sending voice session error")?
+                            .into_err("User not listening to voice session"));
+                    },
+                };
 
-#[async_trait]
-impl songbird::EventHandler for SongbirdHandler {
-    async fn act(&self, ctx: &songbird::EventContext<'_>) -> Option<songbird::Event> {
-        match *ctx {
-            songbird::EventContext::Track(t) => {
-                if t.iter().all(|(s, _)| s.playing.is_done()) {
-                    self.1
-                        .lock()
-                        .await
-                        .leave()
+                match component::SoundAction::from_i32(c.action) {
+                    Some(component::SoundAction::Skip) => session.skip(),
+                    Some(component::SoundAction::Stop) => session.stop(),
+                    Some(component::SoundAction::Unknown) | None => {
+                        return Err(anyhow!("Invalid sound action {:?}", c.action).into());
+                    },
+                }
+
+                let responder = responder
+                    .update_message(self.now_playing(Some(&session)).await.into())
+                    .await
+                    .context("Error updating now playing message")?;
+
+                Ok(responder.into())
+            },
+            ComponentPayload::SoundPost(p) => {
+                let component::SoundPost { file } = p;
+                let (gid, _memb) = visitor.member()?;
+
+                let files = self.files().await?;
+                if files.files.read().await.get(Some(gid), &file).is_none() {
+                    return Err(responder
+                        .create_message(Message::plain(PATH_ERR).ephemeral(true))
                         .await
-                        .map_err(|err| error!(%err, "Error leaving call"))
-                        .ok();
+                        .context("Error sending path error")?
+                        .into_err("File not in sample table"));
                 }
 
-                None
+                let responder = responder
+                    .defer_message(MessageOpts::default().ephemeral(true))
+                    .await
+                    .context("Error sending deferred message")?;
+
+                let msg = self.post(ctx, visitor.resolver(), gid, file).await?;
+
+                responder
+                    .edit(msg)
+                    .await
+                    .context("Error updating deferred response")?;
+
+                Ok(responder.into())
             },
-            _ => None,
+            // TODO: set up an error for this
+            ComponentPayload::Role(_)
+            | ComponentPayload::Share(_)
+            | ComponentPayload::Undo(_)
+            | ComponentPayload::Giveaway(_)
+            | ComponentPayload::PinArchive(_)
+            | ComponentPayload::Health(_)
+            | ComponentPayload::Payloads(_) => unreachable!(),
         }
     }
 }
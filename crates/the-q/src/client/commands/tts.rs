@@ -0,0 +1,232 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use super::{prelude::*, sound::SoundCommand};
+
+const MAX_TEXT_LEN: u16 = 200;
+
+#[derive(Debug, clap::Args)]
+pub struct TtsOpts {
+    /// espeak-compatible program to synthesize speech with
+    #[arg(long, env, default_value = "espeak-ng")]
+    tts_program: String,
+
+    /// Voice to pass to the speech synthesizer
+    #[arg(long, env, default_value = "en")]
+    tts_voice: String,
+
+    /// Speech rate to pass to the speech synthesizer, in words per minute
+    #[arg(long, env, default_value_t = 175)]
+    tts_rate: u32,
+
+    /// Directory to cache synthesized speech in
+    #[arg(long, env)]
+    tts_cache_dir: Option<PathBuf>,
+
+    /// Maximum total size, in bytes, of the synthesized speech cache
+    #[arg(long, env, default_value_t = 64 * 1024 * 1024)]
+    tts_cache_size: u64,
+}
+
+/// A speech synthesizer capable of rendering text to an audio file
+#[async_trait]
+pub trait TtsBackend: fmt::Debug + Send + Sync {
+    /// A string uniquely identifying this backend's voice and settings, used
+    /// to key the audio cache
+    fn cache_key(&self) -> String;
+
+    /// Synthesize the given text, writing a WAV file to `out`
+    async fn synthesize(&self, text: &str, out: &Path) -> Result;
+}
+
+/// A backend shelling out to `espeak`, `espeak-ng`, or a compatible program
+#[derive(Debug)]
+pub struct Espeak {
+    program: String,
+    voice: String,
+    rate: u32,
+}
+
+#[async_trait]
+impl TtsBackend for Espeak {
+    fn cache_key(&self) -> String { format!("{}:{}:{}", self.program, self.voice, self.rate) }
+
+    async fn synthesize(&self, text: &str, out: &Path) -> Result {
+        // Text is passed over stdin so it can't be interpreted as flags
+        let mut child = tokio::process::Command::new(&self.program)
+            .arg("-v")
+            .arg(&self.voice)
+            .arg("-s")
+            .arg(self.rate.to_string())
+            .arg("-w")
+            .arg(out)
+            .arg("--stdin")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Error starting {:?}", self.program))?;
+
+        let mut stdin = child.stdin.take().context("Missing synthesizer stdin")?;
+        stdin
+            .write_all(text.as_bytes())
+            .await
+            .context("Error writing text to synthesizer")?;
+        mem::drop(stdin);
+
+        let status = child.wait().await.context("Error running synthesizer")?;
+        ensure!(status.success(), "Synthesizer exited with {status}");
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, CommandArgs)]
+#[arg(example = "text:hello world")]
+struct TtsArgs<'a> {
+    /// The text to speak
+    #[arg(min = 1, max = MAX_TEXT_LEN)]
+    text: &'a str,
+}
+
+#[derive(Debug)]
+pub struct TtsCommand {
+    name: String,
+    namespace: String,
+    backend: Box<dyn TtsBackend>,
+    cache: CacheDir,
+    sound: Arc<SoundCommand>,
+}
+
+impl TtsCommand {
+    pub fn new(opts: &CommandOpts, sound: Arc<SoundCommand>) -> Self {
+        let TtsOpts {
+            tts_program,
+            tts_voice,
+            tts_rate,
+            tts_cache_dir,
+            tts_cache_size,
+        } = &opts.tts;
+
+        Self {
+            name: "tts".into(),
+            namespace: opts.command_base.clone(),
+            backend: Box::new(Espeak {
+                program: tts_program.clone(),
+                voice: tts_voice.clone(),
+                rate: *tts_rate,
+            }),
+            cache: CacheDir::new(
+                tts_cache_dir
+                    .clone()
+                    .unwrap_or_else(|| std::env::temp_dir().join("the-q-tts")),
+                *tts_cache_size,
+            ),
+            sound,
+        }
+    }
+
+    async fn render(&self, text: &str) -> Result<PathBuf> {
+        // The cache outlives the process, so the key needs a hash that is
+        // stable across builds
+        let mut hasher = Sha256::new();
+        hasher.update(self.backend.cache_key());
+        hasher.update([0]);
+        hasher.update(text);
+        let name = format!("{:x}.wav", hasher.finalize());
+        let path = self.cache.entry(&name);
+
+        if tokio::fs::metadata(&path).await.is_ok() {
+            trace!(?path, "TTS cache hit");
+            return Ok(path);
+        }
+
+        self.cache
+            .create()
+            .await
+            .context("Error creating TTS cache directory")?;
+
+        // Render to a temporary name first so a failed or concurrent render
+        // never leaves a partial file in the cache
+        let tmp = self.cache.temp();
+        self.backend
+            .synthesize(text, &tmp)
+            .await
+            .context("Error synthesizing speech")?;
+        self.cache
+            .insert(&tmp, &name)
+            .await
+            .context("Error caching synthesized speech")
+    }
+}
+
+#[async_trait]
+impl CommandHandler<Schema> for TtsCommand {
+    fn register_global(&self) -> CommandInfo {
+        CommandInfo::build_slash(&self.name, "Say something out loud", TtsArgs::register)
+            .unwrap()
+            .in_namespace(&self.namespace)
+    }
+
+    fn visited_args(&self) -> Option<Visited> { Some(TtsArgs::visited()) }
+
+    async fn warmup(&self, _: &Context) -> Result {
+        self.cache
+            .create()
+            .await
+            .context("Error creating TTS cache directory")?;
+
+        // Loads the synthesizer and its voice data, and catches a missing
+        // program before anyone tries to use it
+        let tmp = self.cache.temp();
+        self.backend
+            .synthesize("warmup", &tmp)
+            .await
+            .context("Error synthesizing warmup text")?;
+        tokio::fs::remove_file(&tmp)
+            .await
+            .context("Error removing warmup audio")?;
+
+        Ok(())
+    }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        visitor: &mut CommandVisitor<'_>,
+        responder: CommandResponder<'_, 'a>,
+    ) -> CommandResult<'a> {
+        let (gid, _memb) = visitor.member()?;
+        let user = visitor.user();
+        let TtsArgs { text } = TtsArgs::from_visitor(visitor)?;
+
+        let responder = responder
+            .defer_message(MessageOpts::default().ephemeral(true))
+            .await
+            .context("Error sending deferred message")?;
+
+        let path = self.render(text).await?;
+
+        let (responder, _pos) = self
+            .sound
+            .play_file(ctx, gid, user, "text-to-speech", &path, responder, |r, m, e| async move {
+                match r.edit(m).await.context("Error sending error message") {
+                    Ok(_) => r.into_err(e),
+                    Err(e) => CommandError::from(e),
+                }
+            })
+            .await?;
+
+        responder
+            .edit(MessageBody::plain("🗣️"))
+            .await
+            .context("Error updating deferred response")?;
+
+        Ok(responder.into())
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::{Duration, Instant};
+
+use serenity::model::{id::UserId, Permissions};
+use tokio::sync::Mutex;
+
+use super::prelude::*;
+
+#[derive(Debug, clap::Args)]
+pub struct UndoOpts {
+    /// Number of seconds an action can be undone for after it is performed
+    #[arg(long, env, default_value_t = 300)]
+    undo_timeout: u64,
+}
+
+// TODO: only pin and unpin register undo entries so far.  Point and Laugh
+//       doesn't keep a point count to restore, there is no role assignment
+//       command, and sound deletion needs the soft-delete described in the
+//       sound module
+/// The inverse of an action performed by a command
+#[async_trait]
+pub trait Undo: fmt::Debug + Send + Sync {
+    /// A short description of the original action, for the guild log
+    fn describe(&self) -> String;
+
+    /// The permissions a member must still have to undo the action
+    fn permissions(&self) -> Permissions;
+
+    /// Reverse the action, returning a message describing the outcome
+    async fn undo(
+        &self,
+        ctx: &Context,
+        res: &visitor::Resolver,
+        gid: GuildId,
+    ) -> Result<MessageBody>;
+}
+
+#[derive(Debug)]
+struct Entry {
+    gid: GuildId,
+    user: UserId,
+    expires: Instant,
+    action: Box<dyn Undo>,
+}
+
+/// Recently performed actions which can still be undone
+///
+/// Entries are only held in memory, so undo buttons stop working if the bot
+/// restarts.  Each entry is keyed by a random token, so a button left over
+/// from before a restart can never trigger a newer entry.
+#[derive(Debug)]
+pub struct UndoLog {
+    timeout: Duration,
+    entries: Mutex<HashMap<u64, Entry>>,
+}
+
+impl From<&UndoOpts> for UndoLog {
+    fn from(opts: &UndoOpts) -> Self {
+        Self {
+            timeout: Duration::from_secs(opts.undo_timeout),
+            entries: Mutex::default(),
+        }
+    }
+}
+
+fn token() -> Result<u64> {
+    let mut buf = [0_u8; 8];
+    getrandom::getrandom(&mut buf).context("Error generating undo token")?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+impl UndoLog {
+    /// Record an action performed by a user, returning a message row with a
+    /// button to undo it
+    ///
+    /// # Errors
+    /// This method returns an error if a token for the entry could not be
+    /// generated.
+    pub async fn push(
+        &self,
+        gid: GuildId,
+        user: UserId,
+        action: impl Undo + 'static,
+    ) -> Result<response::ActionRow<component::Component, MessageComponent, response::id::Error>>
+    {
+        let now = Instant::now();
+
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, e| e.expires > now);
+        let token = loop {
+            let token = token()?;
+            if !entries.contains_key(&token) {
+                break token;
+            }
+        };
+        entries.insert(token, Entry {
+            gid,
+            user,
+            expires: now + self.timeout,
+            action: Box::new(action),
+        });
+
+        Ok(response::ActionRow::default().button(
+            ComponentPayload::Undo(component::Undo { token }),
+            ButtonStyle::Secondary,
+            "Undo",
+            false,
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct UndoComponent {
+    undo: Arc<UndoLog>,
+    log: Arc<GuildLog>,
+}
+
+impl UndoComponent {
+    pub fn new(undo: Arc<UndoLog>, log: Arc<GuildLog>) -> Self { Self { undo, log } }
+}
+
+#[async_trait]
+impl RpcHandler<Schema, ComponentKey> for UndoComponent {
+    fn register_keys(&self) -> &'static [ComponentKey] { &[ComponentKey::Undo] }
+
+    async fn respond<'a>(
+        &self,
+        ctx: &Context,
+        payload: ComponentPayload,
+        visitor: &mut ComponentVisitor<'_>,
+        responder: ComponentResponder<'_, 'a>,
+    ) -> ComponentResult<'a> {
+        let ComponentPayload::Undo(component::Undo { token }) = payload else {
+            unreachable!();
+        };
+        let (gid, memb) = visitor.member()?;
+        let user = visitor.user();
+
+        let mut entries = self.undo.entries.lock().await;
+        let entry = match entries.get(&token) {
+            Some(e) if e.expires > Instant::now() => e,
+            _ => {
+                entries.remove(&token);
+                mem::drop(entries);
+
+                return Err(responder
+                    .create_message(
+                        Message::plain("That can no longer be undone.").ephemeral(true),
+                    )
+                    .await
+                    .context("Error sending expiry error")?
+                    .into_err("Undo entry expired"));
+            },
+        };
+
+        let perms = entry.action.permissions();
+        if entry.gid != gid
+            || entry.user != user.id
+            || !memb.permissions.map_or(false, |p| p.contains(perms))
+        {
+            mem::drop(entries);
+
+            return Err(responder
+                .create_message(
+                    Message::plain("Only the person who did that can undo it.").ephemeral(true),
+                )
+                .await
+                .context("Error sending permission error")?
+                .into_err("User cannot undo action"));
+        }
+
+        let Entry { action, .. } = entries.remove(&token).unwrap_or_else(|| unreachable!());
+        mem::drop(entries);
+
+        let responder = responder
+            .defer_update(MessageOpts::default())
+            .await
+            .context("Error sending deferred update")?;
+
+        let msg = action.undo(ctx, visitor.resolver(), gid).await?;
+
+        self.log
+            .log(ctx, gid, format!("{} undid: {}", user.tag(), action.describe()))
+            .await;
+
+        responder
+            .edit(msg)
+            .await
+            .context("Error updating undone message")?;
+
+        Ok(responder.into())
+    }
+}
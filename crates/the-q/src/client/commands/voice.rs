@@ -0,0 +1,700 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::ChannelId;
+use songbird::{
+    events::context_data::RtcpData,
+    input::Input,
+    packet::rtcp::{report::ReportBlockPacket, Rtcp},
+    tracks::TrackQueue,
+};
+use tokio::sync::Mutex;
+
+use super::prelude::*;
+
+/// The default playback volume, as a percentage
+const DEFAULT_VOLUME: u32 = 100;
+
+/// The length of the sender info block at the start of an RTCP sender report
+const SENDER_INFO_LEN: usize = 20;
+/// The length of a single RTCP report block
+const REPORT_BLOCK_LEN: usize = 24;
+/// The RTP clock rate of Discord's Opus streams, in ticks per millisecond
+const RTP_TICKS_PER_MS: u32 = 48;
+/// Number of consecutive degraded link reports before reconnecting
+const DEGRADED_REPORTS: u32 = 3;
+/// Minimum time between reconnects caused by a degraded link
+const RECONNECT_COOLDOWN: Duration = Duration::from_secs(300);
+
+#[derive(Debug, clap::Args)]
+pub struct VoiceOpts {
+    /// Number of seconds to stay in a voice channel after the queue empties
+    #[arg(long, env, default_value_t = 300)]
+    voice_idle_timeout: u64,
+
+    /// Whether to pause playback while nobody is listening or the bot is
+    /// server-muted
+    #[arg(long, env, default_value_t = true, action = clap::ArgAction::Set)]
+    voice_auto_pause: bool,
+
+    /// Percentage of packets lost, as reported by the voice server, above
+    /// which the voice connection is considered degraded
+    #[arg(long, env, default_value_t = 10)]
+    voice_max_loss: u8,
+
+    /// Interarrival jitter in milliseconds, as reported by the voice server,
+    /// above which the voice connection is considered degraded
+    #[arg(long, env, default_value_t = 60)]
+    voice_max_jitter: u32,
+}
+
+/// Thresholds above which a voice connection is reconnected
+#[derive(Debug, Clone, Copy)]
+struct LinkLimits {
+    max_loss: u8,
+    max_jitter: u32,
+}
+
+type SessionMap = Mutex<HashMap<GuildId, Arc<VoiceSession>>>;
+
+/// Owns the voice connection and playback queue for every guild the bot is
+/// connected to
+#[derive(Debug)]
+pub struct VoiceSessions {
+    idle_timeout: Duration,
+    auto_pause: bool,
+    limits: LinkLimits,
+    log: Arc<GuildLog>,
+    sessions: Arc<SessionMap>,
+}
+
+/// The reason a sound could not be queued
+#[derive(Debug)]
+pub enum JoinError {
+    /// The bot is already playing in a different channel
+    Busy,
+    /// The queue has no room for another sound
+    Full,
+    /// The voice channel could not be joined
+    Join(songbird::error::JoinError),
+    /// An unexpected error occurred
+    Other(Error),
+}
+
+impl From<Error> for JoinError {
+    fn from(err: Error) -> Self { Self::Other(err) }
+}
+
+impl VoiceSessions {
+    /// The maximum number of sounds a single guild may have queued
+    pub const MAX_QUEUE_LEN: usize = 25;
+
+    pub fn new(opts: &CommandOpts, log: Arc<GuildLog>) -> Self {
+        let VoiceOpts {
+            voice_idle_timeout,
+            voice_auto_pause,
+            voice_max_loss,
+            voice_max_jitter,
+        } = opts.voice;
+
+        Self {
+            idle_timeout: Duration::from_secs(voice_idle_timeout),
+            auto_pause: voice_auto_pause,
+            limits: LinkLimits {
+                max_loss: voice_max_loss,
+                max_jitter: voice_max_jitter,
+            },
+            log,
+            sessions: Arc::default(),
+        }
+    }
+
+    /// Get the active session for a guild, if any
+    pub async fn get(&self, gid: GuildId) -> Option<Arc<VoiceSession>> {
+        self.sessions.lock().await.get(&gid).map(Arc::clone)
+    }
+
+    /// Get the number of active sessions and the total number of sounds queued
+    /// across them
+    pub async fn stats(&self) -> (usize, usize) {
+        let sessions = self.sessions.lock().await;
+        let queued = sessions.values().map(|s| s.queue.len()).sum();
+
+        (sessions.len(), queued)
+    }
+
+    /// Get the packet loss percentage and jitter in milliseconds from the
+    /// latest link quality report of each active session
+    pub async fn links(&self) -> Vec<(GuildId, Option<(u8, u32)>)> {
+        self.sessions
+            .lock()
+            .await
+            .values()
+            .map(|s| (s.gid, s.link()))
+            .collect()
+    }
+
+    /// Queue a sound in the given voice channel, joining it if necessary
+    ///
+    /// Returns the sound's position in the queue, where zero means it is
+    /// playing immediately.
+    ///
+    /// # Errors
+    /// This method returns an error if the bot is busy in another channel, the
+    /// queue is full, or the channel cannot be joined.
+    pub async fn enqueue(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        chan: ChannelId,
+        name: &str,
+        source: Input,
+    ) -> Result<usize, JoinError> {
+        let session = match self.get(gid).await {
+            Some(s) if s.channel().await == Some(chan) => s,
+            Some(s) if !s.queue.is_empty() => return Err(JoinError::Busy),
+            _ => self.join(ctx, gid, chan).await?,
+        };
+
+        session.enqueue(name, source, Self::MAX_QUEUE_LEN).await
+    }
+
+    /// Join a voice channel, returning the guild's session
+    ///
+    /// The session map isn't locked while joining, so another sound may have
+    /// set up a session for the same call in the meantime, in which case that
+    /// session is returned instead of creating a new one.
+    async fn join(
+        &self,
+        ctx: &Context,
+        gid: GuildId,
+        chan: ChannelId,
+    ) -> Result<Arc<VoiceSession>, JoinError> {
+        let sb = songbird::get(ctx)
+            .await
+            .context("Missing songbird context")?;
+        let (call, res) = sb.join(gid, chan).await;
+        res.map_err(JoinError::Join)?;
+
+        let mut sessions = self.sessions.lock().await;
+        if let Some(s) = sessions.get(&gid).filter(|s| Arc::ptr_eq(&s.call, &call)) {
+            return Ok(Arc::clone(s));
+        }
+
+        let session = VoiceSession::new(
+            gid,
+            call,
+            self.idle_timeout,
+            self.limits,
+            Arc::downgrade(&self.sessions),
+        )
+        .await;
+        session.monitor(ctx, Arc::clone(&self.log)).await;
+        sessions.insert(gid, Arc::clone(&session));
+
+        Ok(session)
+    }
+
+    /// Pause or resume playback in a guild after a voice state in it changes
+    ///
+    /// Playback is paused while the bot is server-muted or nobody else in its
+    /// channel can hear it, and resumed once someone can.
+    pub async fn update_voice_state(&self, ctx: &Context, gid: GuildId) {
+        if !self.auto_pause {
+            return;
+        }
+
+        let Some(session) = self.get(gid).await else { return };
+        let Some(chan) = session.channel().await else { return };
+        let me = ctx.cache.current_user_id();
+
+        let Some(audible) = ctx.cache.guild_field(gid, |g| {
+            let muted = g
+                .voice_states
+                .get(&me)
+                .map_or(false, |s| s.mute || s.suppress);
+            let listening = g.voice_states.values().any(|s| {
+                s.channel_id == Some(chan)
+                    && s.user_id != me
+                    && !(s.deaf || s.self_deaf)
+                    && !s.member.as_ref().map_or(false, |m| m.user.bot)
+            });
+
+            listening && !muted
+        }) else {
+            return;
+        };
+
+        session.set_audible(audible);
+    }
+}
+
+/// The voice connection and playback queue for a single guild
+#[derive(Debug)]
+pub struct VoiceSession {
+    gid: GuildId,
+    call: Arc<Mutex<songbird::Call>>,
+    queue: TrackQueue,
+    volume: AtomicU32,
+    activity: AtomicU64,
+    auto_paused: AtomicBool,
+    idle_timeout: Duration,
+    limits: LinkLimits,
+    link: std::sync::Mutex<LinkState>,
+    sessions: std::sync::Weak<SessionMap>,
+}
+
+/// Tracks the latest link quality report and how long a voice connection has
+/// been degraded for
+#[derive(Debug, Default)]
+struct LinkState {
+    last: Option<LinkReport>,
+    degraded: u32,
+    last_reconnect: Option<Instant>,
+}
+
+impl VoiceSession {
+    async fn new(
+        gid: GuildId,
+        call_lock: Arc<Mutex<songbird::Call>>,
+        idle_timeout: Duration,
+        limits: LinkLimits,
+        sessions: std::sync::Weak<SessionMap>,
+    ) -> Arc<Self> {
+        let mut call = call_lock.lock().await;
+
+        let session = Arc::new(Self {
+            gid,
+            call: Arc::clone(&call_lock),
+            queue: call.queue().clone(),
+            volume: AtomicU32::new(DEFAULT_VOLUME),
+            activity: AtomicU64::new(0),
+            auto_paused: AtomicBool::new(false),
+            idle_timeout,
+            limits,
+            link: std::sync::Mutex::default(),
+            sessions,
+        });
+
+        call.remove_all_global_events();
+        call.add_global_event(
+            songbird::Event::Track(songbird::TrackEvent::End),
+            IdleMonitor(Arc::downgrade(&session)),
+        );
+
+        session
+    }
+
+    async fn monitor(self: &Arc<Self>, ctx: &Context, log: Arc<GuildLog>) {
+        let mut call = self.call.lock().await;
+
+        for evt in [
+            songbird::CoreEvent::DriverReconnect,
+            songbird::CoreEvent::DriverDisconnect,
+            songbird::CoreEvent::RtcpPacket,
+        ] {
+            call.add_global_event(evt.into(), VoiceMonitor {
+                session: Arc::downgrade(self),
+                ctx: ctx.clone(),
+                log: Arc::clone(&log),
+            });
+        }
+    }
+
+    /// Get the guild this session belongs to
+    #[must_use]
+    pub fn guild(&self) -> GuildId { self.gid }
+
+    /// Get the voice channel this session is connected to
+    pub async fn channel(&self) -> Option<ChannelId> {
+        self.call
+            .lock()
+            .await
+            .current_channel()
+            .map(|c| ChannelId(c.0))
+    }
+
+    /// Queue a sound, returning its position in the queue
+    ///
+    /// The queue length is checked while holding the call lock, so concurrent
+    /// sounds can't push the queue past `max_len`.
+    async fn enqueue(
+        &self,
+        name: &str,
+        mut source: Input,
+        max_len: usize,
+    ) -> Result<usize, JoinError> {
+        let mut call = self.call.lock().await;
+        if self.queue.len() >= max_len {
+            return Err(JoinError::Full);
+        }
+
+        self.activity.fetch_add(1, Ordering::SeqCst);
+        source.metadata.title = Some(name.into());
+
+        let (mut track, _handle) = songbird::tracks::create_player(source);
+        track.set_volume(self.volume_f32());
+        call.enqueue(track);
+
+        Ok(self.queue.len().saturating_sub(1))
+    }
+
+    /// Get the names of every sound in the queue, starting with the one
+    /// currently playing
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        self.queue
+            .current_queue()
+            .iter()
+            .map(|t| t.metadata().title.clone().unwrap_or_else(|| "???".into()))
+            .collect()
+    }
+
+    /// Skip the sound currently playing
+    pub fn skip(&self) {
+        self.queue
+            .skip()
+            .map_err(|err| warn!(%err, "Error skipping track"))
+            .ok();
+    }
+
+    /// Stop the sound currently playing and clear the queue
+    pub fn stop(self: &Arc<Self>) {
+        self.queue.stop();
+        self.idle();
+    }
+
+    /// Remove every queued sound except the one currently playing, returning
+    /// the number removed
+    pub fn clear(&self) -> usize {
+        self.queue.modify_queue(|q| {
+            let removed: Vec<_> = q.drain(1.min(q.len())..).collect();
+            for track in &removed {
+                track
+                    .stop()
+                    .map_err(|err| warn!(%err, "Error stopping cleared track"))
+                    .ok();
+            }
+            removed.len()
+        })
+    }
+
+    /// Get the playback volume, as a percentage
+    #[must_use]
+    pub fn volume(&self) -> u32 { self.volume.load(Ordering::Relaxed) }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn volume_f32(&self) -> f32 { self.volume() as f32 / 100.0 }
+
+    /// Set the playback volume for the current and all queued sounds, as a
+    /// percentage
+    pub fn set_volume(&self, volume: u32) {
+        self.volume.store(volume, Ordering::Relaxed);
+        let vol = self.volume_f32();
+
+        for track in self.queue.current_queue() {
+            track
+                .set_volume(vol)
+                .map_err(|err| warn!(%err, "Error setting track volume"))
+                .ok();
+        }
+    }
+
+    /// Pause playback when nobody can hear it, and resume it once someone can
+    ///
+    /// While paused, the session is treated as idle, so the bot leaves the
+    /// channel (dropping the queue) if nobody returns before the idle timeout.
+    fn set_audible(self: &Arc<Self>, audible: bool) {
+        if audible {
+            if self.auto_paused.swap(false, Ordering::SeqCst) {
+                self.activity.fetch_add(1, Ordering::SeqCst);
+                info!(guild = ?self.gid, "Resuming playback");
+                self.queue
+                    .resume()
+                    .map_err(|err| warn!(%err, "Error resuming track"))
+                    .ok();
+            }
+        } else if !self.queue.is_empty() && !self.auto_paused.swap(true, Ordering::SeqCst) {
+            self.activity.fetch_add(1, Ordering::SeqCst);
+            info!(guild = ?self.gid, "Nobody is listening, pausing playback");
+            self.queue
+                .pause()
+                .map_err(|err| warn!(%err, "Error pausing track"))
+                .ok();
+            self.idle();
+        }
+    }
+
+    /// Rejoin a voice channel, optionally leaving it first to force a fresh
+    /// connection
+    async fn rejoin(&self, chan: ChannelId, leave: bool) {
+        let mut call = self.call.lock().await;
+
+        if leave {
+            call.leave()
+                .await
+                .map_err(|err| warn!(%err, "Error leaving voice channel"))
+                .ok();
+        }
+
+        let res = call.join(chan).await;
+        mem::drop(call);
+
+        match res {
+            Ok(join) => join
+                .await
+                .map_err(|err| error!(%err, "Error rejoining voice channel"))
+                .ok(),
+            Err(err) => {
+                error!(%err, "Error rejoining voice channel");
+                None
+            },
+        };
+    }
+
+    /// Get the packet loss percentage and jitter in milliseconds from the
+    /// latest link quality report, if one has been received
+    #[must_use]
+    pub fn link(&self) -> Option<(u8, u32)> {
+        let link = self.link.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        link.last.map(|r| (r.loss_pct(), r.jitter_ms()))
+    }
+
+    /// Record a link quality report, returning true if the connection has
+    /// been degraded for long enough that it should be reconnected
+    fn degraded(&self, report: LinkReport) -> bool {
+        let LinkLimits {
+            max_loss,
+            max_jitter,
+        } = self.limits;
+        let mut link = self.link.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        link.last = Some(report);
+
+        if report.loss_pct() <= max_loss && report.jitter_ms() <= max_jitter {
+            link.degraded = 0;
+            return false;
+        }
+
+        link.degraded += 1;
+        if link.degraded < DEGRADED_REPORTS
+            || link
+                .last_reconnect
+                .map_or(false, |t| t.elapsed() < RECONNECT_COOLDOWN)
+        {
+            return false;
+        }
+
+        link.degraded = 0;
+        link.last_reconnect = Some(Instant::now());
+        true
+    }
+
+    /// Leave the voice channel after the idle timeout unless another sound is
+    /// queued (or paused playback resumes) before then
+    fn idle(self: &Arc<Self>) {
+        let activity = self.activity.load(Ordering::SeqCst);
+        let timeout = self.idle_timeout;
+        let session = Arc::downgrade(self);
+
+        tokio::task::spawn(
+            async move {
+                tokio::time::sleep(timeout).await;
+
+                let Some(session) = session.upgrade() else { return };
+                let Some(sessions) = session.sessions.upgrade() else { return };
+                let mut sessions = sessions.lock().await;
+
+                if !sessions
+                    .get(&session.gid)
+                    .map_or(false, |s| Arc::ptr_eq(s, &session))
+                    || session.activity.load(Ordering::SeqCst) != activity
+                    || !(session.queue.is_empty() || session.auto_paused.load(Ordering::SeqCst))
+                {
+                    return;
+                }
+
+                sessions.remove(&session.gid);
+                mem::drop(sessions);
+                session.queue.stop();
+
+                info!(guild = ?session.gid, "Leaving idle voice channel");
+                session
+                    .call
+                    .lock()
+                    .await
+                    .leave()
+                    .await
+                    .map_err(|err| error!(%err, "Error leaving call"))
+                    .ok();
+            }
+            .instrument(info_span!(parent: None, "voice_idle")),
+        );
+    }
+}
+
+/// Starts the idle timer whenever a sound finishes
+struct IdleMonitor(std::sync::Weak<VoiceSession>);
+
+#[async_trait]
+impl songbird::EventHandler for IdleMonitor {
+    async fn act(&self, ctx: &songbird::EventContext<'_>) -> Option<songbird::Event> {
+        if let songbird::EventContext::Track(_) = *ctx {
+            if let Some(session) = self.0.upgrade() {
+                session.idle();
+            }
+        }
+
+        None
+    }
+}
+
+/// The worst packet loss and jitter in a single RTCP report from the voice
+/// server
+#[derive(Debug, Clone, Copy)]
+struct LinkReport {
+    /// Fraction of packets lost since the last report, in 256ths
+    fraction_lost: u8,
+    /// Interarrival jitter, in RTP timestamp units
+    jitter: u32,
+}
+
+impl LinkReport {
+    fn parse(data: &RtcpData) -> Option<Self> {
+        let (count, payload, skip) = match data.packet {
+            Rtcp::SenderReport(r) => (r.rx_report_count, &r.payload, SENDER_INFO_LEN),
+            Rtcp::ReceiverReport(r) => (r.rx_report_count, &r.payload, 0),
+            _ => return None,
+        };
+        let end = payload.len().checked_sub(data.payload_end_pad)?;
+        let blocks = payload.get(data.payload_offset + skip..end)?;
+
+        blocks
+            .chunks_exact(REPORT_BLOCK_LEN)
+            .take(count.into())
+            .filter_map(ReportBlockPacket::new)
+            .map(|b| Self {
+                fraction_lost: b.get_fraction_lost(),
+                jitter: b.get_interarrival_jitter(),
+            })
+            .reduce(|a, b| Self {
+                fraction_lost: a.fraction_lost.max(b.fraction_lost),
+                jitter: a.jitter.max(b.jitter),
+            })
+    }
+
+    fn loss_pct(self) -> u8 {
+        u8::try_from(u32::from(self.fraction_lost) * 100 / 256).unwrap_or_else(|_| unreachable!())
+    }
+
+    fn jitter_ms(self) -> u32 { self.jitter / RTP_TICKS_PER_MS }
+}
+
+/// Logs voice server migrations, connection drops and link quality reports,
+/// and reconnects if the queue was interrupted by a connection failure or the
+/// link stays degraded
+///
+/// Packet loss and jitter come from the RTCP reports the voice server sends
+/// about the bot's outgoing stream.  The latest report for each session is
+/// shown by the debug health view, and the guild log hears about them once
+/// the link is degraded enough to reconnect.  Songbird never sends sender
+/// reports of its own, so the reports carry no round-trip timing and latency
+/// isn't tracked.
+struct VoiceMonitor {
+    session: std::sync::Weak<VoiceSession>,
+    ctx: Context,
+    log: Arc<GuildLog>,
+}
+
+#[async_trait]
+impl songbird::EventHandler for VoiceMonitor {
+    async fn act(&self, ctx: &songbird::EventContext<'_>) -> Option<songbird::Event> {
+        match *ctx {
+            songbird::EventContext::DriverReconnect(ref c) => {
+                info!(
+                    guild = ?c.guild_id,
+                    channel = ?c.channel_id,
+                    server = c.server,
+                    "Voice driver reconnected",
+                );
+
+                let session = self.session.upgrade()?;
+                self.log
+                    .log(
+                        &self.ctx,
+                        session.gid,
+                        format!("Voice connection moved to server {}", c.server),
+                    )
+                    .await;
+
+                None
+            },
+            songbird::EventContext::DriverDisconnect(ref d) => {
+                warn!(
+                    guild = ?d.guild_id,
+                    channel = ?d.channel_id,
+                    kind = ?d.kind,
+                    reason = ?d.reason,
+                    "Voice driver disconnected",
+                );
+
+                // A missing reason means the disconnect was requested
+                let (Some(reason), Some(chan)) = (d.reason, d.channel_id) else {
+                    return None;
+                };
+
+                // Only rejoin if there is still something to play
+                let session = self.session.upgrade()?;
+                if session.queue.is_empty() {
+                    return None;
+                }
+
+                self.log
+                    .log(
+                        &self.ctx,
+                        session.gid,
+                        format!("Voice connection dropped ({reason:?}), rejoining"),
+                    )
+                    .await;
+                session.rejoin(ChannelId(chan.0), false).await;
+
+                None
+            },
+            songbird::EventContext::RtcpPacket(ref data) => {
+                let session = self.session.upgrade()?;
+                let report = LinkReport::parse(data)?;
+                debug!(
+                    guild = ?session.gid,
+                    loss_pct = report.loss_pct(),
+                    jitter_ms = report.jitter_ms(),
+                    "Voice link report",
+                );
+
+                if !session.degraded(report) {
+                    return None;
+                }
+
+                let chan = session.channel().await?;
+                warn!(guild = ?session.gid, ?report, "Voice link degraded, reconnecting");
+                self.log
+                    .log(
+                        &self.ctx,
+                        session.gid,
+                        format!(
+                            "Voice connection degraded ({}% packet loss, {} ms jitter), \
+                             reconnecting",
+                            report.loss_pct(),
+                            report.jitter_ms(),
+                        ),
+                    )
+                    .await;
+                session.rejoin(chan, true).await;
+
+                None
+            },
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,17 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
 use paracord::interaction;
 use serenity::{
-    model::{application::interaction::Interaction, gateway::Ready},
+    http::Http,
+    model::{
+        application::interaction::Interaction,
+        gateway::Ready,
+        guild::{Guild, ScheduledEvent},
+        voice::VoiceState,
+    },
     prelude::*,
 };
 
@@ -9,14 +20,50 @@ use crate::prelude::*;
 
 pub struct Handler {
     registry: interaction::Registry<commands::Schema>,
+    voice: Arc<commands::VoiceSessions>,
+    events: commands::EventReminders,
+    startup: Arc<commands::Startup>,
+    created: Instant,
+    connected: AtomicBool,
 }
 
 impl Handler {
     pub fn new_rc(command_opts: &commands::CommandOpts) -> Arc<Self> {
+        let shared = commands::Shared::from(command_opts);
+        let handlers = commands::handlers(command_opts, &shared);
+
         Arc::new(Self {
-            registry: interaction::Registry::new(commands::handlers(command_opts)),
+            registry: interaction::Registry::new(handlers),
+            voice: shared.voice,
+            events: shared.events,
+            startup: shared.startup,
+            created: Instant::now(),
+            connected: AtomicBool::new(false),
         })
     }
+
+    /// Sync commands with Discord in the background, so it overlaps with the
+    /// gateway connecting
+    ///
+    /// The registry turns interactions away until this has finished.
+    pub fn sync_commands(self: &Arc<Self>, http: Arc<Http>) {
+        let this = Arc::clone(self);
+
+        tokio::task::spawn(handler("sync_commands", async move {
+            let start = Instant::now();
+            if http.application_id().is_none() {
+                let info = http
+                    .get_current_application_info()
+                    .await
+                    .context("Error getting application info")?;
+                http.set_application_id(info.id.0);
+            }
+
+            this.registry.init(&http).await?;
+            this.startup.record("commands", start.elapsed());
+            Ok(())
+        }));
+    }
 }
 
 #[instrument(skip(f))]
@@ -27,6 +74,12 @@ async fn handler(method: &'static str, f: impl Future<Output = Result>) {
     }
 }
 
+async fn timed<T>(f: impl Future<Output = T>) -> (T, Duration) {
+    let start = Instant::now();
+    let ret = f.await;
+    (ret, start.elapsed())
+}
+
 #[async_trait]
 impl serenity::client::EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, int: Interaction) {
@@ -40,11 +93,41 @@ impl serenity::client::EventHandler for Handler {
         }
     }
 
+    async fn voice_state_update(&self, ctx: Context, _: Option<VoiceState>, new: VoiceState) {
+        if let Some(gid) = new.guild_id {
+            self.voice.update_voice_state(&ctx, gid).await;
+        }
+    }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild, _: bool) {
+        self.events.load(&ctx, guild.id).await;
+    }
+
+    async fn guild_scheduled_event_create(&self, ctx: Context, event: ScheduledEvent) {
+        self.events.update(&ctx, event).await;
+    }
+
+    async fn guild_scheduled_event_update(&self, ctx: Context, event: ScheduledEvent) {
+        self.events.update(&ctx, event).await;
+    }
+
+    async fn guild_scheduled_event_delete(&self, _: Context, event: ScheduledEvent) {
+        self.events.remove(event.id).await;
+    }
+
     async fn ready(&self, ctx: Context, _: Ready) {
-        handler("ready", async move {
-            self.registry.init(&ctx).await?;
-            Ok(())
-        })
-        .await;
+        // Reconnecting sends another ready event, but startup only happens
+        // once
+        if self.connected.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        self.startup.record("gateway", self.created.elapsed());
+
+        // Event handlers run in their own tasks, so this doesn't hold up any
+        // interactions.  Warmup needs the gateway context, so unlike command
+        // sync it can't start any earlier.
+        let ((), elapsed) = timed(self.registry.warmup(&ctx)).await;
+        self.startup.record("warmup", elapsed);
     }
 }
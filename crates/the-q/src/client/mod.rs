@@ -9,8 +9,8 @@ mod handler;
 #[derive(Debug, clap::Args)]
 pub struct ClientOpts {
     /// The Discord API token to use
-    #[arg(long, env)]
-    discord_token: DebugShim<String>,
+    #[arg(long, env, required = true)]
+    discord_token: Option<DebugShim<String>>,
 
     #[command(flatten)]
     commands: commands::CommandOpts,
@@ -22,12 +22,55 @@ pub async fn build(opts: ClientOpts) -> Result<Client> {
         commands,
     } = opts;
 
+    let discord_token = discord_token.context("Missing Discord API token")?;
     let intents = GatewayIntents::non_privileged(); // TODO
     let handler = handler::Handler::new_rc(&commands);
 
-    Client::builder(discord_token.0, intents)
-        .event_handler_arc(handler)
+    let client = Client::builder(discord_token.0, intents)
+        .event_handler_arc(Arc::clone(&handler))
         .register_songbird()
         .await
-        .context("Error constructing Serenity client")
+        .context("Error constructing Serenity client")?;
+
+    client
+        .data
+        .write()
+        .await
+        .insert::<commands::ShardManagerKey>(Arc::clone(&client.shard_manager));
+    handler.sync_commands(Arc::clone(&client.cache_and_http.http));
+
+    Ok(client)
+}
+
+pub fn validate_schema(opts: &ClientOpts) -> Result {
+    let shared = commands::Shared::from(&opts.commands);
+    let registry =
+        paracord::interaction::Registry::new(commands::handlers(&opts.commands, &shared));
+    let invalid = registry
+        .validate_commands()
+        .context("Error collating commands")?;
+
+    for (name, errs) in &invalid {
+        for err in errs {
+            error!(command = name, "{err}");
+        }
+    }
+
+    ensure!(invalid.is_empty(), "{} command(s) failed validation", invalid.len());
+    info!("All commands passed validation");
+
+    Ok(())
+}
+
+pub fn command_catalog(opts: &ClientOpts) -> Result {
+    let shared = commands::Shared::from(&opts.commands);
+    let registry =
+        paracord::interaction::Registry::new(commands::handlers(&opts.commands, &shared));
+    let catalog = registry
+        .command_catalog()
+        .context("Error collating commands")?;
+
+    println!("{catalog:#}");
+
+    Ok(())
 }
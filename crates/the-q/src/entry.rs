@@ -3,7 +3,7 @@ use tracing_subscriber::{layer::Layered, EnvFilter};
 use crate::prelude::*;
 
 #[derive(Debug, clap::Parser)]
-#[command(version, author, about)]
+#[command(version, author, about, subcommand_negates_reqs = true)]
 struct Opts {
     /// Log filter, using env_logger-like syntax
     #[arg(long, env = "RUST_LOG")]
@@ -19,6 +19,19 @@ struct Opts {
 
     #[command(flatten)]
     client: crate::client::ClientOpts,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Check all commands against Discord's registration limits and exit
+    /// without connecting
+    ValidateSchema,
+    /// Print a JSON description of all commands for external documentation
+    /// and exit without connecting
+    CommandCatalog,
 }
 
 macro_rules! init_error {
@@ -182,8 +195,15 @@ async fn run(opts: Opts) -> Result {
         loki_endpoint: _,
         threads: _,
         client,
+        command,
     } = opts;
 
+    match command {
+        Some(Command::ValidateSchema) => return crate::client::validate_schema(&client),
+        Some(Command::CommandCatalog) => return crate::client::command_catalog(&client),
+        None => (),
+    }
+
     let mut client = crate::client::build(client).await?;
     let signal;
 
@@ -15,3 +15,6 @@ macro_rules! proto_mod {
 
 proto_mod!(pub modal, "modal");
 proto_mod!(pub component, "component");
+
+#[cfg(test)]
+pub mod testing;
@@ -0,0 +1,107 @@
+//! Property test strategies for generating custom ID payloads
+
+use std::fmt;
+
+use paracord::interaction::response::id;
+use proptest::prelude::*;
+
+use super::{component, modal};
+
+/// The longest sound file name generated for payloads.  Custom IDs have a
+/// fixed length limit, so names much longer than this may not fit.
+pub const MAX_FILE_LEN: usize = 64;
+
+fn file() -> impl Strategy<Value = String> {
+    proptest::string::string_regex(&format!("[a-zA-Z0-9 _./-]{{0,{MAX_FILE_LEN}}}"))
+        .unwrap_or_else(|e| unreachable!("{e}"))
+}
+
+fn enum_value<T: Copy + fmt::Debug + Into<i32> + 'static>(
+    values: &[T],
+) -> impl Strategy<Value = i32> {
+    prop::sample::select(values.to_vec()).prop_map(Into::into)
+}
+
+/// Generate an arbitrary component payload
+pub fn component_payload() -> impl Strategy<Value = component::component::Payload> {
+    use component::{component::Payload, GiveawayAction, PageButton, SoundAction};
+
+    prop_oneof![
+        Just(Payload::Role(component::Role {})),
+        file().prop_map(|file| Payload::Soundboard(component::Soundboard { file })),
+        Just(Payload::Share(component::Share {})),
+        enum_value(&[SoundAction::Skip, SoundAction::Stop])
+            .prop_map(|action| Payload::SoundControl(component::SoundControl { action })),
+        any::<u64>().prop_map(|token| Payload::Undo(component::Undo { token })),
+        file().prop_map(|file| Payload::SoundPost(component::SoundPost { file })),
+        enum_value(&[GiveawayAction::Enter, GiveawayAction::Reroll])
+            .prop_map(|action| Payload::Giveaway(component::Giveaway { action })),
+        (
+            any::<u32>(),
+            any::<i64>(),
+            enum_value(&[
+                PageButton::First,
+                PageButton::Prev,
+                PageButton::Next,
+                PageButton::Last,
+            ]),
+        )
+            .prop_map(|(page, expires, button)| Payload::PinArchive(component::PinArchive {
+                page,
+                expires,
+                button,
+            })),
+        Just(Payload::Health(component::Health {})),
+        Just(Payload::Payloads(component::Payloads {})),
+    ]
+}
+
+/// Generate an arbitrary modal payload
+pub fn modal_payload() -> impl Strategy<Value = modal::modal::Payload> {
+    use modal::modal::Payload;
+
+    prop_oneof![Just(Payload::Rename(modal::Rename {}))]
+}
+
+/// Generate an arbitrary component custom ID message
+pub fn component() -> impl Strategy<Value = component::Component> {
+    component_payload().prop_map(|p| component::Component { payload: Some(p) })
+}
+
+/// Generate an arbitrary modal custom ID message
+pub fn modal() -> impl Strategy<Value = modal::Modal> {
+    use modal::ModalSource;
+
+    (
+        enum_value(&[ModalSource::Command, ModalSource::Component]),
+        modal_payload(),
+    )
+        .prop_map(|(source, p)| modal::Modal {
+            source,
+            payload: Some(p),
+        })
+}
+
+/// Encode a message as a custom ID and decode it again, checking that it fits
+/// in the custom ID field and survives unchanged
+///
+/// # Panics
+/// This function panics if the message cannot be encoded or decoded, or if the
+/// decoded message differs from the original.
+pub fn assert_roundtrip<M: prost::Message + Default + PartialEq>(msg: &M) {
+    let id = id::write(msg, id::Field::CustomId).unwrap();
+    let decoded: M = id::read(&id).unwrap();
+    assert_eq!(*msg, decoded);
+}
+
+proptest! {
+    #[test]
+    fn test_component_roundtrip(msg in component()) {
+        assert_roundtrip(&msg);
+    }
+
+    #[test]
+    fn test_modal_roundtrip(msg in modal()) {
+        assert_roundtrip(&msg);
+    }
+}